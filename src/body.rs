@@ -0,0 +1,223 @@
+use crate::titan::TitanRequest;
+use crate::{Error, Response};
+use std::io::{self, Read};
+
+const DEFAULT_MAX_SIZE: u64 = 16 * 1024 * 1024;
+
+#[derive(Debug)]
+pub struct BodyReader<R> {
+    inner: R,
+    buffered: Vec<u8>,
+    pos: usize,
+    read: u64,
+    max_size: u64,
+    charset: Option<String>,
+}
+
+impl<R: Read> BodyReader<R> {
+    pub fn new(inner: R, res: &Response) -> Self {
+        Self {
+            inner,
+            buffered: Vec::new(),
+            pos: 0,
+            read: 0,
+            max_size: DEFAULT_MAX_SIZE,
+            charset: res.charset().map(String::from),
+        }
+    }
+
+    pub fn with_buffered(mut self, buffered: Vec<u8>) -> Self {
+        self.buffered = buffered;
+        self.pos = 0;
+        self
+    }
+
+    pub fn max_size(mut self, max_size: u64) -> Self {
+        self.max_size = max_size;
+        self
+    }
+
+    #[inline]
+    pub fn charset(&self) -> Option<&str> {
+        self.charset.as_deref()
+    }
+
+    #[inline]
+    pub fn bytes_read(&self) -> u64 {
+        self.read
+    }
+
+    #[inline]
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+impl<R: Read> Read for BodyReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = if self.pos < self.buffered.len() {
+            let n = (&self.buffered[self.pos..]).read(buf)?;
+            self.pos += n;
+            n
+        } else {
+            self.inner.read(buf)?
+        };
+
+        self.read += n as u64;
+        if self.read > self.max_size {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "response body exceeds maximum size",
+            ));
+        }
+        Ok(n)
+    }
+}
+
+#[derive(Debug)]
+pub struct TitanBodyReader<R> {
+    inner: R,
+    buffered: Vec<u8>,
+    pos: usize,
+    remaining: u64,
+}
+
+impl<R: Read> TitanBodyReader<R> {
+    pub fn new(inner: R, req: &TitanRequest) -> Result<Self, Error> {
+        Ok(Self {
+            inner,
+            buffered: Vec::new(),
+            pos: 0,
+            remaining: req.size.ok_or(Error::TitanParam)? as u64,
+        })
+    }
+
+    pub fn with_buffered(mut self, buffered: Vec<u8>) -> Self {
+        self.buffered = buffered;
+        self.pos = 0;
+        self
+    }
+
+    #[inline]
+    pub fn remaining(&self) -> u64 {
+        self.remaining
+    }
+
+    pub fn finish(self) -> io::Result<R> {
+        if self.remaining > 0 {
+            return Err(Error::BodyTooShort.into());
+        }
+        if self.pos < self.buffered.len() {
+            return Err(Error::BodyTooLong.into());
+        }
+        Ok(self.inner)
+    }
+
+    #[inline]
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+impl<R: Read> Read for TitanBodyReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let len = buf
+            .len()
+            .min(self.remaining.try_into().unwrap_or(usize::MAX));
+        if len == 0 {
+            return Ok(0);
+        }
+
+        let n = if self.pos < self.buffered.len() {
+            let n = (&self.buffered[self.pos..]).read(&mut buf[..len])?;
+            self.pos += n;
+            n
+        } else {
+            self.inner.read(&mut buf[..len])?
+        };
+        if n == 0 {
+            return Err(Error::BodyTooShort.into());
+        }
+
+        self.remaining -= n as u64;
+        Ok(n)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn response(meta: &str) -> Response {
+        let mut res = Response::new();
+        res.parse(format!("20 {}\r\n", meta).as_bytes()).unwrap();
+        res
+    }
+
+    #[test]
+    fn test_body_reader() {
+        let res = response("text/gemini");
+        let mut reader = BodyReader::new(&b" world"[..], &res).with_buffered(b"hello".to_vec());
+        assert_eq!(reader.charset(), Some("utf-8"));
+
+        let mut body = String::new();
+        reader.read_to_string(&mut body).unwrap();
+        assert_eq!(body, "hello world");
+        assert_eq!(reader.bytes_read(), 11);
+
+        let res = response("text/plain; charset=ISO-8859-1");
+        let reader = BodyReader::new(io::empty(), &res);
+        assert_eq!(reader.charset(), Some("ISO-8859-1"));
+
+        let res = response("image/png");
+        let reader = BodyReader::new(io::empty(), &res);
+        assert_eq!(reader.charset(), None);
+    }
+
+    #[test]
+    fn test_titan_body_reader() {
+        let mut req = TitanRequest::new();
+        req.parse(b"titan://example.com/a.txt;size=11\r\n").unwrap();
+
+        let mut reader = TitanBodyReader::new(&b" world"[..], &req)
+            .unwrap()
+            .with_buffered(b"hello".to_vec());
+        let mut body = String::new();
+        reader.read_to_string(&mut body).unwrap();
+        assert_eq!(body, "hello world");
+        assert_eq!(reader.remaining(), 0);
+        assert!(reader.finish().is_ok());
+
+        let mut reader = TitanBodyReader::new(&b"hello world!"[..], &req).unwrap();
+        assert_eq!(reader.read_to_end(&mut Vec::new()).unwrap(), 11);
+        assert_eq!(reader.finish().unwrap(), b"!");
+
+        let mut reader = TitanBodyReader::new(io::empty(), &req)
+            .unwrap()
+            .with_buffered(vec![0; 12]);
+        assert_eq!(reader.read_to_end(&mut Vec::new()).unwrap(), 11);
+        let err = reader.finish().unwrap_err();
+        assert_eq!(err.to_string(), "body longer than declared size");
+
+        let mut reader = TitanBodyReader::new(&b"hello"[..], &req).unwrap();
+        let err = reader.read_to_end(&mut Vec::new()).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+        assert_eq!(err.to_string(), "body shorter than declared size");
+        assert_eq!(reader.remaining(), 6);
+        assert!(reader.finish().is_err());
+
+        let req = TitanRequest::new();
+        assert!(matches!(
+            TitanBodyReader::new(io::empty(), &req),
+            Err(Error::TitanParam)
+        ));
+    }
+
+    #[test]
+    fn test_body_reader_max_size() {
+        let res = response("text/gemini");
+        let mut reader = BodyReader::new(&[0; 64][..], &res).max_size(10);
+        let err = reader.read_to_end(&mut Vec::new()).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+}
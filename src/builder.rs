@@ -0,0 +1,324 @@
+use crate::meta::{is_param_value, is_token, LanguageTag, MediaType, MetaString};
+use crate::Url;
+use crate::{check_meta, validate_meta, Error, Request, Response, StatusCode, URL_MAX_LENGTH};
+use alloc::string::{String, ToString};
+use core::{result, time::Duration};
+use percent_encoding::{utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
+
+const QUERY: &AsciiSet = &NON_ALPHANUMERIC
+    .remove(b'-')
+    .remove(b'.')
+    .remove(b'_')
+    .remove(b'~');
+
+#[derive(Clone, Debug)]
+pub struct RequestBuilder {
+    url: Url,
+    max_length: usize,
+}
+
+impl RequestBuilder {
+    pub fn new(base: Url) -> Self {
+        Self {
+            url: base,
+            max_length: URL_MAX_LENGTH,
+        }
+    }
+
+    pub fn input(mut self, input: &str) -> Self {
+        let query = utf8_percent_encode(input, QUERY).collect::<String>();
+        self.url.set_query(Some(&query));
+        self
+    }
+
+    pub fn max_length(mut self, max_length: usize) -> Self {
+        self.max_length = max_length;
+        self
+    }
+
+    pub fn build(mut self) -> result::Result<Request, Error> {
+        self.url.set_fragment(None);
+        if self.url.as_str().len() > self.max_length {
+            return Err(Error::UriTooLong);
+        }
+        Ok(Request {
+            url: Some(self.url),
+        })
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ResponseBuilder {
+    status: StatusCode,
+    meta: MetaString,
+}
+
+impl ResponseBuilder {
+    #[inline]
+    fn new(status: StatusCode, meta: impl Into<MetaString>) -> Self {
+        Self {
+            status,
+            meta: meta.into(),
+        }
+    }
+
+    #[inline]
+    pub fn input(prompt: &str) -> Self {
+        Self::new(StatusCode::Input, prompt)
+    }
+
+    #[inline]
+    pub fn sensitive_input(prompt: &str) -> Self {
+        Self::new(StatusCode::SensitiveInput, prompt)
+    }
+
+    #[inline]
+    pub fn success(media_type: &MediaType<'_>) -> Self {
+        Self::new(StatusCode::Success, media_type.to_string())
+    }
+
+    #[inline]
+    pub fn gemtext() -> Self {
+        Self::new(StatusCode::Success, "text/gemini")
+    }
+
+    #[inline]
+    pub fn redirect_temporary(url: &Url) -> Self {
+        Self::new(StatusCode::RedirectTemporary, url.as_str())
+    }
+
+    #[inline]
+    pub fn redirect_permanent(url: &Url) -> Self {
+        Self::new(StatusCode::RedirectPermanent, url.as_str())
+    }
+
+    #[inline]
+    pub fn slow_down(seconds: u64) -> Self {
+        Self::new(StatusCode::SlowDown, seconds.to_string())
+    }
+
+    pub fn retry_after(delay: Duration) -> Self {
+        let seconds = delay.as_secs() + u64::from(delay.subsec_nanos() > 0);
+        Self::slow_down(seconds)
+    }
+
+    pub fn failure(status: StatusCode, message: &str) -> Self {
+        Self::new(status, message)
+    }
+
+    #[inline]
+    pub fn temporary_failure(message: &str) -> Self {
+        Self::failure(StatusCode::TemporaryFailure, message)
+    }
+
+    #[inline]
+    pub fn not_found(message: &str) -> Self {
+        Self::failure(StatusCode::NotFound, message)
+    }
+
+    #[inline]
+    pub fn permanent_failure(message: &str) -> Self {
+        Self::failure(StatusCode::PermanentFailure, message)
+    }
+
+    #[inline]
+    pub fn bad_request(message: &str) -> Self {
+        Self::failure(StatusCode::BadRequest, message)
+    }
+
+    #[inline]
+    pub fn certificate_required(message: &str) -> Self {
+        Self::failure(StatusCode::CertificateRequired, message)
+    }
+
+    pub fn build(self) -> result::Result<Response, Error> {
+        match u16::from(self.status) {
+            30..=39 | 44 => validate_meta(Some(self.status), Some(&self.meta))?,
+            10..=19 | 40..=69 => {}
+            20..=29 => {
+                MediaType::parse(&self.meta)?;
+            }
+            _ => return Err(Error::Status),
+        }
+        check_meta(&self.meta)?;
+
+        Ok(Response {
+            status: Some(self.status),
+            meta: Some(self.meta),
+        })
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MetaBuilder {
+    meta: String,
+    error: Option<Error>,
+}
+
+impl MetaBuilder {
+    pub fn new(essence: &str) -> Self {
+        let valid = essence
+            .split_once('/')
+            .is_some_and(|(ty, subtype)| is_token(ty) && is_token(subtype));
+        Self {
+            meta: String::from(essence),
+            error: if valid { None } else { Some(Error::MediaType) },
+        }
+    }
+
+    #[inline]
+    pub fn charset(self, charset: &str) -> Self {
+        self.param("charset", charset)
+    }
+
+    pub fn lang(mut self, lang: &str) -> Self {
+        if lang.split(',').any(|tag| LanguageTag::parse(tag).is_err()) {
+            self.error.get_or_insert(Error::LanguageTag);
+        }
+        self.param("lang", lang)
+    }
+
+    pub fn param(mut self, key: &str, value: &str) -> Self {
+        if !is_token(key) || !is_param_value(value) {
+            self.error.get_or_insert(Error::MediaType);
+        }
+        self.meta.push_str("; ");
+        self.meta.push_str(key);
+        self.meta.push('=');
+        self.meta.push_str(value);
+        self
+    }
+
+    pub fn build(self) -> result::Result<String, Error> {
+        if let Some(err) = self.error {
+            return Err(err);
+        }
+        check_meta(&self.meta)?;
+        Ok(self.meta)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_response_builder() {
+        let encode = |builder: ResponseBuilder| {
+            let mut buf = alloc::vec::Vec::new();
+            builder.build()?.encode(&mut buf)?;
+            Ok::<_, Error>(buf)
+        };
+
+        let mt = MediaType::parse("text/gemini;lang=en").unwrap();
+        assert_eq!(
+            encode(ResponseBuilder::success(&mt)).unwrap(),
+            b"20 text/gemini; lang=en\r\n"
+        );
+        assert_eq!(
+            encode(ResponseBuilder::input("Name?")).unwrap(),
+            b"10 Name?\r\n"
+        );
+        let url = Url::parse("gemini://example.com/new").unwrap();
+        assert_eq!(
+            encode(ResponseBuilder::redirect_permanent(&url)).unwrap(),
+            b"31 gemini://example.com/new\r\n"
+        );
+        assert_eq!(
+            encode(ResponseBuilder::slow_down(30)).unwrap(),
+            b"44 30\r\n"
+        );
+        assert_eq!(
+            encode(ResponseBuilder::retry_after(Duration::from_millis(1500))).unwrap(),
+            b"44 2\r\n"
+        );
+        assert_eq!(
+            encode(ResponseBuilder::input("a\r\n20 text/html")),
+            Err(Error::ResponseHeader)
+        );
+        assert_eq!(
+            encode(ResponseBuilder::failure(StatusCode::Success, "oops")),
+            Err(Error::MediaType)
+        );
+        assert_eq!(
+            encode(ResponseBuilder::failure(
+                StatusCode::RedirectTemporary,
+                "/relative"
+            )),
+            Ok(b"30 /relative\r\n".to_vec())
+        );
+        assert_eq!(
+            encode(ResponseBuilder::failure(StatusCode::RedirectPermanent, "")),
+            Err(Error::Meta)
+        );
+        assert_eq!(
+            encode(ResponseBuilder::failure(StatusCode::SlowDown, "soon")),
+            Err(Error::Meta)
+        );
+        assert_eq!(
+            encode(ResponseBuilder::failure(StatusCode::SlowDown, "5")).unwrap(),
+            b"44 5\r\n"
+        );
+        assert_eq!(
+            encode(ResponseBuilder::failure(StatusCode::Other(7), "")),
+            Err(Error::Status)
+        );
+    }
+
+    #[test]
+    fn test_meta_builder() {
+        let meta = MetaBuilder::new("text/gemini")
+            .charset("utf-8")
+            .lang("en,fr-CA")
+            .param("x-experiment", "1")
+            .build()
+            .unwrap();
+        assert_eq!(
+            meta,
+            "text/gemini; charset=utf-8; lang=en,fr-CA; x-experiment=1"
+        );
+        assert!(MediaType::parse(&meta).is_ok());
+
+        assert_eq!(MetaBuilder::new("text").build(), Err(Error::MediaType));
+        assert_eq!(
+            MetaBuilder::new("text/plain").charset("utf-8;x=y").build(),
+            Err(Error::MediaType)
+        );
+        assert_eq!(
+            MetaBuilder::new("text/plain").lang("en,").build(),
+            Err(Error::LanguageTag)
+        );
+        let long = "a".repeat(1024);
+        assert_eq!(
+            MetaBuilder::new("text/plain").param("x", &long).build(),
+            Err(Error::MetaTooLong)
+        );
+    }
+
+    #[test]
+    fn test_request_builder() {
+        let base = Url::parse("gemini://example.com/search?old#frag").unwrap();
+        let req = RequestBuilder::new(base.clone())
+            .input("rust & gemini/2?=#+ü")
+            .build()
+            .unwrap();
+        assert_eq!(
+            req.url.unwrap().as_str(),
+            "gemini://example.com/search?rust%20%26%20gemini%2F2%3F%3D%23%2B%C3%BC"
+        );
+
+        let req = RequestBuilder::new(base.clone()).build().unwrap();
+        assert_eq!(req.url.unwrap().as_str(), "gemini://example.com/search?old");
+
+        let input = "a".repeat(URL_MAX_LENGTH);
+        assert_eq!(
+            RequestBuilder::new(base.clone()).input(&input).build(),
+            Err(Error::UriTooLong)
+        );
+        assert!(RequestBuilder::new(base)
+            .input(&input)
+            .max_length(2048)
+            .build()
+            .is_ok());
+    }
+}
@@ -0,0 +1,461 @@
+use crate::{
+    CertificateRequirement, Error, Request, Response, ResponseRef, Status, StatusClass, StatusCode,
+    Url, DEFAULT_META,
+};
+use alloc::{vec, vec::Vec};
+use core::{mem, time::Duration};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Event {
+    Header(Response),
+    Body(Vec<u8>),
+    End,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum State {
+    SendRequest,
+    ReadHeader,
+    ReadBody,
+    Discard,
+    Closed,
+}
+
+#[derive(Debug)]
+pub struct Connection {
+    state: State,
+    transmit: Vec<u8>,
+    buf: Vec<u8>,
+}
+
+impl Connection {
+    pub fn new(req: &Request) -> Result<Self, Error> {
+        let mut transmit = Vec::new();
+        req.encode(&mut transmit)?;
+        Ok(Self {
+            state: State::SendRequest,
+            transmit,
+            buf: Vec::new(),
+        })
+    }
+
+    pub fn poll_transmit(&mut self) -> Option<Vec<u8>> {
+        match self.state {
+            State::SendRequest => {
+                self.state = State::ReadHeader;
+                Some(mem::take(&mut self.transmit))
+            }
+            _ => None,
+        }
+    }
+
+    pub fn feed(&mut self, data: &[u8]) -> Result<Vec<Event>, Error> {
+        let mut events = Vec::new();
+        match self.state {
+            State::SendRequest => return Err(Error::ConnectionState),
+            State::ReadHeader => {
+                self.buf.extend_from_slice(data);
+                let mut res = ResponseRef::new();
+                let end = match res.parse(&self.buf)? {
+                    Status::Complete(end) => end,
+                    Status::Partial(_) => return Ok(events),
+                };
+
+                let success = res.status.is_some_and(StatusCode::is_success);
+                events.push(Event::Header(res.into()));
+
+                self.transmit.clear();
+                if success {
+                    self.state = State::ReadBody;
+                    if end < self.buf.len() {
+                        events.push(Event::Body(self.buf[end..].to_vec()));
+                    }
+                } else {
+                    self.state = State::Discard;
+                }
+                self.buf = Vec::new();
+            }
+            State::ReadBody => {
+                if !data.is_empty() {
+                    events.push(Event::Body(data.to_vec()));
+                }
+            }
+            State::Discard | State::Closed => {}
+        }
+        Ok(events)
+    }
+
+    pub fn finish(&mut self) -> Result<Vec<Event>, Error> {
+        match self.state {
+            State::SendRequest | State::ReadHeader => {
+                self.state = State::Closed;
+                Err(Error::ResponseHeader)
+            }
+            State::ReadBody | State::Discard => {
+                self.state = State::Closed;
+                Ok(vec![Event::End])
+            }
+            State::Closed => Ok(Vec::new()),
+        }
+    }
+
+    #[inline]
+    pub fn is_closed(&self) -> bool {
+        self.state == State::Closed
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ResponseAction<'a> {
+    PromptUser {
+        prompt: &'a str,
+        sensitive: bool,
+    },
+    ReadBody {
+        media_type: &'a str,
+    },
+    FollowRedirect {
+        url: Url,
+        permanent: bool,
+    },
+    RetryLater(Option<Duration>),
+    ReportError {
+        status: StatusCode,
+        message: &'a str,
+    },
+    PresentCertificate {
+        requirement: CertificateRequirement,
+        message: &'a str,
+    },
+    TooManyRedirects,
+    Invalid(Error),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Policy {
+    follow_redirects: bool,
+    allow_cross_scheme_redirects: bool,
+    max_redirects: usize,
+}
+
+impl Default for Policy {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Policy {
+    #[inline]
+    pub fn new() -> Self {
+        Self {
+            follow_redirects: true,
+            allow_cross_scheme_redirects: false,
+            max_redirects: 5,
+        }
+    }
+
+    pub fn follow_redirects(&mut self, value: bool) -> &mut Self {
+        self.follow_redirects = value;
+        self
+    }
+
+    pub fn allow_cross_scheme_redirects(&mut self, value: bool) -> &mut Self {
+        self.allow_cross_scheme_redirects = value;
+        self
+    }
+
+    pub fn max_redirects(&mut self, value: usize) -> &mut Self {
+        self.max_redirects = value;
+        self
+    }
+
+    pub fn action<'a>(
+        &self,
+        res: &'a Response,
+        base: &Url,
+        redirects: usize,
+    ) -> ResponseAction<'a> {
+        let Some(status) = res.status else {
+            return ResponseAction::Invalid(Error::Status);
+        };
+        let meta = res.meta.as_deref().unwrap_or("");
+
+        match status.class() {
+            Some(StatusClass::Input) => ResponseAction::PromptUser {
+                prompt: meta,
+                sensitive: status == StatusCode::SensitiveInput,
+            },
+            Some(StatusClass::Success) => ResponseAction::ReadBody {
+                media_type: if meta.is_empty() { DEFAULT_META } else { meta },
+            },
+            Some(StatusClass::Redirect) if !self.follow_redirects => ResponseAction::ReportError {
+                status,
+                message: meta,
+            },
+            Some(StatusClass::Redirect) if redirects >= self.max_redirects => {
+                ResponseAction::TooManyRedirects
+            }
+            Some(StatusClass::Redirect) => {
+                match res.redirect_target_with(base, self.allow_cross_scheme_redirects) {
+                    Ok(url) => ResponseAction::FollowRedirect {
+                        url,
+                        permanent: status == StatusCode::RedirectPermanent,
+                    },
+                    Err(err) => ResponseAction::Invalid(err),
+                }
+            }
+            Some(StatusClass::ClientCertificateRequired) => ResponseAction::PresentCertificate {
+                requirement: status
+                    .certificate_requirement()
+                    .unwrap_or(CertificateRequirement::Required),
+                message: meta,
+            },
+            _ if status == StatusCode::SlowDown => ResponseAction::RetryLater(res.retry_after()),
+            Some(_) => ResponseAction::ReportError {
+                status,
+                message: meta,
+            },
+            None => ResponseAction::Invalid(Error::Status),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RedirectTracker {
+    visited: Vec<Url>,
+    max_depth: usize,
+    allow_cross_scheme: bool,
+}
+
+impl RedirectTracker {
+    pub fn new(mut start: Url) -> Self {
+        start.set_fragment(None);
+        Self {
+            visited: vec![start],
+            max_depth: 5,
+            allow_cross_scheme: false,
+        }
+    }
+
+    pub fn max_depth(&mut self, value: usize) -> &mut Self {
+        self.max_depth = value;
+        self
+    }
+
+    pub fn allow_cross_scheme(&mut self, value: bool) -> &mut Self {
+        self.allow_cross_scheme = value;
+        self
+    }
+
+    pub fn follow(&mut self, res: &Response) -> Result<&Url, Error> {
+        let target = res.redirect_target(self.current())?;
+        self.push(target)
+    }
+
+    pub fn push(&mut self, mut target: Url) -> Result<&Url, Error> {
+        if self.depth() >= self.max_depth {
+            return Err(Error::TooManyRedirects);
+        }
+        if !self.allow_cross_scheme && target.scheme() != self.current().scheme() {
+            return Err(Error::CrossSchemeRedirect);
+        }
+        target.set_fragment(None);
+        if self.visited.contains(&target) {
+            return Err(Error::RedirectLoop);
+        }
+        self.visited.push(target);
+        Ok(self.current())
+    }
+
+    #[inline]
+    pub fn current(&self) -> &Url {
+        &self.visited[self.visited.len() - 1]
+    }
+
+    #[inline]
+    pub fn depth(&self) -> usize {
+        self.visited.len() - 1
+    }
+
+    #[inline]
+    pub fn visited(&self) -> &[Url] {
+        &self.visited
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn connection() -> Connection {
+        let req = Request {
+            url: Some(Url::parse("gemini://example.com/").unwrap()),
+        };
+        Connection::new(&req).unwrap()
+    }
+
+    #[test]
+    fn test_transaction() {
+        let mut conn = connection();
+        assert_eq!(
+            conn.poll_transmit(),
+            Some(b"gemini://example.com/\r\n".to_vec())
+        );
+        assert_eq!(conn.poll_transmit(), None);
+
+        assert_eq!(conn.feed(b"20 text/ge"), Ok(vec![]));
+        let events = conn.feed(b"mini\r\n# Hi").unwrap();
+        assert_eq!(
+            events,
+            vec![
+                Event::Header(Response {
+                    status: Some(StatusCode::Success),
+                    meta: Some("text/gemini".into()),
+                }),
+                Event::Body(b"# Hi".to_vec()),
+            ]
+        );
+        assert_eq!(conn.feed(b"\n"), Ok(vec![Event::Body(b"\n".to_vec())]));
+        assert_eq!(conn.finish(), Ok(vec![Event::End]));
+        assert!(conn.is_closed());
+    }
+
+    #[test]
+    fn test_failure_and_truncation() {
+        let mut conn = connection();
+        conn.poll_transmit();
+        let events = conn.feed(b"51 Not found\r\nignored").unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!(conn.feed(b"more"), Ok(vec![]));
+        assert_eq!(conn.finish(), Ok(vec![Event::End]));
+
+        let mut conn = connection();
+        conn.poll_transmit();
+        conn.feed(b"20 text").unwrap();
+        assert_eq!(conn.finish(), Err(Error::ResponseHeader));
+    }
+
+    #[test]
+    fn test_feed_before_transmit() {
+        let mut conn = connection();
+        assert_eq!(
+            conn.feed(b"20 text/gemini\r\n"),
+            Err(Error::ConnectionState)
+        );
+        assert!(conn.poll_transmit().is_some());
+        assert_eq!(conn.feed(b"20 text/gemini\r\n").unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_response_action() {
+        let base = Url::parse("gemini://example.com/a").unwrap();
+        let policy = Policy::new();
+        let response = |status, meta: &str| Response {
+            status: Some(status),
+            meta: Some(meta.into()),
+        };
+
+        let res = response(StatusCode::SensitiveInput, "Password");
+        assert_eq!(
+            policy.action(&res, &base, 0),
+            ResponseAction::PromptUser {
+                prompt: "Password",
+                sensitive: true
+            }
+        );
+        let res = response(StatusCode::Success, "");
+        assert_eq!(
+            policy.action(&res, &base, 0),
+            ResponseAction::ReadBody {
+                media_type: "text/gemini; charset=utf-8"
+            }
+        );
+        let res = response(StatusCode::RedirectPermanent, "b");
+        assert_eq!(
+            policy.action(&res, &base, 0),
+            ResponseAction::FollowRedirect {
+                url: Url::parse("gemini://example.com/b").unwrap(),
+                permanent: true
+            }
+        );
+        assert_eq!(
+            policy.action(&res, &base, 5),
+            ResponseAction::TooManyRedirects
+        );
+        let res = response(StatusCode::RedirectTemporary, "https://example.com/");
+        assert_eq!(
+            policy.action(&res, &base, 0),
+            ResponseAction::Invalid(Error::CrossSchemeRedirect)
+        );
+        let res = response(StatusCode::SlowDown, "30");
+        assert_eq!(
+            policy.action(&res, &base, 0),
+            ResponseAction::RetryLater(Some(Duration::from_secs(30)))
+        );
+        let res = response(StatusCode::CertificateNotValid, "Expired");
+        assert_eq!(
+            policy.action(&res, &base, 0),
+            ResponseAction::PresentCertificate {
+                requirement: CertificateRequirement::NotValid,
+                message: "Expired"
+            }
+        );
+        let res = response(StatusCode::NotFound, "Gone fishing");
+        assert_eq!(
+            policy.action(&res, &base, 0),
+            ResponseAction::ReportError {
+                status: StatusCode::NotFound,
+                message: "Gone fishing"
+            }
+        );
+        assert_eq!(
+            policy.action(&Response::new(), &base, 0),
+            ResponseAction::Invalid(Error::Status)
+        );
+
+        let mut policy = Policy::new();
+        policy.follow_redirects(false);
+        let res = response(StatusCode::RedirectTemporary, "b");
+        assert!(matches!(
+            policy.action(&res, &base, 0),
+            ResponseAction::ReportError { .. }
+        ));
+    }
+
+    #[test]
+    fn test_redirect_tracker() {
+        let redirect = |meta: &str| Response {
+            status: Some(StatusCode::RedirectTemporary),
+            meta: Some(meta.into()),
+        };
+        let start = Url::parse("gemini://example.com/a").unwrap();
+        let mut tracker = RedirectTracker::new(start.clone());
+        assert_eq!(
+            tracker.follow(&redirect("b#frag")).unwrap().as_str(),
+            "gemini://example.com/b"
+        );
+        assert_eq!(tracker.depth(), 1);
+        assert_eq!(tracker.follow(&redirect("/a")), Err(Error::RedirectLoop));
+        assert_eq!(
+            tracker.follow(&redirect("https://example.com/")),
+            Err(Error::CrossSchemeRedirect)
+        );
+        assert_eq!(tracker.follow(&Response::new()), Err(Error::NotRedirect));
+
+        tracker.allow_cross_scheme(true).max_depth(2);
+        tracker.follow(&redirect("https://example.com/")).unwrap();
+        assert_eq!(
+            tracker.follow(&redirect("/c")),
+            Err(Error::TooManyRedirects)
+        );
+        assert_eq!(tracker.visited().len(), 3);
+        assert_eq!(tracker.visited()[0], start);
+
+        let mut tracker = RedirectTracker::new(Url::parse("gemini://a/#x").unwrap());
+        assert_eq!(
+            tracker.push(Url::parse("gemini://a/").unwrap()),
+            Err(Error::RedirectLoop)
+        );
+    }
+}
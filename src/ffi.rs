@@ -0,0 +1,359 @@
+use crate::gemtext::{Line, Lines};
+use crate::{Error, RequestRef, ResponseRef, Status};
+use alloc::boxed::Box;
+use core::{ffi::c_int, ptr, slice, str};
+
+pub const GEMINI_OK: c_int = 0;
+pub const GEMINI_PARTIAL: c_int = 1;
+
+pub const GEMINI_ERR_NULL: c_int = -1;
+pub const GEMINI_ERR_NEW_LINE: c_int = -2;
+pub const GEMINI_ERR_UTF8: c_int = -3;
+pub const GEMINI_ERR_URL: c_int = -4;
+pub const GEMINI_ERR_RESPONSE_HEADER: c_int = -5;
+pub const GEMINI_ERR_STATUS: c_int = -6;
+pub const GEMINI_ERR_EMPTY_REQUEST: c_int = -7;
+pub const GEMINI_ERR_URI_TOO_LONG: c_int = -8;
+pub const GEMINI_ERR_BYTE_ORDER_MARK: c_int = -9;
+pub const GEMINI_ERR_BARE_LINE_FEED: c_int = -10;
+pub const GEMINI_ERR_INVALID_REQUEST: c_int = -11;
+pub const GEMINI_ERR_META: c_int = -12;
+pub const GEMINI_ERR_LIMIT: c_int = -13;
+pub const GEMINI_ERR_META_TOO_LONG: c_int = -14;
+pub const GEMINI_ERR_MISSING_SPACE: c_int = -15;
+pub const GEMINI_ERR_LONE_CR: c_int = -16;
+pub const GEMINI_ERR_OTHER: c_int = -99;
+
+pub const GEMINI_LINE_TEXT: c_int = 0;
+pub const GEMINI_LINE_LINK: c_int = 1;
+pub const GEMINI_LINE_HEADING: c_int = 2;
+pub const GEMINI_LINE_LIST_ITEM: c_int = 3;
+pub const GEMINI_LINE_QUOTE: c_int = 4;
+pub const GEMINI_LINE_PREFORMAT_TOGGLE: c_int = 5;
+pub const GEMINI_LINE_PREFORMATTED: c_int = 6;
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug)]
+pub struct GeminiStr {
+    pub ptr: *const u8,
+    pub len: usize,
+}
+
+impl GeminiStr {
+    const NULL: GeminiStr = GeminiStr {
+        ptr: ptr::null(),
+        len: 0,
+    };
+
+    #[inline]
+    fn new(s: Option<&str>) -> Self {
+        s.map_or(Self::NULL, |s| Self {
+            ptr: s.as_ptr(),
+            len: s.len(),
+        })
+    }
+}
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug)]
+pub struct GeminiRequest {
+    pub url: GeminiStr,
+    pub scheme: GeminiStr,
+    pub host: GeminiStr,
+    pub port: c_int,
+    pub path: GeminiStr,
+    pub query: GeminiStr,
+}
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug)]
+pub struct GeminiResponse {
+    pub status: c_int,
+    pub meta: GeminiStr,
+}
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug)]
+pub struct GeminiLine {
+    pub kind: c_int,
+    pub level: c_int,
+    pub text: GeminiStr,
+    pub url: GeminiStr,
+}
+
+pub struct GeminiLines {
+    lines: Lines<'static>,
+}
+
+fn error_code(err: &Error) -> c_int {
+    match err {
+        Error::NewLine => GEMINI_ERR_NEW_LINE,
+        Error::InvalidUtf8(_) => GEMINI_ERR_UTF8,
+        Error::ParseUrl(_)
+        | Error::Scheme
+        | Error::UserInfo
+        | Error::MissingHost
+        | Error::Fragment => GEMINI_ERR_URL,
+        Error::ResponseHeader => GEMINI_ERR_RESPONSE_HEADER,
+        Error::Status => GEMINI_ERR_STATUS,
+        Error::EmptyRequest => GEMINI_ERR_EMPTY_REQUEST,
+        Error::UriTooLong => GEMINI_ERR_URI_TOO_LONG,
+        Error::ByteOrderMark => GEMINI_ERR_BYTE_ORDER_MARK,
+        Error::BareLineFeed => GEMINI_ERR_BARE_LINE_FEED,
+        Error::RequestLine => GEMINI_ERR_INVALID_REQUEST,
+        Error::MediaType | Error::Meta | Error::LanguageTag => GEMINI_ERR_META,
+        Error::LimitExceeded => GEMINI_ERR_LIMIT,
+        Error::MetaTooLong => GEMINI_ERR_META_TOO_LONG,
+        Error::MissingSpaceAfterStatus => GEMINI_ERR_MISSING_SPACE,
+        Error::LoneCarriageReturn => GEMINI_ERR_LONE_CR,
+        _ => GEMINI_ERR_OTHER,
+    }
+}
+
+#[inline]
+unsafe fn input<'a>(buf: *const u8, len: usize) -> Option<&'a [u8]> {
+    match (buf.is_null(), len) {
+        (_, 0) => Some(&[]),
+        (true, _) => None,
+        (false, len) => Some(slice::from_raw_parts(buf, len)),
+    }
+}
+
+/// # Safety
+///
+/// `buf` must be null or point to `len` readable bytes, and `out` must be null or
+/// valid for writes. `consumed` may be null; otherwise it must be valid for writes.
+/// The strings written to `out` borrow from `buf`, so `buf` must outlive every use
+/// of them.
+#[no_mangle]
+pub unsafe extern "C" fn gemini_parse_request(
+    buf: *const u8,
+    len: usize,
+    out: *mut GeminiRequest,
+    consumed: *mut usize,
+) -> c_int {
+    let Some(buf) = input(buf, len) else {
+        return GEMINI_ERR_NULL;
+    };
+    if out.is_null() {
+        return GEMINI_ERR_NULL;
+    }
+
+    let mut req = RequestRef::new();
+    match req.parse(buf) {
+        Ok(Status::Complete(n)) => {
+            *out = GeminiRequest {
+                url: GeminiStr::new(req.line),
+                scheme: GeminiStr::new(req.scheme()),
+                host: GeminiStr::new(req.host()),
+                port: req.port().map_or(-1, c_int::from),
+                path: GeminiStr::new(req.path()),
+                query: GeminiStr::new(req.query()),
+            };
+            if !consumed.is_null() {
+                *consumed = n;
+            }
+            GEMINI_OK
+        }
+        Ok(Status::Partial(_)) => GEMINI_PARTIAL,
+        Err(err) => error_code(&err),
+    }
+}
+
+/// # Safety
+///
+/// `buf` must be null or point to `len` readable bytes, and `out` must be null or
+/// valid for writes. `consumed` may be null; otherwise it must be valid for writes.
+/// The strings written to `out` borrow from `buf`, so `buf` must outlive every use
+/// of them.
+#[no_mangle]
+pub unsafe extern "C" fn gemini_parse_response(
+    buf: *const u8,
+    len: usize,
+    out: *mut GeminiResponse,
+    consumed: *mut usize,
+) -> c_int {
+    let Some(buf) = input(buf, len) else {
+        return GEMINI_ERR_NULL;
+    };
+    if out.is_null() {
+        return GEMINI_ERR_NULL;
+    }
+
+    let mut res = ResponseRef::new();
+    match res.parse(buf) {
+        Ok(Status::Complete(n)) => {
+            *out = GeminiResponse {
+                status: res.status.map_or(0, |status| c_int::from(status.as_u16())),
+                meta: GeminiStr::new(res.meta),
+            };
+            if !consumed.is_null() {
+                *consumed = n;
+            }
+            GEMINI_OK
+        }
+        Ok(Status::Partial(_)) => GEMINI_PARTIAL,
+        Err(err) => error_code(&err),
+    }
+}
+
+/// # Safety
+///
+/// `buf` must be null or point to `len` readable bytes. The returned iterator
+/// borrows `buf` without copying it: the caller must keep `buf` alive and
+/// unmodified until the iterator has been passed to `gemini_lines_free`, and
+/// must not use any string returned by `gemini_lines_next` after that point.
+#[no_mangle]
+pub unsafe extern "C" fn gemini_lines_new(buf: *const u8, len: usize) -> *mut GeminiLines {
+    let Some(buf) = input(buf, len) else {
+        return ptr::null_mut();
+    };
+    match str::from_utf8(buf) {
+        Ok(body) => Box::into_raw(Box::new(GeminiLines {
+            lines: Lines::new(body),
+        })),
+        Err(_) => ptr::null_mut(),
+    }
+}
+
+/// # Safety
+///
+/// `lines` must be null or a live pointer returned by `gemini_lines_new`, and
+/// `out` must be null or valid for writes. The strings written to `out` borrow
+/// from the buffer passed to `gemini_lines_new`.
+#[no_mangle]
+pub unsafe extern "C" fn gemini_lines_next(lines: *mut GeminiLines, out: *mut GeminiLine) -> c_int {
+    let (Some(lines), false) = (lines.as_mut(), out.is_null()) else {
+        return GEMINI_ERR_NULL;
+    };
+    let Some(line) = lines.lines.next() else {
+        return 0;
+    };
+
+    let (kind, level, text, url) = match line {
+        Line::Text(text) => (GEMINI_LINE_TEXT, 0, Some(text), None),
+        Line::Link { url, label } => (GEMINI_LINE_LINK, 0, label, Some(url)),
+        Line::Heading { level, text } => (GEMINI_LINE_HEADING, level, Some(text), None),
+        Line::ListItem(text) => (GEMINI_LINE_LIST_ITEM, 0, Some(text), None),
+        Line::Quote(text) => (GEMINI_LINE_QUOTE, 0, Some(text), None),
+        Line::PreformatToggle { alt } => (GEMINI_LINE_PREFORMAT_TOGGLE, 0, alt, None),
+        Line::Preformatted(text) => (GEMINI_LINE_PREFORMATTED, 0, Some(text), None),
+    };
+    *out = GeminiLine {
+        kind,
+        level: c_int::from(level),
+        text: GeminiStr::new(text),
+        url: GeminiStr::new(url),
+    };
+    1
+}
+
+/// # Safety
+///
+/// `lines` must be null or a pointer returned by `gemini_lines_new` that has not
+/// already been freed. It must not be used after this call.
+#[no_mangle]
+pub unsafe extern "C" fn gemini_lines_free(lines: *mut GeminiLines) {
+    if !lines.is_null() {
+        drop(Box::from_raw(lines));
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    unsafe fn as_str<'a>(s: GeminiStr) -> &'a str {
+        str::from_utf8(slice::from_raw_parts(s.ptr, s.len)).unwrap()
+    }
+
+    #[test]
+    fn test_parse_request() {
+        let buf = b"gemini://example.com:1966/a?q\r\nrest";
+        let mut req = GeminiRequest {
+            url: GeminiStr::NULL,
+            scheme: GeminiStr::NULL,
+            host: GeminiStr::NULL,
+            port: 0,
+            path: GeminiStr::NULL,
+            query: GeminiStr::NULL,
+        };
+        let mut consumed = 0;
+        unsafe {
+            let code = gemini_parse_request(buf.as_ptr(), buf.len(), &mut req, &mut consumed);
+            assert_eq!(code, GEMINI_OK);
+            assert_eq!(consumed, 31);
+            assert_eq!(as_str(req.url), "gemini://example.com:1966/a?q");
+            assert_eq!(as_str(req.host), "example.com");
+            assert_eq!(req.port, 1966);
+            assert_eq!(as_str(req.path), "/a");
+            assert_eq!(as_str(req.query), "q");
+
+            assert_eq!(
+                gemini_parse_request(buf.as_ptr(), 10, &mut req, ptr::null_mut()),
+                GEMINI_PARTIAL
+            );
+            assert_eq!(
+                gemini_parse_request(b"\n".as_ptr(), 1, &mut req, ptr::null_mut()),
+                GEMINI_PARTIAL
+            );
+            assert_eq!(
+                gemini_parse_request(ptr::null(), 5, &mut req, ptr::null_mut()),
+                GEMINI_ERR_NULL
+            );
+        }
+    }
+
+    #[test]
+    fn test_parse_response() {
+        let mut res = GeminiResponse {
+            status: 0,
+            meta: GeminiStr::NULL,
+        };
+        unsafe {
+            let buf = b"20 text/gemini\r\n";
+            let code = gemini_parse_response(buf.as_ptr(), buf.len(), &mut res, ptr::null_mut());
+            assert_eq!(code, GEMINI_OK);
+            assert_eq!(res.status, 20);
+            assert_eq!(as_str(res.meta), "text/gemini");
+
+            let buf = b"2x text/gemini\r\n";
+            let code = gemini_parse_response(buf.as_ptr(), buf.len(), &mut res, ptr::null_mut());
+            assert_eq!(code, GEMINI_ERR_STATUS);
+        }
+    }
+
+    #[test]
+    fn test_lines() {
+        let buf = b"## Hi\n=> /a label\n```\ncode\n";
+        let mut line = GeminiLine {
+            kind: -1,
+            level: 0,
+            text: GeminiStr::NULL,
+            url: GeminiStr::NULL,
+        };
+        unsafe {
+            let lines = gemini_lines_new(buf.as_ptr(), buf.len());
+            assert!(!lines.is_null());
+
+            assert_eq!(gemini_lines_next(lines, &mut line), 1);
+            assert_eq!((line.kind, line.level), (GEMINI_LINE_HEADING, 2));
+            assert_eq!(as_str(line.text), "Hi");
+
+            assert_eq!(gemini_lines_next(lines, &mut line), 1);
+            assert_eq!(line.kind, GEMINI_LINE_LINK);
+            assert_eq!((as_str(line.url), as_str(line.text)), ("/a", "label"));
+
+            assert_eq!(gemini_lines_next(lines, &mut line), 1);
+            assert_eq!(line.kind, GEMINI_LINE_PREFORMAT_TOGGLE);
+            assert!(line.text.ptr.is_null());
+
+            assert_eq!(gemini_lines_next(lines, &mut line), 1);
+            assert_eq!(line.kind, GEMINI_LINE_PREFORMATTED);
+            assert_eq!(gemini_lines_next(lines, &mut line), 0);
+            gemini_lines_free(lines);
+
+            assert!(gemini_lines_new(b"\xff".as_ptr(), 1).is_null());
+        }
+    }
+}
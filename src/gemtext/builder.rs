@@ -0,0 +1,170 @@
+use alloc::string::String;
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Builder {
+    out: String,
+}
+
+impl Builder {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn text(&mut self, text: &str) -> &mut Self {
+        for line in text.lines() {
+            if needs_escape(line) {
+                self.out.push(' ');
+            }
+            self.out.push_str(line);
+            self.out.push('\n');
+        }
+        if text.is_empty() {
+            self.out.push('\n');
+        }
+        self
+    }
+
+    #[inline]
+    pub fn blank(&mut self) -> &mut Self {
+        self.out.push('\n');
+        self
+    }
+
+    pub fn heading(&mut self, level: u8, text: &str) -> &mut Self {
+        for _ in 0..level.clamp(1, 3) {
+            self.out.push('#');
+        }
+        self.out.push(' ');
+        self.push_single_line(text);
+        self
+    }
+
+    pub fn link(&mut self, url: &str, label: Option<&str>) -> &mut Self {
+        self.out.push_str("=> ");
+        for c in url.trim().chars() {
+            if c.is_whitespace() {
+                let mut buf = [0; 4];
+                for b in c.encode_utf8(&mut buf).bytes() {
+                    self.out.push_str(&alloc::format!("%{:02X}", b));
+                }
+            } else {
+                self.out.push(c);
+            }
+        }
+        match label.map(str::trim) {
+            Some(label) if !label.is_empty() => {
+                self.out.push(' ');
+                self.push_single_line(label);
+            }
+            _ => self.out.push('\n'),
+        }
+        self
+    }
+
+    pub fn list_item(&mut self, text: &str) -> &mut Self {
+        self.out.push_str("* ");
+        self.push_single_line(text);
+        self
+    }
+
+    pub fn quote(&mut self, text: &str) -> &mut Self {
+        for line in text.lines() {
+            self.out.push_str("> ");
+            self.out.push_str(line);
+            self.out.push('\n');
+        }
+        self
+    }
+
+    pub fn pre(&mut self, alt: Option<&str>, text: &str) -> &mut Self {
+        self.out.push_str("```");
+        self.push_single_line(alt.unwrap_or(""));
+        for line in text.lines() {
+            if line.starts_with("```") {
+                self.out.push(' ');
+            }
+            self.out.push_str(line);
+            self.out.push('\n');
+        }
+        self.out.push_str("```\n");
+        self
+    }
+
+    #[inline]
+    pub fn as_str(&self) -> &str {
+        &self.out
+    }
+
+    #[inline]
+    pub fn build(self) -> String {
+        self.out
+    }
+
+    fn push_single_line(&mut self, text: &str) {
+        for c in text.chars() {
+            match c {
+                '\r' | '\n' => self.out.push(' '),
+                c => self.out.push(c),
+            }
+        }
+        self.out.push('\n');
+    }
+}
+
+#[inline]
+fn needs_escape(line: &str) -> bool {
+    line.starts_with("=>")
+        || line.starts_with('#')
+        || line.starts_with("* ")
+        || line.starts_with('>')
+        || line.starts_with("```")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::gemtext::{Line, Lines};
+
+    #[test]
+    fn test_builder() {
+        let mut builder = Builder::new();
+        builder
+            .heading(1, "Title\nline")
+            .text("# not a heading\nplain")
+            .link("/a b", Some("Label"))
+            .link("gemini://a.com", None)
+            .list_item("item")
+            .quote("q1\nq2")
+            .pre(Some("sh"), "```\necho")
+            .heading(9, "Deep");
+        let gmi = builder.build();
+        assert_eq!(
+            gmi,
+            "# Title line\n\
+             \x20# not a heading\n\
+             plain\n\
+             => /a%20b Label\n\
+             => gemini://a.com\n\
+             * item\n\
+             > q1\n\
+             > q2\n\
+             ```sh\n\
+             \x20```\n\
+             echo\n\
+             ```\n\
+             ### Deep\n"
+        );
+
+        let lines: alloc::vec::Vec<_> = Lines::new(&gmi).collect();
+        assert_eq!(lines[1], Line::Text(" # not a heading"));
+        assert_eq!(
+            lines[3],
+            Line::Link {
+                url: "/a%20b",
+                label: Some("Label"),
+            }
+        );
+        assert_eq!(lines[9], Line::Preformatted(" ```"));
+    }
+}
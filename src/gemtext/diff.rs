@@ -0,0 +1,201 @@
+use super::{Block, Document};
+use alloc::{format, string::String, vec, vec::Vec};
+use core::{mem, ops::Range};
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Change<'a> {
+    Added {
+        new: usize,
+        block: &'a Block,
+    },
+    Removed {
+        old: usize,
+        block: &'a Block,
+    },
+    Changed {
+        old: usize,
+        new: usize,
+        from: &'a Block,
+        to: &'a Block,
+    },
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Hunk<'a> {
+    pub old: Range<usize>,
+    pub new: Range<usize>,
+    pub changes: Vec<Change<'a>>,
+}
+
+pub fn diff<'a>(old: &'a Document, new: &'a Document) -> Vec<Hunk<'a>> {
+    let (a, b) = (&old.blocks, &new.blocks);
+    let mut lcs = vec![vec![0usize; b.len() + 1]; a.len() + 1];
+    for i in (0..a.len()).rev() {
+        for j in (0..b.len()).rev() {
+            lcs[i][j] = if a[i] == b[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut hunks = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < a.len() || j < b.len() {
+        if i < a.len() && j < b.len() && a[i] == b[j] {
+            i += 1;
+            j += 1;
+            continue;
+        }
+
+        let (old_start, new_start) = (i, j);
+        while i < a.len() || j < b.len() {
+            if i < a.len() && j < b.len() && a[i] == b[j] {
+                break;
+            }
+            if j == b.len() || (i < a.len() && lcs[i + 1][j] >= lcs[i][j + 1]) {
+                i += 1;
+            } else {
+                j += 1;
+            }
+        }
+        hunks.push(Hunk {
+            old: old_start..i,
+            new: new_start..j,
+            changes: pair(a, b, old_start..i, new_start..j),
+        });
+    }
+    hunks
+}
+
+fn pair<'a>(
+    a: &'a [Block],
+    b: &'a [Block],
+    old: Range<usize>,
+    new: Range<usize>,
+) -> Vec<Change<'a>> {
+    let mut changes = Vec::new();
+    let (mut old, mut new) = (old.peekable(), new.peekable());
+    loop {
+        match (old.peek().copied(), new.peek().copied()) {
+            (Some(i), Some(j)) if mem::discriminant(&a[i]) == mem::discriminant(&b[j]) => {
+                changes.push(Change::Changed {
+                    old: i,
+                    new: j,
+                    from: &a[i],
+                    to: &b[j],
+                });
+                old.next();
+                new.next();
+            }
+            (Some(i), _) => {
+                changes.push(Change::Removed {
+                    old: i,
+                    block: &a[i],
+                });
+                old.next();
+            }
+            (None, Some(j)) => {
+                changes.push(Change::Added {
+                    new: j,
+                    block: &b[j],
+                });
+                new.next();
+            }
+            (None, None) => return changes,
+        }
+    }
+}
+
+pub fn render_diff(hunks: &[Hunk<'_>]) -> String {
+    let mut out = String::new();
+    if hunks.is_empty() {
+        return out;
+    }
+
+    out.push_str("```diff\n");
+    for hunk in hunks {
+        out.push_str(&format!(
+            "@@ -{},{} +{},{} @@\n",
+            hunk.old.start + 1,
+            hunk.old.len(),
+            hunk.new.start + 1,
+            hunk.new.len()
+        ));
+        for change in &hunk.changes {
+            match change {
+                Change::Added { block, .. } => prefixed('+', block, &mut out),
+                Change::Removed { block, .. } => prefixed('-', block, &mut out),
+                Change::Changed { from, to, .. } => {
+                    prefixed('-', from, &mut out);
+                    prefixed('+', to, &mut out);
+                }
+            }
+        }
+    }
+    out.push_str("```\n");
+    out
+}
+
+fn prefixed(marker: char, block: &Block, out: &mut String) {
+    for line in format!("{}", block).lines() {
+        out.push(marker);
+        out.push_str(line);
+        out.push('\n');
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_diff() {
+        let old_body = "# Title\ntext\n=> /a A\n* one\n* two\nend\n";
+        let new_body = "# New title\ntext\n* one\n* three\nadded\nend\n";
+        let (old, old_spans) = Document::parse_spanned(old_body);
+        let (new, new_spans) = Document::parse_spanned(new_body);
+        let hunks = diff(&old, &new);
+
+        assert_eq!(hunks.len(), 2);
+        assert_eq!((hunks[0].old.clone(), hunks[0].new.clone()), (0..1, 0..1));
+        assert!(matches!(
+            hunks[0].changes[..],
+            [Change::Changed { old: 0, new: 0, .. }]
+        ));
+        assert_eq!(&new_body[new_spans[0].range()], "# New title");
+
+        assert_eq!((hunks[1].old.clone(), hunks[1].new.clone()), (2..4, 2..4));
+        assert!(matches!(
+            hunks[1].changes[..],
+            [
+                Change::Removed { old: 2, .. },
+                Change::Changed { old: 3, new: 2, .. },
+                Change::Added { new: 3, .. },
+            ]
+        ));
+        assert_eq!(&old_body[old_spans[2].range()], "=> /a A");
+
+        assert_eq!(
+            render_diff(&hunks),
+            "```diff\n\
+             @@ -1,1 +1,1 @@\n\
+             -# Title\n\
+             +# New title\n\
+             @@ -3,2 +3,2 @@\n\
+             -=> /a A\n\
+             -* one\n\
+             -* two\n\
+             +* one\n\
+             +* three\n\
+             +added\n\
+             ```\n"
+        );
+
+        assert!(diff(&old, &old).is_empty());
+        assert_eq!(render_diff(&[]), "");
+        let empty = Document::new();
+        assert_eq!(diff(&empty, &new)[0].new, 0..5);
+    }
+}
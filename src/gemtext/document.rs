@@ -0,0 +1,284 @@
+use super::{Line, Lines, SourceSpan};
+use alloc::{string::String, vec, vec::Vec};
+use core::fmt;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Block {
+    Text(String),
+    Link {
+        url: String,
+        label: Option<String>,
+    },
+    Heading {
+        level: u8,
+        text: String,
+    },
+    List(Vec<String>),
+    Quote(Vec<String>),
+    Preformatted {
+        alt: Option<String>,
+        lines: Vec<String>,
+    },
+    Table {
+        rows: Vec<Vec<String>>,
+        header: bool,
+        source: Vec<Block>,
+    },
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Document {
+    pub blocks: Vec<Block>,
+}
+
+impl Document {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn parse(body: &str) -> Self {
+        Self::from_lines(Lines::new(body))
+    }
+
+    pub fn from_lines<'a, I>(lines: I) -> Self
+    where
+        I: IntoIterator<Item = Line<'a>>,
+    {
+        let lines = lines.into_iter().map(|line| (SourceSpan::default(), line));
+        Self::build(lines, None)
+    }
+
+    pub fn parse_spanned(body: &str) -> (Self, Vec<SourceSpan>) {
+        let mut spans = Vec::new();
+        let doc = Self::build(Lines::new(body).spanned(), Some(&mut spans));
+        (doc, spans)
+    }
+
+    fn build<'a, I>(lines: I, mut spans: Option<&mut Vec<SourceSpan>>) -> Self
+    where
+        I: Iterator<Item = (SourceSpan, Line<'a>)>,
+    {
+        let mut blocks = Vec::new();
+        let mut pre = false;
+
+        for (span, line) in lines {
+            let grouped = push_line(&mut blocks, &mut pre, line);
+            if let Some(spans) = spans.as_deref_mut() {
+                push_span(spans, span, grouped);
+            }
+        }
+
+        Self { blocks }
+    }
+
+    pub fn links(&self) -> impl Iterator<Item = (&str, Option<&str>)> {
+        self.blocks.iter().filter_map(|block| match block {
+            Block::Link { url, label } => Some((url.as_str(), label.as_deref())),
+            _ => None,
+        })
+    }
+
+    pub fn headings(&self) -> impl Iterator<Item = (u8, &str)> {
+        self.blocks.iter().filter_map(|block| match block {
+            Block::Heading { level, text } => Some((*level, text.as_str())),
+            _ => None,
+        })
+    }
+}
+
+pub(super) fn push_line(blocks: &mut Vec<Block>, pre: &mut bool, line: Line<'_>) -> bool {
+    match (line, blocks.last_mut()) {
+        (Line::PreformatToggle { alt }, _) => {
+            *pre = !*pre;
+            if *pre {
+                blocks.push(Block::Preformatted {
+                    alt: alt.map(String::from),
+                    lines: Vec::new(),
+                });
+            }
+            !*pre
+        }
+        (Line::Preformatted(text), Some(Block::Preformatted { lines, .. })) if *pre => {
+            lines.push(String::from(text));
+            true
+        }
+        (Line::ListItem(text), Some(Block::List(items))) => {
+            items.push(String::from(text));
+            true
+        }
+        (Line::Quote(text), Some(Block::Quote(lines))) => {
+            lines.push(String::from(text));
+            true
+        }
+        (line, _) => {
+            blocks.push(Block::from(line));
+            false
+        }
+    }
+}
+
+#[inline]
+pub(super) fn push_span(spans: &mut Vec<SourceSpan>, span: SourceSpan, grouped: bool) {
+    match spans.last_mut() {
+        Some(last) if grouped => last.end = span.end,
+        _ => spans.push(span),
+    }
+}
+
+impl<'a> From<Line<'a>> for Block {
+    fn from(line: Line<'a>) -> Self {
+        match line {
+            Line::Text(text) => Block::Text(String::from(text)),
+            Line::Link { url, label } => Block::Link {
+                url: String::from(url),
+                label: label.map(String::from),
+            },
+            Line::Heading { level, text } => Block::Heading {
+                level,
+                text: String::from(text),
+            },
+            Line::ListItem(text) => Block::List(vec![String::from(text)]),
+            Line::Quote(text) => Block::Quote(vec![String::from(text)]),
+            Line::PreformatToggle { alt } => Block::Preformatted {
+                alt: alt.map(String::from),
+                lines: Vec::new(),
+            },
+            Line::Preformatted(text) => Block::Preformatted {
+                alt: None,
+                lines: vec![String::from(text)],
+            },
+        }
+    }
+}
+
+impl fmt::Display for Block {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Block::Text(text) => writeln!(f, "{}", text),
+            Block::Link { url, label: None } => writeln!(f, "=> {}", url),
+            Block::Link {
+                url,
+                label: Some(label),
+            } => writeln!(f, "=> {} {}", url, label),
+            Block::Heading { level, text } => {
+                writeln!(f, "{} {}", "#".repeat(*level as usize), text)
+            }
+            Block::List(items) => items.iter().try_for_each(|item| writeln!(f, "* {}", item)),
+            Block::Quote(lines) => lines.iter().try_for_each(|line| writeln!(f, "> {}", line)),
+            Block::Preformatted { alt, lines } => {
+                writeln!(f, "```{}", alt.as_deref().unwrap_or(""))?;
+                lines.iter().try_for_each(|line| writeln!(f, "{}", line))?;
+                writeln!(f, "```")
+            }
+            Block::Table { source, .. } => source.iter().try_for_each(|block| block.fmt(f)),
+        }
+    }
+}
+
+impl fmt::Display for Document {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.blocks.iter().try_for_each(|block| block.fmt(f))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const BODY: &str = "# Title\n\
+        * one\n\
+        * two\n\
+        > a\n\
+        > b\n\
+        => gemini://a.com A\n\
+        ```alt\n\
+        * not a list\n\
+        ```\n\
+        ## Sub\n";
+
+    #[test]
+    fn test_document_parse() {
+        let doc = Document::parse(BODY);
+        assert_eq!(
+            doc.blocks,
+            vec![
+                Block::Heading {
+                    level: 1,
+                    text: "Title".to_string(),
+                },
+                Block::List(vec!["one".to_string(), "two".to_string()]),
+                Block::Quote(vec!["a".to_string(), "b".to_string()]),
+                Block::Link {
+                    url: "gemini://a.com".to_string(),
+                    label: Some("A".to_string()),
+                },
+                Block::Preformatted {
+                    alt: Some("alt".to_string()),
+                    lines: vec!["* not a list".to_string()],
+                },
+                Block::Heading {
+                    level: 2,
+                    text: "Sub".to_string(),
+                },
+            ]
+        );
+        assert_eq!(
+            doc.links().collect::<Vec<_>>(),
+            vec![("gemini://a.com", Some("A"))]
+        );
+        assert_eq!(
+            doc.headings().collect::<Vec<_>>(),
+            vec![(1, "Title"), (2, "Sub")]
+        );
+    }
+
+    #[test]
+    fn test_block_grouping() {
+        let body = "* a\n* b\n\n* c\n> q\n>r\n* d\n";
+        let doc = Document::parse(body);
+        assert_eq!(
+            doc.blocks,
+            vec![
+                Block::List(vec!["a".to_string(), "b".to_string()]),
+                Block::Text(String::new()),
+                Block::List(vec!["c".to_string()]),
+                Block::Quote(vec!["q".to_string(), "r".to_string()]),
+                Block::List(vec!["d".to_string()]),
+            ]
+        );
+        assert_eq!(Lines::new(body).count(), 7);
+    }
+
+    #[test]
+    fn test_parse_spanned() {
+        let (doc, spans) = Document::parse_spanned(BODY);
+        assert_eq!(doc, Document::parse(BODY));
+        assert_eq!(spans.len(), doc.blocks.len());
+
+        let texts: Vec<_> = spans.iter().map(|s| &BODY[s.range()]).collect();
+        assert_eq!(
+            texts,
+            vec![
+                "# Title",
+                "* one\n* two",
+                "> a\n> b",
+                "=> gemini://a.com A",
+                "```alt\n* not a list\n```",
+                "## Sub",
+            ]
+        );
+        let lines: Vec<_> = spans.iter().map(|s| s.line).collect();
+        assert_eq!(lines, vec![1, 2, 4, 6, 7, 10]);
+    }
+
+    #[test]
+    fn test_document_to_string() {
+        let doc = Document::parse(BODY);
+        assert_eq!(doc.to_string(), BODY);
+        assert_eq!(Document::parse(&doc.to_string()), doc);
+
+        let doc = Document::parse("```\nunterminated");
+        assert_eq!(doc.to_string(), "```\nunterminated\n```\n");
+    }
+}
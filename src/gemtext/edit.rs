@@ -0,0 +1,155 @@
+use super::document::{push_line, push_span};
+use super::{Block, Document, Lines, SourceSpan};
+use alloc::{string::String, vec::Vec};
+use core::ops::Range;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EditableDocument {
+    text: String,
+    doc: Document,
+    spans: Vec<SourceSpan>,
+}
+
+impl EditableDocument {
+    pub fn new(text: String) -> Self {
+        let (doc, spans) = Document::parse_spanned(&text);
+        Self { text, doc, spans }
+    }
+
+    #[inline]
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    #[inline]
+    pub fn document(&self) -> &Document {
+        &self.doc
+    }
+
+    #[inline]
+    pub fn spans(&self) -> &[SourceSpan] {
+        &self.spans
+    }
+
+    #[inline]
+    pub fn into_document(self) -> Document {
+        self.doc
+    }
+
+    pub fn edit(&mut self, range: Range<usize>, replacement: &str) -> Option<Range<usize>> {
+        if range.start > range.end
+            || !self.text.is_char_boundary(range.start)
+            || !self.text.is_char_boundary(range.end)
+        {
+            return None;
+        }
+
+        let old = &self.spans;
+        let first = old
+            .iter()
+            .position(|span| span.end >= range.start)
+            .unwrap_or(old.len())
+            .saturating_sub(1);
+        let (start, line) = match old.get(first) {
+            Some(span) if span.start <= range.start => (span.start, span.line),
+            _ => (0, 1),
+        };
+        let mut next = first + old[first..].partition_point(|span| span.start < range.end);
+
+        self.text.replace_range(range.clone(), replacement);
+        let shift = |pos: usize| (pos + replacement.len()).wrapping_sub(range.len());
+
+        let mut blocks = Vec::new();
+        let mut spans = Vec::new();
+        let mut pre = false;
+        let mut lines = Lines::at(&self.text, start, line);
+        loop {
+            while next < old.len() && shift(old[next].start) < lines.pos {
+                next += 1;
+            }
+            let synced = next < old.len()
+                && shift(old[next].start) == lines.pos
+                && !lines.is_preformatted()
+                && !merges(blocks.last(), &self.doc.blocks[next]);
+            if synced {
+                break;
+            }
+            match lines.next_spanned() {
+                Some((span, line)) => {
+                    let grouped = push_line(&mut blocks, &mut pre, line);
+                    push_span(&mut spans, span, grouped);
+                }
+                None => break,
+            }
+        }
+
+        if next < old.len() {
+            let line_delta = (lines.line + 1).wrapping_sub(old[next].line);
+            for span in &mut self.spans[next..] {
+                span.start = shift(span.start);
+                span.end = shift(span.end);
+                span.line = span.line.wrapping_add(line_delta);
+            }
+        }
+
+        let changed = first..first + blocks.len();
+        self.doc.blocks.splice(first..next, blocks);
+        self.spans.splice(first..next, spans);
+        Some(changed)
+    }
+}
+
+#[inline]
+fn merges(prev: Option<&Block>, next: &Block) -> bool {
+    matches!(
+        (prev, next),
+        (Some(Block::List(_)), Block::List(_)) | (Some(Block::Quote(_)), Block::Quote(_))
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn check(text: &str, range: Range<usize>, replacement: &str) -> Range<usize> {
+        let mut doc = EditableDocument::new(String::from(text));
+        let changed = doc.edit(range, replacement).unwrap();
+        let fresh = EditableDocument::new(String::from(doc.text()));
+        assert_eq!(doc, fresh, "{:?}", doc.text());
+        changed
+    }
+
+    #[test]
+    fn test_edit() {
+        let text = "# Title\n* a\n* b\ntext\n```\ncode\n```\n> q\nend\n";
+        assert_eq!(check(text, 2..7, "Heading"), 0..1);
+        assert_eq!(check(text, 18..20, "* c"), 1..3);
+        assert_eq!(check(text, 0..0, "```\n"), 0..3);
+        assert_eq!(check(text, 33..37, ""), 2..4);
+        assert_eq!(check(text, 8..9, ""), 0..3);
+        check(text, 0..text.len(), "");
+        check(text, text.len()..text.len(), "more\n* x");
+        check("", 0..0, "# a\n");
+        check("a\nb", 1..2, "");
+
+        let mut doc = EditableDocument::new(String::from("caf\u{e9}\n"));
+        assert_eq!(doc.edit(0..7, ""), None);
+        assert_eq!(doc.edit(4..5, ""), None);
+        let (start, end) = (2, 1);
+        assert_eq!(doc.edit(start..end, ""), None);
+        assert_eq!(doc.text(), "caf\u{e9}\n");
+
+        for text in [text, "\u{feff}* a\r\n```\r\nc\r\n> q\r\n"] {
+            for start in 0..=text.len() {
+                for end in start..=text.len() {
+                    if !text.is_char_boundary(start) || !text.is_char_boundary(end) {
+                        continue;
+                    }
+                    for replacement in ["", "\n", "* ", "```\n", "> x\ny"] {
+                        check(text, start..end, replacement);
+                    }
+                }
+            }
+        }
+    }
+}
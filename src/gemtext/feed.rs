@@ -0,0 +1,270 @@
+use super::render::html::escape;
+use super::{Block, Document};
+use crate::Url;
+use alloc::{format, string::String, vec::Vec};
+use core::{fmt, str::FromStr};
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Date {
+    pub year: u16,
+    pub month: u8,
+    pub day: u8,
+}
+
+impl Date {
+    pub fn parse(s: &str) -> Option<Self> {
+        let b = s.as_bytes();
+        if b.len() != 10 || b[4] != b'-' || b[7] != b'-' {
+            return None;
+        }
+        let year = s[..4].parse().ok()?;
+        let month = s[5..7].parse().ok()?;
+        let day = s[8..10].parse().ok()?;
+        if !s
+            .bytes()
+            .enumerate()
+            .all(|(i, b)| i == 4 || i == 7 || b.is_ascii_digit())
+        {
+            return None;
+        }
+        if !(1..=12).contains(&month) || day == 0 || day > days_in_month(year, month) {
+            return None;
+        }
+        Some(Self { year, month, day })
+    }
+}
+
+impl FromStr for Date {
+    type Err = ();
+
+    #[inline]
+    fn from_str(s: &str) -> Result<Self, ()> {
+        Self::parse(s).ok_or(())
+    }
+}
+
+impl fmt::Display for Date {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FeedEntry {
+    pub date: Date,
+    pub url: String,
+    pub title: String,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Feed {
+    pub title: Option<String>,
+    pub subtitle: Option<String>,
+    pub entries: Vec<FeedEntry>,
+}
+
+impl Feed {
+    pub fn parse(body: &str) -> Self {
+        Self::from_document(&Document::parse(body))
+    }
+
+    pub fn from_document(doc: &Document) -> Self {
+        let mut feed = Feed::default();
+        let mut headings = doc.blocks.iter().filter(|b| !is_blank(b));
+
+        while let Some(block) = headings.next() {
+            if let Block::Heading { level: 1, text } = block {
+                feed.title = Some(text.clone());
+                if let Some(Block::Heading { level: 2, text }) = headings.next() {
+                    feed.subtitle = Some(text.clone());
+                }
+                break;
+            }
+        }
+
+        feed.entries = doc
+            .blocks
+            .iter()
+            .filter_map(|block| match block {
+                Block::Link {
+                    url,
+                    label: Some(label),
+                } => entry(url, label),
+                _ => None,
+            })
+            .collect();
+        feed
+    }
+
+    pub fn to_atom(&self, base: &Url) -> String {
+        let mut out = String::from("<?xml version=\"1.0\" encoding=\"utf-8\"?>\n");
+        out.push_str("<feed xmlns=\"http://www.w3.org/2005/Atom\">\n");
+
+        out.push_str("  <title>");
+        escape(self.title.as_deref().unwrap_or(base.as_str()), &mut out);
+        out.push_str("</title>\n");
+        if let Some(subtitle) = &self.subtitle {
+            out.push_str("  <subtitle>");
+            escape(subtitle, &mut out);
+            out.push_str("</subtitle>\n");
+        }
+        push_element(&mut out, "  ", "id", base.as_str());
+        push_link(&mut out, "  ", "alternate", base.as_str());
+        let updated = self.entries.iter().map(|e| e.date).max();
+        push_element(&mut out, "  ", "updated", &timestamp(updated));
+        out.push_str("  <author><name>");
+        escape(base.host_str().unwrap_or(base.as_str()), &mut out);
+        out.push_str("</name></author>\n");
+
+        for entry in &self.entries {
+            let url = base.join(&entry.url);
+            let url = url.as_ref().map_or(entry.url.as_str(), Url::as_str);
+            out.push_str("  <entry>\n");
+            out.push_str("    <title>");
+            escape(&entry.title, &mut out);
+            out.push_str("</title>\n");
+            push_element(&mut out, "    ", "id", url);
+            push_link(&mut out, "    ", "alternate", url);
+            push_element(&mut out, "    ", "updated", &timestamp(Some(entry.date)));
+            out.push_str("  </entry>\n");
+        }
+
+        out.push_str("</feed>\n");
+        out
+    }
+}
+
+fn push_element(out: &mut String, indent: &str, name: &str, text: &str) {
+    out.push_str(indent);
+    out.push_str(&format!("<{}>", name));
+    escape(text, out);
+    out.push_str(&format!("</{}>\n", name));
+}
+
+fn push_link(out: &mut String, indent: &str, rel: &str, href: &str) {
+    out.push_str(indent);
+    out.push_str(&format!("<link rel=\"{}\" href=\"", rel));
+    escape(href, out);
+    out.push_str("\"/>\n");
+}
+
+fn timestamp(date: Option<Date>) -> String {
+    match date {
+        Some(date) => format!("{}T00:00:00Z", date),
+        None => String::from("1970-01-01T00:00:00Z"),
+    }
+}
+
+fn entry(url: &str, label: &str) -> Option<FeedEntry> {
+    let date = Date::parse(label.get(..10)?)?;
+    let title = label[10..]
+        .trim_start_matches([' ', '\t', '-', ':'])
+        .trim_end();
+    Some(FeedEntry {
+        date,
+        url: String::from(url),
+        title: String::from(title),
+    })
+}
+
+#[inline]
+fn is_blank(block: &Block) -> bool {
+    matches!(block, Block::Text(text) if text.trim().is_empty())
+}
+
+fn days_in_month(year: u16, month: u8) -> u8 {
+    match month {
+        2 if year.is_multiple_of(4) && (!year.is_multiple_of(100) || year.is_multiple_of(400)) => {
+            29
+        }
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_date() {
+        let date = Date::parse("2024-02-29").unwrap();
+        assert_eq!((date.year, date.month, date.day), (2024, 2, 29));
+        assert_eq!(date.to_string(), "2024-02-29");
+        assert_eq!("2023-02-29".parse::<Date>(), Err(()));
+        assert_eq!(Date::parse("2023-13-01"), None);
+        assert_eq!(Date::parse("2023-1-01"), None);
+        assert_eq!(Date::parse("+023-01-01"), None);
+        assert!(Date::parse("2023-01-02") > Date::parse("2023-01-01"));
+    }
+
+    #[test]
+    fn test_feed_parse() {
+        let feed = Feed::parse(
+            "\n# My gemlog\n## Thoughts\n\
+             => /about About me\n\
+             => 2024-01-05-post.gmi 2024-01-05 - First post\n\
+             => /second 2024-03-01 Second: more\n\
+             => /bad 2024-13-01 Not a date\n",
+        );
+        assert_eq!(feed.title.as_deref(), Some("My gemlog"));
+        assert_eq!(feed.subtitle.as_deref(), Some("Thoughts"));
+        assert_eq!(
+            feed.entries,
+            vec![
+                FeedEntry {
+                    date: Date::parse("2024-01-05").unwrap(),
+                    url: "2024-01-05-post.gmi".to_string(),
+                    title: "First post".to_string(),
+                },
+                FeedEntry {
+                    date: Date::parse("2024-03-01").unwrap(),
+                    url: "/second".to_string(),
+                    title: "Second: more".to_string(),
+                },
+            ]
+        );
+
+        assert_eq!(
+            Feed::parse("## Sub\n# Late"),
+            Feed {
+                title: Some("Late".to_string()),
+                ..Feed::default()
+            }
+        );
+    }
+
+    #[test]
+    fn test_to_atom() {
+        let feed = Feed::parse(
+            "# Log & notes\n\
+             => a.gmi 2024-01-05 First <post>\n\
+             => gemini://other.org/b 2024-03-01 Second\n",
+        );
+        let base = Url::parse("gemini://example.com/log/").unwrap();
+        assert_eq!(
+            feed.to_atom(&base),
+            "<?xml version=\"1.0\" encoding=\"utf-8\"?>\n\
+             <feed xmlns=\"http://www.w3.org/2005/Atom\">\n\
+             \x20 <title>Log &amp; notes</title>\n\
+             \x20 <id>gemini://example.com/log/</id>\n\
+             \x20 <link rel=\"alternate\" href=\"gemini://example.com/log/\"/>\n\
+             \x20 <updated>2024-03-01T00:00:00Z</updated>\n\
+             \x20 <author><name>example.com</name></author>\n\
+             \x20 <entry>\n\
+             \x20   <title>First &lt;post&gt;</title>\n\
+             \x20   <id>gemini://example.com/log/a.gmi</id>\n\
+             \x20   <link rel=\"alternate\" href=\"gemini://example.com/log/a.gmi\"/>\n\
+             \x20   <updated>2024-01-05T00:00:00Z</updated>\n\
+             \x20 </entry>\n\
+             \x20 <entry>\n\
+             \x20   <title>Second</title>\n\
+             \x20   <id>gemini://other.org/b</id>\n\
+             \x20   <link rel=\"alternate\" href=\"gemini://other.org/b\"/>\n\
+             \x20   <updated>2024-03-01T00:00:00Z</updated>\n\
+             \x20 </entry>\n\
+             </feed>\n"
+        );
+    }
+}
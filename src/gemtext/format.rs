@@ -0,0 +1,96 @@
+use super::{Line, Lines};
+use alloc::string::String;
+
+pub fn format(body: &str) -> String {
+    let mut out = String::with_capacity(body.len());
+    let mut blank = false;
+
+    for line in Lines::new(body) {
+        if let Line::Text(text) = line {
+            let text = text.trim_end();
+            if text.is_empty() {
+                blank = !out.is_empty();
+                continue;
+            }
+        }
+        if blank {
+            out.push('\n');
+            blank = false;
+        }
+
+        match line {
+            Line::Text(text) => out.push_str(text.trim_end()),
+            Line::Link { url, label } => {
+                out.push_str("=>");
+                if !url.is_empty() {
+                    out.push(' ');
+                    out.push_str(url);
+                }
+                if let Some(label) = label {
+                    out.push(' ');
+                    out.push_str(label);
+                }
+            }
+            Line::Heading { level, text } => {
+                for _ in 0..level {
+                    out.push('#');
+                }
+                push_content(&mut out, text);
+            }
+            Line::ListItem(text) => {
+                out.push_str("* ");
+                out.push_str(text.trim());
+            }
+            Line::Quote(text) => {
+                out.push('>');
+                push_content(&mut out, text);
+            }
+            Line::PreformatToggle { alt } => {
+                out.push_str("```");
+                out.push_str(alt.unwrap_or(""));
+            }
+            Line::Preformatted(text) => out.push_str(text),
+        }
+        out.push('\n');
+    }
+
+    out
+}
+
+#[inline]
+fn push_content(out: &mut String, text: &str) {
+    let text = text.trim();
+    if !text.is_empty() {
+        out.push(' ');
+        out.push_str(text);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_format() {
+        let body = "\n\n#Title  \r\n\n\n\n=>   gemini://a.com    A   site \n=>/b\n\
+            *   item\n>quoted\n>\n##  \n\n```  rust \n  keep   \n\n\n```\ntext   \n\n\n";
+        let formatted = "#Title\n\
+            \n\
+            => gemini://a.com A   site\n\
+            => /b\n\
+            * item\n\
+            > quoted\n\
+            >\n\
+            ##\n\
+            \n\
+            ```rust\n  keep   \n\n\n```\n\
+            text\n";
+        let formatted = formatted.replacen("#Title", "# Title", 1);
+        assert_eq!(format(body), formatted);
+        assert_eq!(format(&formatted), formatted);
+        assert_eq!(format(""), "");
+        assert_eq!(format("\n\n"), "");
+        assert_eq!(format("a"), "a\n");
+        assert_eq!(format("*   "), "* \n");
+    }
+}
@@ -0,0 +1,449 @@
+use super::Builder;
+use alloc::{string::String, vec::Vec};
+
+const SKIP: &[&str] = &["head", "script", "style", "template", "noscript"];
+
+const BLOCKS: &[&str] = &[
+    "address",
+    "article",
+    "aside",
+    "blockquote",
+    "dd",
+    "div",
+    "dl",
+    "dt",
+    "figcaption",
+    "figure",
+    "footer",
+    "form",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "header",
+    "hr",
+    "li",
+    "main",
+    "nav",
+    "ol",
+    "p",
+    "section",
+    "table",
+    "td",
+    "th",
+    "tr",
+    "ul",
+];
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum Kind {
+    Paragraph,
+    Heading(u8),
+    ListItem,
+    Quote,
+}
+
+struct Converter {
+    out: Builder,
+    kind: Kind,
+    buf: String,
+    links: Vec<(String, String)>,
+    anchor: Option<(String, usize)>,
+    pre: Option<(Option<String>, String)>,
+    quotes: usize,
+    last: Option<Kind>,
+}
+
+pub fn from_html(html: &str) -> String {
+    let mut conv = Converter {
+        out: Builder::new(),
+        kind: Kind::Paragraph,
+        buf: String::new(),
+        links: Vec::new(),
+        anchor: None,
+        pre: None,
+        quotes: 0,
+        last: None,
+    };
+
+    let mut rest = html;
+    while !rest.is_empty() {
+        let Some(lt) = rest.find('<') else {
+            conv.text(rest);
+            break;
+        };
+        conv.text(&rest[..lt]);
+        rest = &rest[lt..];
+
+        if let Some(comment) = rest.strip_prefix("<!--") {
+            rest = comment.find("-->").map_or("", |end| &comment[end + 3..]);
+            continue;
+        }
+        if rest.starts_with("<!") || rest.starts_with("<?") {
+            rest = rest.find('>').map_or("", |end| &rest[end + 1..]);
+            continue;
+        }
+
+        let Some((closing, name, attrs, len)) = tag(rest) else {
+            conv.text("<");
+            rest = &rest[1..];
+            continue;
+        };
+        rest = &rest[len..];
+
+        if closing {
+            conv.close(&name);
+        } else if SKIP.contains(&name.as_str()) {
+            rest = skip_element(rest, &name);
+        } else {
+            conv.open(&name, attrs);
+        }
+    }
+
+    if conv.pre.is_some() {
+        conv.close("pre");
+    }
+    conv.flush();
+    conv.out.build()
+}
+
+impl Converter {
+    fn text(&mut self, text: &str) {
+        let mut decoded = String::new();
+        decode(text, &mut decoded);
+
+        if let Some((_, pre)) = &mut self.pre {
+            pre.push_str(&decoded);
+            return;
+        }
+        for c in decoded.chars() {
+            if c.is_whitespace() {
+                if !self.buf.is_empty() && !self.buf.ends_with([' ', '\n']) {
+                    self.buf.push(' ');
+                }
+            } else {
+                self.buf.push(c);
+            }
+        }
+    }
+
+    fn open(&mut self, name: &str, attrs: &str) {
+        if let Some((alt, _)) = &mut self.pre {
+            if name == "code" && alt.is_none() {
+                *alt = attr(attrs, "class").and_then(|class| language(&class).map(String::from));
+            }
+            return;
+        }
+
+        match name {
+            "a" => {
+                if let Some(href) = attr(attrs, "href").filter(|href| is_followable(href)) {
+                    self.anchor = Some((href, self.buf.len()));
+                }
+            }
+            "img" => {
+                if let Some(src) = attr(attrs, "src") {
+                    let alt = attr(attrs, "alt").unwrap_or_default();
+                    self.links.push((src, alt));
+                }
+            }
+            "br" => {
+                let len = self.buf.trim_end_matches(' ').len();
+                self.buf.truncate(len);
+                if !self.buf.is_empty() {
+                    self.buf.push('\n');
+                }
+            }
+            "pre" => {
+                self.flush();
+                self.pre = Some((None, String::new()));
+            }
+            _ if BLOCKS.contains(&name) => {
+                self.flush();
+                if name == "blockquote" {
+                    self.quotes += 1;
+                }
+                self.kind = match name.as_bytes() {
+                    [b'h', level @ b'1'..=b'6'] => Kind::Heading(level - b'0'),
+                    b"li" => Kind::ListItem,
+                    _ => self.default_kind(),
+                };
+            }
+            _ => {}
+        }
+    }
+
+    fn close(&mut self, name: &str) {
+        if self.pre.is_some() {
+            if let Some((alt, text)) = self.pre.take_if(|_| name == "pre") {
+                let text = text.strip_prefix('\n').unwrap_or(&text);
+                self.separate(None);
+                self.out.pre(alt.as_deref(), text.trim_end_matches('\n'));
+            }
+            return;
+        }
+
+        match name {
+            "a" => {
+                if let Some((href, start)) = self.anchor.take() {
+                    let label = self.buf.get(start..).unwrap_or("").trim();
+                    let label = String::from(label);
+                    self.links.push((href, label));
+                }
+            }
+            _ if BLOCKS.contains(&name) => {
+                self.flush();
+                if name == "blockquote" {
+                    self.quotes = self.quotes.saturating_sub(1);
+                }
+                self.kind = self.default_kind();
+            }
+            _ => {}
+        }
+    }
+
+    fn default_kind(&self) -> Kind {
+        if self.quotes > 0 {
+            Kind::Quote
+        } else {
+            Kind::Paragraph
+        }
+    }
+
+    fn separate(&mut self, kind: Option<Kind>) {
+        let grouped = matches!(
+            (self.last, kind),
+            (Some(Kind::ListItem), Some(Kind::ListItem))
+        );
+        if self.last.is_some() && !grouped {
+            self.out.blank();
+        }
+        self.last = Some(kind.unwrap_or(Kind::Paragraph));
+    }
+
+    fn flush(&mut self) {
+        if let Some((href, _)) = self.anchor.take() {
+            self.links.push((href, String::new()));
+        }
+        let text = String::from(self.buf.trim());
+        self.buf.clear();
+        if text.is_empty() && self.links.is_empty() {
+            return;
+        }
+
+        self.separate(Some(self.kind));
+        let only_link = matches!(&self.links[..], [(_, label)] if *label == text);
+        match self.kind {
+            _ if text.is_empty() => {}
+            Kind::Paragraph if only_link => {}
+            Kind::Paragraph => {
+                self.out.text(&text);
+            }
+            Kind::Heading(level) => {
+                self.out.heading(level.min(3), &text);
+            }
+            Kind::ListItem => {
+                self.out.list_item(&text);
+            }
+            Kind::Quote => {
+                self.out.quote(&text);
+            }
+        }
+        for (url, label) in self.links.drain(..) {
+            let label = Some(label.as_str()).filter(|l| !l.is_empty() && *l != url);
+            self.out.link(&url, label);
+        }
+    }
+}
+
+fn tag(s: &str) -> Option<(bool, String, &str, usize)> {
+    let (closing, start) = match s.as_bytes().get(1)? {
+        b'/' => (true, 2),
+        b if b.is_ascii_alphabetic() => (false, 1),
+        _ => return None,
+    };
+    let name_len = s[start..]
+        .bytes()
+        .take_while(|b| b.is_ascii_alphanumeric())
+        .count();
+    let name = s[start..start + name_len].to_ascii_lowercase();
+
+    let mut quote = None;
+    for (i, b) in s.bytes().enumerate().skip(start + name_len) {
+        match (quote, b) {
+            (Some(q), _) if b == q => quote = None,
+            (Some(_), _) => {}
+            (None, b'"' | b'\'') => quote = Some(b),
+            (None, b'>') => {
+                let attrs = s[start + name_len..i].trim_end_matches('/');
+                return Some((closing, name, attrs, i + 1));
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+fn skip_element<'a>(rest: &'a str, name: &str) -> &'a str {
+    let mut search = rest;
+    while let Some(i) = search.find("</") {
+        let after = &search[i + 2..];
+        if after.len() >= name.len() && after[..name.len()].eq_ignore_ascii_case(name) {
+            return after.find('>').map_or("", |end| &after[end + 1..]);
+        }
+        search = after;
+    }
+    ""
+}
+
+fn attr(attrs: &str, name: &str) -> Option<String> {
+    let mut rest = attrs.trim_start();
+    while !rest.is_empty() {
+        let key_len = rest
+            .find(|c: char| c == '=' || c.is_whitespace())
+            .unwrap_or(rest.len());
+        let key = &rest[..key_len];
+        rest = rest[key_len..].trim_start();
+
+        let mut value = "";
+        if let Some(after) = rest.strip_prefix('=') {
+            let after = after.trim_start();
+            let (v, len) = match after.as_bytes().first() {
+                Some(&q @ (b'"' | b'\'')) => {
+                    let end = after[1..].find(q as char).map_or(after.len(), |e| e + 1);
+                    (&after[1..end], (end + 1).min(after.len()))
+                }
+                _ => {
+                    let end = after.find(char::is_whitespace).unwrap_or(after.len());
+                    (&after[..end], end)
+                }
+            };
+            value = v;
+            rest = after[len..].trim_start();
+        }
+
+        if key.eq_ignore_ascii_case(name) {
+            let mut decoded = String::new();
+            decode(value, &mut decoded);
+            return Some(decoded);
+        }
+    }
+    None
+}
+
+fn language(class: &str) -> Option<&str> {
+    class.split_whitespace().find_map(|c| {
+        c.strip_prefix("language-")
+            .or_else(|| c.strip_prefix("lang-"))
+            .filter(|lang| !lang.is_empty())
+    })
+}
+
+fn is_followable(href: &str) -> bool {
+    let href = href.trim();
+    !href.is_empty()
+        && !href.starts_with('#')
+        && !href
+            .get(..11)
+            .is_some_and(|scheme| scheme.eq_ignore_ascii_case("javascript:"))
+}
+
+fn decode(text: &str, out: &mut String) {
+    let mut rest = text;
+    while let Some(amp) = rest.find('&') {
+        out.push_str(&rest[..amp]);
+        rest = &rest[amp..];
+
+        let end = rest[1..]
+            .find(|c: char| !c.is_ascii_alphanumeric() && c != '#')
+            .map_or(rest.len(), |i| i + 1);
+        let entity = &rest[1..end];
+        let c = match entity {
+            "amp" => Some('&'),
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            "nbsp" => Some('\u{a0}'),
+            _ => entity.strip_prefix('#').and_then(|num| {
+                let code = match num.strip_prefix(['x', 'X']) {
+                    Some(hex) => u32::from_str_radix(hex, 16).ok(),
+                    None => num.parse().ok(),
+                };
+                code.and_then(char::from_u32)
+            }),
+        };
+
+        match c {
+            Some(c) => {
+                out.push(c);
+                rest = &rest[end..];
+                rest = rest.strip_prefix(';').unwrap_or(rest);
+            }
+            None => {
+                out.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_from_html() {
+        let html = "<!DOCTYPE html>\n\
+            <html><head><title>Ignored</title><style>p { color: red }</style></head>\n\
+            <body>\n\
+            <h1>Hello &amp; welcome</h1>\n\
+            <!-- a comment -->\n\
+            <p>Some <b>bold</b>   text with a <a href=\"gemini://a.com/\">link</a>\n\
+            and <a href='#top'>an anchor</a>.</p>\n\
+            <script>document.write(\"<p>no</p>\")</script>\n\
+            <p><a href=\"/only\">Only link</a></p>\n\
+            <ul><li>one</li><li>two<br>lines</li></ul>\n\
+            <blockquote><p>quoted &lt;text&gt;</p></blockquote>\n\
+            <pre><code class=\"language-rust\">fn main() {\n    x &lt; 1;\n}\n</code></pre>\n\
+            <h4>Deep</h4><img src=\"/i.png\" alt=\"pic\">\n\
+            <p>1 &lt; 2 &#x263A; &unknown;</p>\n\
+            </body></html>";
+
+        assert_eq!(
+            from_html(html),
+            "# Hello & welcome\n\
+             \n\
+             Some bold text with a link and an anchor.\n\
+             => gemini://a.com/ link\n\
+             \n\
+             => /only Only link\n\
+             \n\
+             * one\n\
+             * two lines\n\
+             \n\
+             > quoted <text>\n\
+             \n\
+             ```rust\n\
+             fn main() {\n\
+             \x20   x < 1;\n\
+             }\n\
+             ```\n\
+             \n\
+             ### Deep\n\
+             \n\
+             => /i.png pic\n\
+             \n\
+             1 < 2 \u{263A} &unknown;\n"
+        );
+    }
+
+    #[test]
+    fn test_from_html_unterminated() {
+        assert_eq!(from_html("a < b <pre>x"), "a < b\n\n```\nx\n```\n");
+        assert_eq!(from_html("<p>text<script>x"), "text\n");
+    }
+}
@@ -0,0 +1,77 @@
+use super::Document;
+use crate::{Url, DEFAULT_PORT};
+use alloc::{string::String, vec::Vec};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ResolvedLink {
+    pub url: Url,
+    pub label: Option<String>,
+    pub external: bool,
+}
+
+impl ResolvedLink {
+    #[inline]
+    pub fn is_same_capsule(&self) -> bool {
+        !self.external
+    }
+}
+
+pub fn extract_links(doc: &Document, base: &Url) -> Vec<ResolvedLink> {
+    doc.links()
+        .filter_map(|(url, label)| {
+            let url = base.join(url).ok()?;
+            Some(ResolvedLink {
+                external: !same_capsule(&url, base),
+                label: label.map(String::from),
+                url,
+            })
+        })
+        .collect()
+}
+
+fn same_capsule(a: &Url, b: &Url) -> bool {
+    a.scheme() == b.scheme() && a.host_str() == b.host_str() && port(a) == port(b)
+}
+
+#[inline]
+fn port(url: &Url) -> Option<u16> {
+    match url.scheme() {
+        "gemini" => Some(url.port().unwrap_or(DEFAULT_PORT)),
+        _ => url.port_or_known_default(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_extract_links() {
+        let doc = Document::parse(
+            "=> /about About\n\
+             => sub/page\n\
+             => gemini://example.com:1965/x\n\
+             => gemini://other.org/ Other\n\
+             => https://example.com/ Web\n\
+             => gemini://[bad\n",
+        );
+        let base = Url::parse("gemini://example.com/dir/index.gmi").unwrap();
+        let links = extract_links(&doc, &base);
+
+        let urls: Vec<_> = links.iter().map(|l| l.url.as_str()).collect();
+        assert_eq!(
+            urls,
+            vec![
+                "gemini://example.com/about",
+                "gemini://example.com/dir/sub/page",
+                "gemini://example.com:1965/x",
+                "gemini://other.org/",
+                "https://example.com/",
+            ]
+        );
+        let external: Vec<_> = links.iter().map(|l| l.external).collect();
+        assert_eq!(external, vec![false, false, false, true, true]);
+        assert_eq!(links[0].label.as_deref(), Some("About"));
+        assert!(links[1].is_same_capsule());
+    }
+}
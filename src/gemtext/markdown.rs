@@ -0,0 +1,434 @@
+use super::Builder;
+use alloc::{string::String, vec::Vec};
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum Kind {
+    Paragraph,
+    ListItem,
+    Quote,
+}
+
+struct Fence<'a> {
+    marker: u8,
+    len: usize,
+    alt: Option<&'a str>,
+    lines: Vec<&'a str>,
+}
+
+struct Converter<'a> {
+    out: Builder,
+    refs: Vec<(String, &'a str)>,
+    kind: Kind,
+    buf: Vec<&'a str>,
+    last: Option<Kind>,
+}
+
+pub fn from_markdown(md: &str) -> String {
+    let refs = md.lines().filter_map(reference_definition).collect();
+    let mut conv = Converter {
+        out: Builder::new(),
+        refs,
+        kind: Kind::Paragraph,
+        buf: Vec::new(),
+        last: None,
+    };
+
+    let mut fence: Option<Fence<'_>> = None;
+    for line in md.lines() {
+        if let Some(f) = &mut fence {
+            if closes_fence(line, f.marker, f.len) {
+                conv.separate(None);
+                let text = f.lines.join("\n");
+                conv.out.pre(f.alt, &text);
+                fence = None;
+            } else {
+                f.lines.push(line);
+            }
+            continue;
+        }
+
+        let trimmed = line.trim();
+        if trimmed.is_empty() {
+            conv.flush();
+            continue;
+        }
+        if indent(line) < 4 {
+            if let Some(f) = open_fence(trimmed) {
+                conv.flush();
+                fence = Some(f);
+                continue;
+            }
+            if let Some((level, text)) = atx_heading(trimmed) {
+                conv.flush();
+                conv.heading(level, text);
+                continue;
+            }
+            if conv.kind == Kind::Paragraph && !conv.buf.is_empty() {
+                if let Some(level) = setext_underline(trimmed) {
+                    let text = conv.buf.join(" ");
+                    conv.buf.clear();
+                    conv.heading(level, &text);
+                    continue;
+                }
+            }
+            if is_thematic_break(trimmed) {
+                conv.flush();
+                continue;
+            }
+            if reference_definition(line).is_some() {
+                conv.flush();
+                continue;
+            }
+        }
+
+        if let Some(rest) = trimmed.strip_prefix('>') {
+            let rest = rest.trim_start_matches(['>', ' ']);
+            if conv.kind != Kind::Quote || rest.is_empty() {
+                conv.flush();
+                conv.kind = Kind::Quote;
+            }
+            if !rest.is_empty() {
+                conv.buf.push(rest);
+            }
+        } else if let Some(rest) = list_item(trimmed) {
+            conv.flush();
+            conv.kind = Kind::ListItem;
+            conv.buf.push(rest);
+        } else {
+            if conv.buf.is_empty() {
+                conv.kind = Kind::Paragraph;
+            }
+            conv.buf.push(trimmed);
+        }
+    }
+
+    if let Some(f) = fence {
+        conv.flush();
+        conv.separate(None);
+        conv.out.pre(f.alt, &f.lines.join("\n"));
+    }
+    conv.flush();
+    conv.out.build()
+}
+
+impl<'a> Converter<'a> {
+    fn separate(&mut self, kind: Option<Kind>) {
+        let grouped = matches!(
+            (self.last, kind),
+            (Some(Kind::ListItem), Some(Kind::ListItem))
+        );
+        if self.last.is_some() && !grouped {
+            self.out.blank();
+        }
+        self.last = Some(kind.unwrap_or(Kind::Paragraph));
+    }
+
+    fn heading(&mut self, level: usize, text: &str) {
+        let (text, links) = inline(text, &self.refs);
+        self.separate(None);
+        self.out.heading(level.min(3) as u8, &text);
+        self.links(&links);
+    }
+
+    fn flush(&mut self) {
+        if self.buf.is_empty() {
+            return;
+        }
+        let (text, links) = inline(&self.buf.join(" "), &self.refs);
+        self.buf.clear();
+
+        self.separate(Some(self.kind));
+        let only_link = matches!(&links[..], [(_, label)] if *label == text);
+        match self.kind {
+            Kind::Paragraph if only_link => {}
+            Kind::Paragraph => {
+                self.out.text(&text);
+            }
+            Kind::ListItem => {
+                self.out.list_item(&text);
+            }
+            Kind::Quote => {
+                self.out.quote(&text);
+            }
+        }
+        self.links(&links);
+    }
+
+    fn links(&mut self, links: &[(String, String)]) {
+        for (url, label) in links {
+            let label = Some(label.as_str()).filter(|l| l != url);
+            self.out.link(url, label);
+        }
+    }
+}
+
+fn inline(text: &str, refs: &[(String, &str)]) -> (String, Vec<(String, String)>) {
+    let mut out = String::new();
+    let mut links = Vec::new();
+    let mut rest = text;
+
+    while let Some(c) = rest.chars().next() {
+        let prev = out.chars().next_back();
+        match c {
+            '\\' if rest[1..].starts_with(|c: char| c.is_ascii_punctuation()) => {
+                out.push_str(&rest[1..2]);
+                rest = &rest[2..];
+                continue;
+            }
+            '`' => {
+                let ticks = rest.bytes().take_while(|&b| b == b'`').count();
+                let fence = &rest[..ticks];
+                if let Some(end) = rest[ticks..].find(fence) {
+                    out.push_str(rest[ticks..ticks + end].trim());
+                    rest = &rest[ticks + end + ticks..];
+                } else {
+                    out.push_str(fence);
+                    rest = &rest[ticks..];
+                }
+                continue;
+            }
+            '!' | '[' => {
+                let start = if c == '!' { 1 } else { 0 };
+                if rest[start..].starts_with('[') {
+                    if let Some((label, url, len)) = link(&rest[start..], refs) {
+                        let (label, mut nested) = inline(label, refs);
+                        out.push_str(&label);
+                        links.append(&mut nested);
+                        links.push((String::from(url), label));
+                        rest = &rest[start + len..];
+                        continue;
+                    }
+                }
+            }
+            '<' => {
+                if let Some(end) = rest.find('>') {
+                    let url = &rest[1..end];
+                    if url.contains(':') && !url.contains(char::is_whitespace) {
+                        out.push_str(url);
+                        links.push((String::from(url), String::from(url)));
+                        rest = &rest[end + 1..];
+                        continue;
+                    }
+                }
+            }
+            '*' | '~' => {
+                rest = &rest[1..];
+                continue;
+            }
+            '_' => {
+                let next = rest[1..].chars().next();
+                let intraword = prev.is_some_and(char::is_alphanumeric)
+                    && next.is_some_and(char::is_alphanumeric);
+                if !intraword {
+                    rest = &rest[1..];
+                    continue;
+                }
+            }
+            _ => {}
+        }
+        out.push(c);
+        rest = &rest[c.len_utf8()..];
+    }
+
+    (out, links)
+}
+
+fn link<'t>(text: &'t str, refs: &[(String, &'t str)]) -> Option<(&'t str, &'t str, usize)> {
+    let close = matching_bracket(text)?;
+    let label = &text[1..close];
+    let after = &text[close + 1..];
+
+    if let Some(inner) = after.strip_prefix('(') {
+        let end = inner.find(')')?;
+        let dest = inner[..end].trim();
+        let url = match dest.strip_prefix('<') {
+            Some(dest) => dest.split('>').next().unwrap_or(""),
+            None => dest.split_whitespace().next().unwrap_or(""),
+        };
+        return Some((label, url, close + 2 + end + 1));
+    }
+
+    let (key, len) = match after.strip_prefix('[') {
+        Some(inner) => {
+            let end = inner.find(']')?;
+            let key = if end == 0 { label } else { &inner[..end] };
+            (key, close + 1 + end + 2)
+        }
+        None => (label, close + 1),
+    };
+    let (_, url) = refs.iter().find(|(k, _)| k.eq_ignore_ascii_case(key))?;
+    Some((label, url, len))
+}
+
+fn matching_bracket(text: &str) -> Option<usize> {
+    let mut depth = 0;
+    let mut escaped = false;
+    for (i, b) in text.bytes().enumerate() {
+        match b {
+            _ if escaped => escaped = false,
+            b'\\' => escaped = true,
+            b'[' => depth += 1,
+            b']' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(i);
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+fn reference_definition(line: &str) -> Option<(String, &str)> {
+    if indent(line) >= 4 {
+        return None;
+    }
+    let line = line.trim();
+    let (label, rest) = line.strip_prefix('[')?.split_once("]:")?;
+    let url = rest.split_whitespace().next()?;
+    let url = url
+        .strip_prefix('<')
+        .and_then(|u| u.strip_suffix('>'))
+        .unwrap_or(url);
+    Some((String::from(label), url))
+}
+
+fn atx_heading(line: &str) -> Option<(usize, &str)> {
+    let level = line.bytes().take_while(|&b| b == b'#').count();
+    if !(1..=6).contains(&level) {
+        return None;
+    }
+    let rest = &line[level..];
+    if !rest.is_empty() && !rest.starts_with([' ', '\t']) {
+        return None;
+    }
+    let text = rest.trim().trim_end_matches('#').trim_end();
+    Some((level, text))
+}
+
+fn setext_underline(line: &str) -> Option<usize> {
+    match line.as_bytes()[0] {
+        b'=' if line.bytes().all(|b| b == b'=') => Some(1),
+        b'-' if line.bytes().all(|b| b == b'-') => Some(2),
+        _ => None,
+    }
+}
+
+fn is_thematic_break(line: &str) -> bool {
+    let marker = line.as_bytes()[0];
+    matches!(marker, b'-' | b'*' | b'_')
+        && line.bytes().filter(|&b| b == marker).count() >= 3
+        && line.bytes().all(|b| b == marker || b == b' ' || b == b'\t')
+}
+
+fn list_item(line: &str) -> Option<&str> {
+    let digits = line.bytes().take_while(u8::is_ascii_digit).count();
+    let rest = match line.as_bytes().get(digits) {
+        Some(b'.' | b')') if (1..=9).contains(&digits) => &line[digits + 1..],
+        Some(b'-' | b'*' | b'+') if digits == 0 => &line[1..],
+        _ => return None,
+    };
+    if rest.is_empty() {
+        return Some(rest);
+    }
+    rest.strip_prefix([' ', '\t']).map(str::trim_start)
+}
+
+fn open_fence(line: &str) -> Option<Fence<'_>> {
+    let marker = *line.as_bytes().first()?;
+    if marker != b'`' && marker != b'~' {
+        return None;
+    }
+    let len = line.bytes().take_while(|&b| b == marker).count();
+    let info = line[len..].trim();
+    if len < 3 || (marker == b'`' && info.contains('`')) {
+        return None;
+    }
+    Some(Fence {
+        marker,
+        len,
+        alt: Some(info).filter(|s| !s.is_empty()),
+        lines: Vec::new(),
+    })
+}
+
+fn closes_fence(line: &str, marker: u8, len: usize) -> bool {
+    let line = line.trim();
+    line.len() >= len && line.bytes().all(|b| b == marker)
+}
+
+#[inline]
+fn indent(line: &str) -> usize {
+    line.bytes()
+        .take_while(|&b| b == b' ' || b == b'\t')
+        .map(|b| if b == b'\t' { 4 } else { 1 })
+        .sum()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_from_markdown() {
+        let md = "Title\n\
+            =====\n\
+            \n\
+            Some *emphasis* and a [link](gemini://a.com/ \"t\") in\n\
+            one paragraph with `code_span` and snake_case.\n\
+            \n\
+            - item [one][ref]\n\
+            \x20 - nested\n\
+            1. third\n\
+            \n\
+            > quoted\n\
+            > > deeper\n\
+            \n\
+            ##### Deep heading ##\n\
+            \n\
+            <https://example.com>\n\
+            \n\
+            ```rust\n\
+            let x = [1](2);\n\
+            ```\n\
+            ***\n\
+            ![alt text](/img.png)\n\
+            \n\
+            [ref]: /one\n";
+
+        assert_eq!(
+            from_markdown(md),
+            "# Title\n\
+             \n\
+             Some emphasis and a link in one paragraph with code_span and snake_case.\n\
+             => gemini://a.com/ link\n\
+             \n\
+             * item one\n\
+             => /one one\n\
+             * nested\n\
+             * third\n\
+             \n\
+             > quoted deeper\n\
+             \n\
+             ### Deep heading\n\
+             \n\
+             => https://example.com\n\
+             \n\
+             ```rust\n\
+             let x = [1](2);\n\
+             ```\n\
+             \n\
+             => /img.png alt text\n"
+        );
+    }
+
+    #[test]
+    fn test_from_markdown_unterminated_fence() {
+        assert_eq!(
+            from_markdown("text\n~~~~\ncode"),
+            "text\n\n```\ncode\n```\n"
+        );
+        assert_eq!(from_markdown("a\\*b\\_c"), "a*b_c\n");
+    }
+}
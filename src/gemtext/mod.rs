@@ -0,0 +1,326 @@
+mod builder;
+mod diff;
+mod document;
+mod edit;
+mod format;
+mod html;
+mod links;
+mod markdown;
+mod outline;
+mod slug;
+mod stats;
+mod stream;
+mod table;
+mod tokens;
+mod visitor;
+mod wrap;
+
+pub mod feed;
+pub mod render;
+
+pub use builder::Builder;
+pub use diff::{diff, render_diff, Change, Hunk};
+pub use document::{Block, Document};
+pub use edit::EditableDocument;
+pub use format::format;
+pub use html::from_html;
+pub use links::{extract_links, ResolvedLink};
+pub use markdown::from_markdown;
+pub use outline::OutlineEntry;
+pub use slug::{slugify, Slugger};
+pub use stats::Stats;
+pub use stream::{Limits, StreamParser};
+pub use tokens::{tokenize, Token, TokenKind, Tokens};
+pub use visitor::{parse_with, Visitor};
+pub use wrap::{char_width, reflow, str_width};
+
+use core::{ops::Range, str};
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Line<'a> {
+    Text(&'a str),
+    Link {
+        url: &'a str,
+        label: Option<&'a str>,
+    },
+    Heading {
+        level: u8,
+        text: &'a str,
+    },
+    ListItem(&'a str),
+    Quote(&'a str),
+    PreformatToggle {
+        alt: Option<&'a str>,
+    },
+    Preformatted(&'a str),
+}
+
+impl<'a> Line<'a> {
+    pub fn parse(line: &'a str, preformatted: bool) -> Self {
+        if let Some(alt) = line.strip_prefix("```") {
+            return Line::PreformatToggle {
+                alt: non_empty(alt.trim()),
+            };
+        }
+
+        if preformatted {
+            return Line::Preformatted(line);
+        }
+
+        if let Some(rest) = line.strip_prefix("=>") {
+            let rest = rest.trim_start();
+            let (url, label) = match rest.find(char::is_whitespace) {
+                Some(i) => (&rest[..i], non_empty(rest[i..].trim())),
+                None => (rest, None),
+            };
+            return Line::Link { url, label };
+        }
+
+        if line.starts_with('#') {
+            let level = line.bytes().take(3).take_while(|&b| b == b'#').count();
+            return Line::Heading {
+                level: level as u8,
+                text: line[level..].trim_start(),
+            };
+        }
+
+        if let Some(rest) = line.strip_prefix("* ") {
+            return Line::ListItem(rest);
+        }
+
+        if let Some(rest) = line.strip_prefix('>') {
+            return Line::Quote(rest.trim_start());
+        }
+
+        Line::Text(line)
+    }
+}
+
+#[inline]
+fn non_empty(s: &str) -> Option<&str> {
+    if s.is_empty() {
+        None
+    } else {
+        Some(s)
+    }
+}
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct SourceSpan {
+    pub start: usize,
+    pub end: usize,
+    pub line: usize,
+}
+
+impl SourceSpan {
+    #[inline]
+    pub fn range(&self) -> Range<usize> {
+        self.start..self.end
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Lines<'a> {
+    rest: &'a str,
+    preformatted: bool,
+    bom: bool,
+    pos: usize,
+    line: usize,
+}
+
+impl<'a> Lines<'a> {
+    #[inline]
+    pub fn new(body: &'a str) -> Self {
+        let rest = body.strip_prefix('\u{feff}');
+        Self {
+            bom: rest.is_some(),
+            rest: rest.unwrap_or(body),
+            preformatted: false,
+            pos: if rest.is_some() { 3 } else { 0 },
+            line: 0,
+        }
+    }
+
+    pub(super) fn at(body: &'a str, pos: usize, line: usize) -> Self {
+        if pos == 0 {
+            return Self::new(body);
+        }
+        Self {
+            rest: &body[pos..],
+            preformatted: false,
+            bom: false,
+            pos,
+            line: line - 1,
+        }
+    }
+
+    #[inline]
+    pub fn spanned(self) -> Spanned<'a> {
+        Spanned(self)
+    }
+
+    #[inline]
+    pub fn from_bytes(body: &'a [u8]) -> Result<Self, str::Utf8Error> {
+        Ok(Self::new(str::from_utf8(body)?))
+    }
+
+    #[inline]
+    pub fn is_preformatted(&self) -> bool {
+        self.preformatted
+    }
+
+    #[inline]
+    pub fn has_bom(&self) -> bool {
+        self.bom
+    }
+}
+
+impl<'a> Iterator for Lines<'a> {
+    type Item = Line<'a>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Line<'a>> {
+        self.next_spanned().map(|(_, line)| line)
+    }
+}
+
+impl<'a> Lines<'a> {
+    fn next_spanned(&mut self) -> Option<(SourceSpan, Line<'a>)> {
+        if self.rest.is_empty() {
+            return None;
+        }
+
+        let (line, rest) = match self.rest.find('\n') {
+            Some(i) => (&self.rest[..i], &self.rest[i + 1..]),
+            None => (self.rest, ""),
+        };
+        let content = line.strip_suffix('\r').unwrap_or(line);
+        self.line += 1;
+        let span = SourceSpan {
+            start: self.pos,
+            end: self.pos + content.len(),
+            line: self.line,
+        };
+        self.pos += self.rest.len() - rest.len();
+        self.rest = rest;
+
+        Some((span, next_line(content, &mut self.preformatted)))
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Spanned<'a>(Lines<'a>);
+
+impl<'a> Iterator for Spanned<'a> {
+    type Item = (SourceSpan, Line<'a>);
+
+    #[inline]
+    fn next(&mut self) -> Option<(SourceSpan, Line<'a>)> {
+        self.0.next_spanned()
+    }
+}
+
+#[inline]
+fn next_line<'a>(line: &'a str, preformatted: &mut bool) -> Line<'a> {
+    let line = Line::parse(line, *preformatted);
+    if let Line::PreformatToggle { .. } = line {
+        *preformatted = !*preformatted;
+    }
+    line
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_line_parse() {
+        assert_eq!(Line::parse("hello", false), Line::Text("hello"));
+        assert_eq!(Line::parse("", false), Line::Text(""));
+        assert_eq!(
+            Line::parse("=> gemini://a.com  A  site ", false),
+            Line::Link {
+                url: "gemini://a.com",
+                label: Some("A  site"),
+            }
+        );
+        assert_eq!(
+            Line::parse("=>/path", false),
+            Line::Link {
+                url: "/path",
+                label: None,
+            }
+        );
+        assert_eq!(
+            Line::parse("## Title", false),
+            Line::Heading {
+                level: 2,
+                text: "Title",
+            }
+        );
+        assert_eq!(
+            Line::parse("####Deep", false),
+            Line::Heading {
+                level: 3,
+                text: "#Deep",
+            }
+        );
+        assert_eq!(Line::parse("* item", false), Line::ListItem("item"));
+        assert_eq!(Line::parse("*bold*", false), Line::Text("*bold*"));
+        assert_eq!(Line::parse("> quote", false), Line::Quote("quote"));
+        assert_eq!(
+            Line::parse("```rust", false),
+            Line::PreformatToggle { alt: Some("rust") }
+        );
+        assert_eq!(Line::parse("# x", true), Line::Preformatted("# x"));
+    }
+
+    #[test]
+    fn test_lines() {
+        let body = "# Title\r\n```\n=> not a link\n```\ntext";
+        let lines: Vec<_> = Lines::new(body).collect();
+        assert_eq!(
+            lines,
+            vec![
+                Line::Heading {
+                    level: 1,
+                    text: "Title",
+                },
+                Line::PreformatToggle { alt: None },
+                Line::Preformatted("=> not a link"),
+                Line::PreformatToggle { alt: None },
+                Line::Text("text"),
+            ]
+        );
+
+        assert_eq!(Lines::new("a\n\nb\n").count(), 3);
+        assert!(Lines::from_bytes(b"\xff").is_err());
+        assert!(!Lines::new(body).has_bom());
+
+        let spans: Vec<_> = Lines::new("\u{feff}a\r\n\n=> b")
+            .spanned()
+            .map(|(span, _)| (span.range(), span.line))
+            .collect();
+        assert_eq!(spans, vec![(3..4, 1), (6..6, 2), (7..11, 3)]);
+
+        let body = "a\r\n```\r\nb\r\n";
+        for (span, line) in Lines::new(body).spanned() {
+            let text = &body[span.range()];
+            assert!(!text.ends_with('\r'));
+            match line {
+                Line::Text(s) | Line::Preformatted(s) => assert_eq!(s, text),
+                line => assert_eq!(line, Line::PreformatToggle { alt: None }),
+            }
+        }
+
+        let mut lines = Lines::new("\u{feff}# Title\n");
+        assert!(lines.has_bom());
+        assert_eq!(
+            lines.next(),
+            Some(Line::Heading {
+                level: 1,
+                text: "Title",
+            })
+        );
+    }
+}
@@ -0,0 +1,100 @@
+use super::{Block, Document};
+use alloc::{vec, vec::Vec};
+use core::{fmt, iter::Peekable, ops::Range};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OutlineEntry<'a> {
+    pub level: u8,
+    pub text: &'a str,
+    pub range: Range<usize>,
+    pub children: Vec<OutlineEntry<'a>>,
+}
+
+impl Document {
+    pub fn outline(&self) -> Vec<OutlineEntry<'_>> {
+        let mut flat: Vec<OutlineEntry<'_>> = Vec::new();
+        let mut pos = 0;
+
+        for block in &self.blocks {
+            if let Block::Heading { level, text } = block {
+                let open = flat
+                    .iter_mut()
+                    .filter(|e| e.range.end == usize::MAX && e.level >= *level);
+                for entry in open {
+                    entry.range.end = pos;
+                }
+                flat.push(OutlineEntry {
+                    level: *level,
+                    text,
+                    range: pos..usize::MAX,
+                    children: vec![],
+                });
+            }
+            pos += display_len(block);
+        }
+
+        for entry in &mut flat {
+            entry.range.end = entry.range.end.min(pos);
+        }
+        nest(&mut flat.into_iter().peekable(), 0)
+    }
+}
+
+fn nest<'a, I>(entries: &mut Peekable<I>, level: u8) -> Vec<OutlineEntry<'a>>
+where
+    I: Iterator<Item = OutlineEntry<'a>>,
+{
+    let mut out = Vec::new();
+    while let Some(mut entry) = entries.next_if(|e| e.level > level) {
+        entry.children = nest(entries, entry.level);
+        out.push(entry);
+    }
+    out
+}
+
+fn display_len(block: &Block) -> usize {
+    struct Counter(usize);
+
+    impl fmt::Write for Counter {
+        fn write_str(&mut self, s: &str) -> fmt::Result {
+            self.0 += s.len();
+            Ok(())
+        }
+    }
+
+    let mut counter = Counter(0);
+    let _ = fmt::write(&mut counter, format_args!("{}", block));
+    counter.0
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_outline() {
+        let body = "## Pre\n\
+            # A\n\
+            text\n\
+            ### A.1\n\
+            ## A.2\n\
+            # B\n";
+        let doc = Document::parse(body);
+        let outline = doc.outline();
+
+        assert_eq!(outline.len(), 3);
+        assert_eq!(outline[0].text, "Pre");
+        assert_eq!(&body[outline[0].range.clone()], "## Pre\n");
+
+        let a = &outline[1];
+        assert_eq!((a.level, a.text), (1, "A"));
+        assert_eq!(&body[a.range.clone()], "# A\ntext\n### A.1\n## A.2\n");
+        let children: Vec<_> = a.children.iter().map(|c| (c.level, c.text)).collect();
+        assert_eq!(children, vec![(3, "A.1"), (2, "A.2")]);
+        assert_eq!(&body[a.children[0].range.clone()], "### A.1\n");
+
+        assert_eq!(&body[outline[2].range.clone()], "# B\n");
+        assert!(outline[2].children.is_empty());
+        assert!(Document::parse("text\n").outline().is_empty());
+    }
+}
@@ -0,0 +1,219 @@
+use crate::gemtext::{str_width, Block, Document};
+use alloc::{format, string::String};
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Theme<'a> {
+    pub heading: [&'a str; 3],
+    pub link: &'a str,
+    pub link_number: &'a str,
+    pub list: &'a str,
+    pub quote: &'a str,
+    pub preformatted: &'a str,
+}
+
+impl<'a> Theme<'a> {
+    pub const PLAIN: Theme<'static> = Theme {
+        heading: ["", "", ""],
+        link: "",
+        link_number: "",
+        list: "",
+        quote: "",
+        preformatted: "",
+    };
+}
+
+impl<'a> Default for Theme<'a> {
+    fn default() -> Self {
+        Self {
+            heading: ["1;35", "1;36", "1;34"],
+            link: "4;34",
+            link_number: "2",
+            list: "",
+            quote: "3;32",
+            preformatted: "33",
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Renderer<'a> {
+    theme: Theme<'a>,
+    width: usize,
+}
+
+impl<'a> Default for Renderer<'a> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a> Renderer<'a> {
+    #[inline]
+    pub fn new() -> Self {
+        Self {
+            theme: Theme::default(),
+            width: 80,
+        }
+    }
+
+    #[inline]
+    pub fn theme(mut self, theme: Theme<'a>) -> Self {
+        self.theme = theme;
+        self
+    }
+
+    #[inline]
+    pub fn width(mut self, width: usize) -> Self {
+        self.width = width;
+        self
+    }
+
+    pub fn render(&self, doc: &Document) -> String {
+        let mut out = String::new();
+        let mut links = 0;
+        for block in &doc.blocks {
+            self.render_block(block, &mut links, &mut out);
+        }
+        out
+    }
+
+    fn render_block(&self, block: &Block, links: &mut usize, out: &mut String) {
+        let theme = &self.theme;
+        match block {
+            Block::Text(text) => self.wrap(text, "", "", "", out),
+            Block::Link { url, label } => {
+                *links += 1;
+                let number = format!("[{}]", links);
+                let indent = " ".repeat(number.len() + 1);
+                styled(theme.link_number, &number, out);
+                out.push(' ');
+                self.wrap(
+                    label.as_deref().unwrap_or(url),
+                    "",
+                    &indent,
+                    theme.link,
+                    out,
+                );
+            }
+            Block::Heading { level, text } => {
+                let level = (*level).clamp(1, 3);
+                let style = theme.heading[level as usize - 1];
+                let marker = format!("{} ", "#".repeat(level as usize));
+                self.wrap(text, &marker, &marker, style, out);
+            }
+            Block::List(items) => {
+                let mut bullet = String::new();
+                styled(theme.list, "•", &mut bullet);
+                bullet.push(' ');
+                for item in items {
+                    self.wrap(item, &bullet, "  ", "", out);
+                }
+            }
+            Block::Quote(lines) => {
+                for line in lines {
+                    self.wrap(line, "> ", "> ", theme.quote, out);
+                }
+            }
+            Block::Preformatted { lines, .. } => {
+                for line in lines {
+                    styled(theme.preformatted, line, out);
+                    out.push('\n');
+                }
+            }
+            Block::Table { source, .. } => {
+                for block in source {
+                    self.render_block(block, links, out);
+                }
+            }
+        }
+    }
+
+    fn wrap(&self, text: &str, first: &str, rest: &str, style: &str, out: &mut String) {
+        let avail = self.width.saturating_sub(str_width(rest));
+        let mut prefix = first;
+        let mut line = String::new();
+        let mut len = 0;
+
+        for word in text.split_whitespace() {
+            let word_len = str_width(word);
+            if len > 0 && self.width > 0 && len + 1 + word_len > avail {
+                out.push_str(prefix);
+                styled(style, &line, out);
+                out.push('\n');
+                prefix = rest;
+                line.clear();
+                len = 0;
+            }
+            if len > 0 {
+                line.push(' ');
+                len += 1;
+            }
+            line.push_str(word);
+            len += word_len;
+        }
+
+        out.push_str(prefix);
+        styled(style, &line, out);
+        out.push('\n');
+    }
+}
+
+pub fn render(doc: &Document) -> String {
+    Renderer::new().render(doc)
+}
+
+fn styled(style: &str, text: &str, out: &mut String) {
+    if text.is_empty() {
+        return;
+    }
+    if !style.is_empty() {
+        out.push_str(&format!("\x1b[{}m", style));
+    }
+    out.extend(text.chars().filter(|&c| c == '\t' || !c.is_control()));
+    if !style.is_empty() {
+        out.push_str("\x1b[0m");
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_render() {
+        let doc = Document::parse("## Hi\n=> gemini://a.com Site\n> q\n```\n  keep   this\n```\n");
+        assert_eq!(
+            render(&doc),
+            "## \x1b[1;36mHi\x1b[0m\n\
+             \x1b[2m[1]\x1b[0m \x1b[4;34mSite\x1b[0m\n\
+             > \x1b[3;32mq\x1b[0m\n\
+             \x1b[33m  keep   this\x1b[0m\n"
+        );
+    }
+
+    #[test]
+    fn test_render_wrap() {
+        let doc = Document::parse(
+            "one two three four\n\n* alpha beta gamma\n=> /a\n=> /b link label text\n",
+        );
+        let out = Renderer::new().theme(Theme::PLAIN).width(10).render(&doc);
+        assert_eq!(
+            out,
+            "one two\nthree four\n\n\
+             • alpha\n  beta\n  gamma\n\
+             [1] /a\n\
+             [2] link\n    label\n    text\n"
+        );
+    }
+
+    #[test]
+    fn test_render_control_chars() {
+        let doc = Document::parse(
+            "a\x1b]52;c;aGk=\x07b\n=> /x \x1b]0;title\x07\n> \u{9b}2J\n```\n\tpre\x1b[31m\n```\n",
+        );
+        let out = Renderer::new().theme(Theme::PLAIN).render(&doc);
+        assert_eq!(out, "a]52;c;aGk=b\n[1] ]0;title\n> 2J\n\tpre[31m\n");
+        assert!(!render(&doc).contains("\x1b]"));
+    }
+}
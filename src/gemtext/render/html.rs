@@ -0,0 +1,274 @@
+use crate::gemtext::{Block, Document, Slugger};
+use alloc::{boxed::Box, format, string::String};
+
+type Escape<'a> = Box<dyn Fn(&str, &mut String) + 'a>;
+type RewriteLink<'a> = Box<dyn Fn(&str) -> String + 'a>;
+type Highlight<'a> = Box<dyn Fn(&str, &str) -> Option<String> + 'a>;
+
+pub struct Renderer<'a> {
+    escape: Escape<'a>,
+    rewrite_link: Option<RewriteLink<'a>>,
+    highlight: Option<Highlight<'a>>,
+    heading_ids: bool,
+}
+
+impl<'a> Default for Renderer<'a> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a> Renderer<'a> {
+    pub fn new() -> Self {
+        Self {
+            escape: Box::new(escape),
+            rewrite_link: None,
+            highlight: None,
+            heading_ids: false,
+        }
+    }
+
+    pub fn escape<F>(mut self, f: F) -> Self
+    where
+        F: Fn(&str, &mut String) + 'a,
+    {
+        self.escape = Box::new(f);
+        self
+    }
+
+    pub fn rewrite_link<F>(mut self, f: F) -> Self
+    where
+        F: Fn(&str) -> String + 'a,
+    {
+        self.rewrite_link = Some(Box::new(f));
+        self
+    }
+
+    pub fn highlight<F>(mut self, f: F) -> Self
+    where
+        F: Fn(&str, &str) -> Option<String> + 'a,
+    {
+        self.highlight = Some(Box::new(f));
+        self
+    }
+
+    #[inline]
+    pub fn heading_ids(mut self, enabled: bool) -> Self {
+        self.heading_ids = enabled;
+        self
+    }
+
+    pub fn render(&self, doc: &Document) -> String {
+        let mut out = String::new();
+        let mut slugger = Slugger::new();
+        for block in &doc.blocks {
+            self.render_block(block, &mut slugger, &mut out);
+        }
+        out
+    }
+
+    fn render_block(&self, block: &Block, slugger: &mut Slugger, out: &mut String) {
+        let escape = &self.escape;
+        match block {
+            Block::Text(text) if text.trim().is_empty() => {}
+            Block::Text(text) => {
+                out.push_str("<p>");
+                escape(text, out);
+                out.push_str("</p>\n");
+            }
+            Block::Link { url, label } => {
+                let href = match &self.rewrite_link {
+                    Some(rewrite) => rewrite(url),
+                    None if is_unsafe_link(url) => {
+                        out.push_str("<p>");
+                        escape(label.as_deref().unwrap_or(url), out);
+                        out.push_str("</p>\n");
+                        return;
+                    }
+                    None => url.clone(),
+                };
+                out.push_str("<p><a href=\"");
+                escape(&href, out);
+                out.push_str("\">");
+                escape(label.as_deref().unwrap_or(url), out);
+                out.push_str("</a></p>\n");
+            }
+            Block::Heading { level, text } => {
+                let level = (*level).clamp(1, 3);
+                if self.heading_ids {
+                    out.push_str(&format!("<h{} id=\"", level));
+                    escape(&slugger.slug(text), out);
+                    out.push_str("\">");
+                } else {
+                    out.push_str(&format!("<h{}>", level));
+                }
+                escape(text, out);
+                out.push_str(&format!("</h{}>\n", level));
+            }
+            Block::List(items) => {
+                out.push_str("<ul>\n");
+                for item in items {
+                    out.push_str("<li>");
+                    escape(item, out);
+                    out.push_str("</li>\n");
+                }
+                out.push_str("</ul>\n");
+            }
+            Block::Quote(lines) => {
+                out.push_str("<blockquote>\n");
+                for line in lines {
+                    out.push_str("<p>");
+                    escape(line, out);
+                    out.push_str("</p>\n");
+                }
+                out.push_str("</blockquote>\n");
+            }
+            Block::Preformatted { alt, lines } => {
+                match alt {
+                    Some(alt) => {
+                        out.push_str("<pre aria-label=\"");
+                        escape(alt, out);
+                        out.push_str("\">");
+                    }
+                    None => out.push_str("<pre>"),
+                }
+                let highlighted = match (&self.highlight, alt) {
+                    (Some(highlight), Some(alt)) => highlight(alt, &lines.join("\n")),
+                    _ => None,
+                };
+                match highlighted {
+                    Some(html) => out.push_str(&html),
+                    None => {
+                        for (i, line) in lines.iter().enumerate() {
+                            if i > 0 {
+                                out.push('\n');
+                            }
+                            escape(line, out);
+                        }
+                    }
+                }
+                out.push_str("</pre>\n");
+            }
+            Block::Table { rows, header, .. } => {
+                out.push_str("<table>\n");
+                for (i, row) in rows.iter().enumerate() {
+                    let cell = if *header && i == 0 { "th" } else { "td" };
+                    out.push_str("<tr>");
+                    for text in row {
+                        out.push_str(&format!("<{}>", cell));
+                        escape(text, out);
+                        out.push_str(&format!("</{}>", cell));
+                    }
+                    out.push_str("</tr>\n");
+                }
+                out.push_str("</table>\n");
+            }
+        }
+    }
+}
+
+pub fn render(doc: &Document) -> String {
+    Renderer::new().render(doc)
+}
+
+fn is_unsafe_link(url: &str) -> bool {
+    let scheme: String = url
+        .chars()
+        .filter(|c| !c.is_ascii_whitespace() && !c.is_control())
+        .take_while(|&c| c != ':' && c != '/' && c != '?' && c != '#')
+        .collect();
+    ["javascript", "data", "vbscript"]
+        .iter()
+        .any(|unsafe_scheme| scheme.eq_ignore_ascii_case(unsafe_scheme))
+}
+
+pub fn escape(s: &str, out: &mut String) {
+    for c in s.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            c => out.push(c),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_render() {
+        let doc = Document::parse(
+            "# A & B\n\ntext <b>\n=> /x?a=1&b=2\n=> gemini://a.com Site\n* one\n> q\n```sh\n<cmd>\nmore\n```\n",
+        );
+        assert_eq!(
+            render(&doc),
+            "<h1>A &amp; B</h1>\n\
+             <p>text &lt;b&gt;</p>\n\
+             <p><a href=\"/x?a=1&amp;b=2\">/x?a=1&amp;b=2</a></p>\n\
+             <p><a href=\"gemini://a.com\">Site</a></p>\n\
+             <ul>\n<li>one</li>\n</ul>\n\
+             <blockquote>\n<p>q</p>\n</blockquote>\n\
+             <pre aria-label=\"sh\">&lt;cmd&gt;\nmore</pre>\n"
+        );
+    }
+
+    #[test]
+    fn test_render_options() {
+        let doc = Document::parse("=> gemini://a.com/b <Site>\n");
+        let html = Renderer::new()
+            .escape(|s, out| out.push_str(s))
+            .rewrite_link(|url| url.replacen("gemini://", "https://proxy/", 1))
+            .render(&doc);
+        assert_eq!(
+            html,
+            "<p><a href=\"https://proxy/a.com/b\"><Site></a></p>\n"
+        );
+
+        let doc = Document::parse(
+            "=> javascript:alert(1) x\n=> JaVaScRiPt:alert(1) Java\n=> DATA:text/html,<b> y\n=> /data:z z\n",
+        );
+        assert_eq!(
+            render(&doc),
+            "<p>x</p>\n<p>Java</p>\n<p>y</p>\n<p><a href=\"/data:z\">z</a></p>\n"
+        );
+        let html = Renderer::new()
+            .rewrite_link(|url| String::from(url))
+            .render(&Document::parse("=> javascript:void(0) x\n"));
+        assert_eq!(html, "<p><a href=\"javascript:void(0)\">x</a></p>\n");
+
+        let doc = Document::parse("```rust\nfn main() {}\n```\n```txt\n<x>\n```\n```\n<y>\n```\n");
+        let html = Renderer::new()
+            .highlight(|alt, code| match alt {
+                "rust" => Some(format!("<code class=\"rust\">{}</code>", code)),
+                _ => None,
+            })
+            .render(&doc);
+        assert_eq!(
+            html,
+            "<pre aria-label=\"rust\"><code class=\"rust\">fn main() {}</code></pre>\n\
+             <pre aria-label=\"txt\">&lt;x&gt;</pre>\n\
+             <pre>&lt;y&gt;</pre>\n"
+        );
+
+        let mut doc = Document::parse("| a | <b> |\n|---|---|\n| 1 | 2 |\n");
+        doc.detect_tables();
+        assert_eq!(
+            render(&doc),
+            "<table>\n\
+             <tr><th>a</th><th>&lt;b&gt;</th></tr>\n\
+             <tr><td>1</td><td>2</td></tr>\n\
+             </table>\n"
+        );
+
+        let doc = Document::parse("# Notes\n## Notes\n");
+        assert_eq!(
+            Renderer::new().heading_ids(true).render(&doc),
+            "<h1 id=\"notes\">Notes</h1>\n<h2 id=\"notes-1\">Notes</h2>\n"
+        );
+    }
+}
@@ -0,0 +1,218 @@
+use crate::gemtext::{Block, Document};
+use alloc::{format, string::String, vec::Vec};
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum LinkStyle {
+    #[default]
+    Inline,
+    Reference,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Renderer {
+    link_style: LinkStyle,
+}
+
+impl Renderer {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[inline]
+    pub fn link_style(mut self, style: LinkStyle) -> Self {
+        self.link_style = style;
+        self
+    }
+
+    pub fn render(&self, doc: &Document) -> String {
+        let mut out = String::new();
+        let mut refs = Vec::new();
+        for block in &doc.blocks {
+            if let Block::Text(text) = block {
+                if text.trim().is_empty() {
+                    continue;
+                }
+            }
+            if !out.is_empty() {
+                out.push('\n');
+            }
+            self.render_block(block, &mut refs, &mut out);
+        }
+
+        if !refs.is_empty() {
+            out.push('\n');
+            for (i, url) in refs.iter().enumerate() {
+                out.push_str(&format!("[{}]: {}\n", i + 1, destination(url)));
+            }
+        }
+        out
+    }
+
+    fn render_block<'a>(&self, block: &'a Block, refs: &mut Vec<&'a str>, out: &mut String) {
+        match block {
+            Block::Text(text) => {
+                escape(text, out);
+                out.push('\n');
+            }
+            Block::Link { url, label } => {
+                out.push('[');
+                escape(label.as_deref().unwrap_or(url), out);
+                out.push(']');
+                match self.link_style {
+                    LinkStyle::Inline => out.push_str(&format!("({})", destination(url))),
+                    LinkStyle::Reference => {
+                        refs.push(url);
+                        out.push_str(&format!("[{}]", refs.len()));
+                    }
+                }
+                out.push('\n');
+            }
+            Block::Heading { level, text } => {
+                out.push_str(&"#".repeat((*level).clamp(1, 6) as usize));
+                out.push(' ');
+                escape(text, out);
+                out.push('\n');
+            }
+            Block::List(items) => {
+                for item in items {
+                    out.push_str("- ");
+                    escape(item, out);
+                    out.push('\n');
+                }
+            }
+            Block::Quote(lines) => {
+                for (i, line) in lines.iter().enumerate() {
+                    if i > 0 {
+                        out.push_str(">\n");
+                    }
+                    out.push_str("> ");
+                    escape(line, out);
+                    out.push('\n');
+                }
+            }
+            Block::Preformatted { alt, lines } => {
+                let longest = lines
+                    .iter()
+                    .map(|line| line.bytes().take_while(|&b| b == b'`').count())
+                    .max()
+                    .unwrap_or(0);
+                let fence = "`".repeat(longest.max(2) + 1);
+                out.push_str(&fence);
+                if let Some(alt) = alt {
+                    out.push_str(&alt.replace('`', ""));
+                }
+                out.push('\n');
+                for line in lines {
+                    out.push_str(line);
+                    out.push('\n');
+                }
+                out.push_str(&fence);
+                out.push('\n');
+            }
+            Block::Table {
+                rows, header: true, ..
+            } => {
+                for (i, row) in rows.iter().enumerate() {
+                    for cell in row {
+                        out.push_str("| ");
+                        out.push_str(&cell.replace('|', "\\|"));
+                        out.push(' ');
+                    }
+                    out.push_str("|\n");
+                    if i == 0 {
+                        out.push_str(&"| --- ".repeat(row.len()));
+                        out.push_str("|\n");
+                    }
+                }
+            }
+            Block::Table { source, .. } => {
+                for (i, block) in source.iter().enumerate() {
+                    if i > 0 {
+                        out.push('\n');
+                    }
+                    self.render_block(block, refs, out);
+                }
+            }
+        }
+    }
+}
+
+pub fn render(doc: &Document) -> String {
+    Renderer::new().render(doc)
+}
+
+pub fn escape(s: &str, out: &mut String) {
+    let s = s.trim_start();
+    let digits = s.bytes().take_while(u8::is_ascii_digit).count();
+    if digits > 0 && matches!(s.as_bytes().get(digits), Some(b'.' | b')')) {
+        out.push_str(&s[..digits]);
+        out.push('\\');
+        return escape_inline(&s[digits..], out);
+    }
+    if s.starts_with(['-', '+', '=']) {
+        out.push('\\');
+    }
+    escape_inline(s, out)
+}
+
+fn escape_inline(s: &str, out: &mut String) {
+    for c in s.chars() {
+        if matches!(
+            c,
+            '\\' | '`' | '*' | '_' | '[' | ']' | '<' | '>' | '#' | '!' | '|' | '~' | '&'
+        ) {
+            out.push('\\');
+        }
+        out.push(c);
+    }
+}
+
+fn destination(url: &str) -> String {
+    if url.contains([' ', '(', ')', '<', '>']) {
+        format!("<{}>", url.replace('<', "%3C").replace('>', "%3E"))
+    } else {
+        String::from(url)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_render() {
+        let doc = Document::parse(
+            "# Title *1*\n\nSome text\n1. not a list\n=> gemini://a.com/x Site\n=> /(y)\n\
+             * one\n* two\n> a\n> b\n```rust\nfn main() {}\n```\n",
+        );
+        assert_eq!(
+            render(&doc),
+            "# Title \\*1\\*\n\n\
+             Some text\n\n\
+             1\\. not a list\n\n\
+             [Site](gemini://a.com/x)\n\n\
+             [/(y)](</(y)>)\n\n\
+             - one\n- two\n\n\
+             > a\n>\n> b\n\n\
+             ```rust\nfn main() {}\n```\n"
+        );
+    }
+
+    #[test]
+    fn test_render_reference_links() {
+        let mut doc = Document::parse("=> gemini://a.com A\n=> /b\n");
+        doc.blocks.push(Block::Preformatted {
+            alt: None,
+            lines: vec!["```inner".to_string()],
+        });
+        let md = Renderer::new()
+            .link_style(LinkStyle::Reference)
+            .render(&doc);
+        assert_eq!(
+            md,
+            "[A][1]\n\n[/b][2]\n\n````\n```inner\n````\n\n\
+             [1]: gemini://a.com\n[2]: /b\n"
+        );
+    }
+}
@@ -0,0 +1,4 @@
+pub mod ansi;
+pub mod html;
+pub mod markdown;
+pub mod plain;
@@ -0,0 +1,126 @@
+use crate::gemtext::{str_width, Block, Document};
+use alloc::string::String;
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum HeadingStyle {
+    #[default]
+    Underline,
+    Uppercase,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Renderer {
+    heading_style: HeadingStyle,
+}
+
+impl Renderer {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[inline]
+    pub fn heading_style(mut self, style: HeadingStyle) -> Self {
+        self.heading_style = style;
+        self
+    }
+
+    pub fn render(&self, doc: &Document) -> String {
+        let mut out = String::new();
+        for block in &doc.blocks {
+            self.render_block(block, &mut out);
+        }
+        out
+    }
+
+    fn render_block(&self, block: &Block, out: &mut String) {
+        match block {
+            Block::Text(text) => push_line(text, out),
+            Block::Link { url, label } => {
+                if let Some(label) = label {
+                    out.push_str(label);
+                    out.push_str(" <");
+                    out.push_str(url);
+                    out.push_str(">\n");
+                } else {
+                    push_line(url, out);
+                }
+            }
+            Block::Heading { level, text } => match self.heading_style {
+                HeadingStyle::Underline => {
+                    push_line(text, out);
+                    let rule = match level {
+                        1 => '=',
+                        2 => '-',
+                        _ => '~',
+                    };
+                    out.extend(core::iter::repeat_n(rule, str_width(text)));
+                    out.push('\n');
+                }
+                HeadingStyle::Uppercase => push_line(&text.to_uppercase(), out),
+            },
+            Block::List(items) => {
+                for item in items {
+                    out.push_str("- ");
+                    push_line(item, out);
+                }
+            }
+            Block::Quote(lines) => {
+                for line in lines {
+                    out.push_str("  ");
+                    push_line(line, out);
+                }
+            }
+            Block::Preformatted { lines, .. } => {
+                for line in lines {
+                    push_line(line, out);
+                }
+            }
+            Block::Table { rows, .. } => {
+                for row in rows {
+                    push_line(&row.join("\t"), out);
+                }
+            }
+        }
+    }
+}
+
+pub fn render(doc: &Document) -> String {
+    Renderer::new().render(doc)
+}
+
+#[inline]
+fn push_line(text: &str, out: &mut String) {
+    out.push_str(text);
+    out.push('\n');
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_render() {
+        let doc = Document::parse(
+            "# Title\n\ntext\n=> gemini://a.com Site\n=> /b\n## Sub\n* one\n> q\n```\n  code\n```\n",
+        );
+        assert_eq!(
+            render(&doc),
+            "Title\n=====\n\
+             \n\
+             text\n\
+             Site <gemini://a.com>\n\
+             /b\n\
+             Sub\n---\n\
+             - one\n\
+             \x20 q\n\
+             \x20 code\n"
+        );
+
+        let doc = Document::parse("### Straße\n");
+        let out = Renderer::new()
+            .heading_style(HeadingStyle::Uppercase)
+            .render(&doc);
+        assert_eq!(out, "STRASSE\n");
+    }
+}
@@ -0,0 +1,81 @@
+use super::{Block, Document};
+use alloc::{collections::BTreeSet, format, string::String, vec::Vec};
+
+pub fn slugify(text: &str) -> String {
+    let mut slug = String::with_capacity(text.len());
+    let mut dash = false;
+    for c in text.chars() {
+        if c.is_alphanumeric() {
+            if dash && !slug.is_empty() {
+                slug.push('-');
+            }
+            dash = false;
+            slug.extend(c.to_lowercase());
+        } else if c.is_whitespace() || c == '-' || c == '_' {
+            dash = true;
+        }
+    }
+    if slug.is_empty() {
+        slug.push_str("section");
+    }
+    slug
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Slugger {
+    seen: BTreeSet<String>,
+}
+
+impl Slugger {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn slug(&mut self, text: &str) -> String {
+        let base = slugify(text);
+        let mut slug = base.clone();
+        let mut n = 0;
+        while self.seen.contains(&slug) {
+            n += 1;
+            slug = format!("{}-{}", base, n);
+        }
+        self.seen.insert(slug.clone());
+        slug
+    }
+}
+
+impl Document {
+    pub fn heading_slugs(&self) -> Vec<String> {
+        let mut slugger = Slugger::new();
+        self.blocks
+            .iter()
+            .filter_map(|block| match block {
+                Block::Heading { text, .. } => Some(slugger.slug(text)),
+                _ => None,
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_slugify() {
+        assert_eq!(slugify("Hello, World!"), "hello-world");
+        assert_eq!(slugify("  Déjà  vu -- again_ "), "déjà-vu-again");
+        assert_eq!(slugify("Ünïcödé 日本語"), "ünïcödé-日本語");
+        assert_eq!(slugify("!!!"), "section");
+    }
+
+    #[test]
+    fn test_heading_slugs() {
+        let doc = Document::parse("# Intro\n## Intro\n### Intro 1\n# Intro\ntext\n# ?\n");
+        assert_eq!(
+            doc.heading_slugs(),
+            ["intro", "intro-1", "intro-1-1", "intro-2", "section"]
+        );
+    }
+}
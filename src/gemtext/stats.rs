@@ -0,0 +1,93 @@
+use super::{Block, Document};
+use core::time::Duration;
+
+const WORDS_PER_MINUTE: u64 = 200;
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct Stats {
+    pub links: usize,
+    pub headings: [usize; 3],
+    pub list_items: usize,
+    pub quotes: usize,
+    pub preformatted: usize,
+    pub tables: usize,
+    pub words: usize,
+}
+
+impl Stats {
+    #[inline]
+    pub fn total_headings(&self) -> usize {
+        self.headings.iter().sum()
+    }
+
+    pub fn reading_time(&self) -> Duration {
+        Duration::from_secs((self.words as u64 * 60).div_ceil(WORDS_PER_MINUTE))
+    }
+}
+
+impl Document {
+    pub fn stats(&self) -> Stats {
+        let mut stats = Stats::default();
+        for block in &self.blocks {
+            match block {
+                Block::Text(text) => stats.words += words(text),
+                Block::Link { label, .. } => {
+                    stats.links += 1;
+                    stats.words += label.as_deref().map_or(0, words);
+                }
+                Block::Heading { level, text } => {
+                    stats.headings[(*level).clamp(1, 3) as usize - 1] += 1;
+                    stats.words += words(text);
+                }
+                Block::List(items) => {
+                    stats.list_items += items.len();
+                    stats.words += items.iter().map(|item| words(item)).sum::<usize>();
+                }
+                Block::Quote(lines) => {
+                    stats.quotes += 1;
+                    stats.words += lines.iter().map(|line| words(line)).sum::<usize>();
+                }
+                Block::Preformatted { .. } => stats.preformatted += 1,
+                Block::Table { rows, .. } => {
+                    stats.tables += 1;
+                    stats.words += rows.iter().flatten().map(|cell| words(cell)).sum::<usize>();
+                }
+            }
+        }
+        stats
+    }
+}
+
+#[inline]
+fn words(text: &str) -> usize {
+    text.split_whitespace().count()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_stats() {
+        let doc = Document::parse(
+            "# Title here\n## Sub\n## Sub 2\n\nsome body text\n=> /a A link\n=> /b\n\
+             * one\n* two words\n> quoted\n```\nnot counted\n```\n",
+        );
+        let stats = doc.stats();
+        assert_eq!(
+            stats,
+            Stats {
+                links: 2,
+                headings: [1, 2, 0],
+                list_items: 2,
+                quotes: 1,
+                preformatted: 1,
+                tables: 0,
+                words: 14,
+            }
+        );
+        assert_eq!(stats.total_headings(), 3);
+        assert_eq!(stats.reading_time(), Duration::from_secs(5));
+        assert_eq!(Document::new().stats().reading_time(), Duration::ZERO);
+    }
+}
@@ -0,0 +1,240 @@
+use super::{next_line, Line};
+use crate::Error;
+use alloc::vec::Vec;
+use core::{mem, str};
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Limits {
+    pub max_line_len: usize,
+    pub max_lines: usize,
+    pub max_doc_bytes: usize,
+    pub max_pre_depth: usize,
+}
+
+impl Limits {
+    pub const UNLIMITED: Limits = Limits {
+        max_line_len: usize::MAX,
+        max_lines: usize::MAX,
+        max_doc_bytes: usize::MAX,
+        max_pre_depth: usize::MAX,
+    };
+}
+
+impl Default for Limits {
+    #[inline]
+    fn default() -> Self {
+        Self::UNLIMITED
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct StreamParser {
+    buf: Vec<u8>,
+    preformatted: bool,
+    limits: Limits,
+    bytes: usize,
+    lines: usize,
+    pre_lines: usize,
+}
+
+impl StreamParser {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[inline]
+    pub fn with_limits(limits: Limits) -> Self {
+        Self {
+            limits,
+            ..Self::default()
+        }
+    }
+
+    #[inline]
+    pub fn limits(&self) -> &Limits {
+        &self.limits
+    }
+
+    #[inline]
+    pub fn is_preformatted(&self) -> bool {
+        self.preformatted
+    }
+
+    #[inline]
+    pub fn buffered(&self) -> usize {
+        self.buf.len()
+    }
+
+    pub fn feed<F>(&mut self, mut chunk: &[u8], mut f: F) -> Result<(), Error>
+    where
+        F: FnMut(Line<'_>),
+    {
+        self.bytes = self.bytes.saturating_add(chunk.len());
+        if self.bytes > self.limits.max_doc_bytes {
+            return Err(Error::LimitExceeded);
+        }
+
+        if !self.buf.is_empty() {
+            match chunk.iter().position(|&b| b == b'\n') {
+                Some(i) => {
+                    self.buf.extend_from_slice(&chunk[..i]);
+                    chunk = &chunk[i + 1..];
+                    let buf = mem::take(&mut self.buf);
+                    self.emit(&buf, &mut f)?;
+                    self.buf = buf;
+                    self.buf.clear();
+                }
+                None => {
+                    self.buf.extend_from_slice(chunk);
+                    return self.check_line_len(self.buf.len());
+                }
+            }
+        }
+
+        while let Some(i) = chunk.iter().position(|&b| b == b'\n') {
+            self.emit(&chunk[..i], &mut f)?;
+            chunk = &chunk[i + 1..];
+        }
+
+        self.check_line_len(chunk.len())?;
+        self.buf.extend_from_slice(chunk);
+        Ok(())
+    }
+
+    pub fn finish<F>(&mut self, mut f: F) -> Result<(), Error>
+    where
+        F: FnMut(Line<'_>),
+    {
+        let buf = mem::take(&mut self.buf);
+        let res = if buf.is_empty() {
+            Ok(())
+        } else {
+            self.emit(&buf, &mut f)
+        };
+        self.preformatted = false;
+        self.bytes = 0;
+        self.lines = 0;
+        self.pre_lines = 0;
+        res
+    }
+
+    fn emit<F>(&mut self, line: &[u8], f: &mut F) -> Result<(), Error>
+    where
+        F: FnMut(Line<'_>),
+    {
+        self.check_line_len(line.len())?;
+        self.lines += 1;
+        if self.lines > self.limits.max_lines {
+            return Err(Error::LimitExceeded);
+        }
+
+        let line = str::from_utf8(line.strip_suffix(b"\r").unwrap_or(line))?;
+        let line = next_line(line, &mut self.preformatted);
+        match line {
+            Line::Preformatted(_) => {
+                self.pre_lines += 1;
+                if self.pre_lines > self.limits.max_pre_depth {
+                    return Err(Error::LimitExceeded);
+                }
+            }
+            Line::PreformatToggle { .. } => self.pre_lines = 0,
+            _ => {}
+        }
+        f(line);
+        Ok(())
+    }
+
+    #[inline]
+    fn check_line_len(&self, len: usize) -> Result<(), Error> {
+        if len > self.limits.max_line_len {
+            Err(Error::LimitExceeded)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::gemtext::{Block, Lines};
+    use alloc::vec::Vec;
+
+    const BODY: &str = "# Title\r\n```alt\n* pre\n```\n=> /a b\n\ntail";
+
+    #[test]
+    fn test_stream_parser() {
+        for size in 1..BODY.len() {
+            let mut parser = StreamParser::new();
+            let mut blocks = Vec::new();
+            for chunk in BODY.as_bytes().chunks(size) {
+                parser
+                    .feed(chunk, |line| blocks.push(Block::from(line)))
+                    .unwrap();
+            }
+            parser
+                .finish(|line| blocks.push(Block::from(line)))
+                .unwrap();
+            assert_eq!(parser.buffered(), 0);
+
+            let expected: Vec<_> = Lines::new(BODY).map(Block::from).collect();
+            assert_eq!(blocks, expected, "chunk size {}", size);
+        }
+    }
+
+    #[test]
+    fn test_stream_parser_utf8() {
+        let mut parser = StreamParser::new();
+        let mut lines = 0;
+        parser.feed(b"caf\xc3", |_| lines += 1).unwrap();
+        parser.feed(b"\xa9\n", |_| lines += 1).unwrap();
+        assert_eq!(lines, 1);
+        assert!(parser.feed(b"\xff\n", |_| {}).is_err());
+    }
+
+    #[test]
+    fn test_stream_parser_limits() {
+        fn run(limits: Limits, chunks: &[&[u8]]) -> Result<usize, Error> {
+            let mut parser = StreamParser::with_limits(limits);
+            let mut lines = 0;
+            for chunk in chunks {
+                parser.feed(chunk, |_| lines += 1)?;
+            }
+            parser.finish(|_| lines += 1)?;
+            Ok(lines)
+        }
+
+        let body: &[&[u8]] = &[b"short\nlonger line", b" here\n```\na\nb\n```\n"];
+        assert_eq!(run(Limits::default(), body), Ok(6));
+
+        let limits = |f: fn(&mut Limits)| {
+            let mut limits = Limits::UNLIMITED;
+            f(&mut limits);
+            limits
+        };
+        assert_eq!(
+            run(limits(|l| l.max_line_len = 15), body),
+            Err(Error::LimitExceeded)
+        );
+        assert_eq!(
+            run(limits(|l| l.max_line_len = 16), &[b"0123456789abcdefg"]),
+            Err(Error::LimitExceeded)
+        );
+        assert_eq!(run(limits(|l| l.max_line_len = 16), body), Ok(6));
+        assert_eq!(
+            run(limits(|l| l.max_lines = 5), body),
+            Err(Error::LimitExceeded)
+        );
+        assert_eq!(run(limits(|l| l.max_lines = 6), body), Ok(6));
+        assert_eq!(
+            run(limits(|l| l.max_doc_bytes = 30), body),
+            Err(Error::LimitExceeded)
+        );
+        assert_eq!(
+            run(limits(|l| l.max_pre_depth = 1), body),
+            Err(Error::LimitExceeded)
+        );
+        assert_eq!(run(limits(|l| l.max_pre_depth = 2), body), Ok(6));
+    }
+}
@@ -0,0 +1,204 @@
+use super::{Block, Document};
+use alloc::{string::String, vec, vec::Vec};
+use core::mem;
+
+impl Document {
+    pub fn detect_tables(&mut self) {
+        let mut blocks = Vec::with_capacity(self.blocks.len());
+        let mut run: Vec<Block> = Vec::new();
+
+        for block in mem::take(&mut self.blocks) {
+            match block {
+                Block::Text(text) if is_pipe_row(&text) => {
+                    run.push(Block::Text(text));
+                    continue;
+                }
+                Block::Preformatted { alt, lines } => {
+                    flush_run(&mut run, &mut blocks);
+                    let table = grid_table(&lines);
+                    let source = Block::Preformatted { alt, lines };
+                    blocks.push(match table {
+                        Some((rows, header)) => Block::Table {
+                            rows,
+                            header,
+                            source: vec![source],
+                        },
+                        None => source,
+                    });
+                }
+                block => {
+                    flush_run(&mut run, &mut blocks);
+                    blocks.push(block);
+                }
+            }
+        }
+        flush_run(&mut run, &mut blocks);
+        self.blocks = blocks;
+    }
+}
+
+fn flush_run(run: &mut Vec<Block>, blocks: &mut Vec<Block>) {
+    let lines: Vec<&str> = run
+        .iter()
+        .filter_map(|block| match block {
+            Block::Text(text) => Some(text.as_str()),
+            _ => None,
+        })
+        .collect();
+
+    match lines.len() {
+        0 => {}
+        1 => blocks.append(run),
+        _ => match pipe_table(&lines) {
+            Some((rows, header)) => blocks.push(Block::Table {
+                rows,
+                header,
+                source: mem::take(run),
+            }),
+            None => blocks.append(run),
+        },
+    }
+}
+
+fn pipe_table(lines: &[&str]) -> Option<(Vec<Vec<String>>, bool)> {
+    let mut rows = Vec::new();
+    let mut header = false;
+    for (i, line) in lines.iter().enumerate() {
+        if is_separator(line) {
+            if i != 1 {
+                return None;
+            }
+            header = true;
+        } else {
+            rows.push(cells(line));
+        }
+    }
+    consistent(rows, header)
+}
+
+fn grid_table(lines: &[String]) -> Option<(Vec<Vec<String>>, bool)> {
+    let mut rows = Vec::new();
+    let mut header = false;
+    let mut borders = 0;
+    for line in lines.iter().map(|line| line.trim()) {
+        if line.is_empty() {
+            continue;
+        }
+        if is_border(line) {
+            borders += 1;
+            if rows.len() == 1 && (line.contains('=') || is_separator(line)) {
+                header = true;
+            }
+        } else if is_pipe_row(line) {
+            rows.push(cells(line));
+        } else {
+            return None;
+        }
+    }
+    if borders == 0 && rows.len() < 2 {
+        return None;
+    }
+    consistent(rows, header)
+}
+
+fn consistent(rows: Vec<Vec<String>>, header: bool) -> Option<(Vec<Vec<String>>, bool)> {
+    let columns = rows.first()?.len();
+    if columns < 2 || rows.iter().any(|row| row.len() != columns) {
+        return None;
+    }
+    Some((rows, header))
+}
+
+fn is_pipe_row(line: &str) -> bool {
+    let line = line.trim();
+    line.len() > 1 && line.starts_with('|') && line[1..].contains('|')
+}
+
+fn is_separator(line: &str) -> bool {
+    let line = line.trim();
+    line.starts_with('|')
+        && line.contains('-')
+        && line.bytes().all(|b| matches!(b, b'|' | b'-' | b':' | b' '))
+}
+
+fn is_border(line: &str) -> bool {
+    (line.contains('-') || line.contains('='))
+        && line
+            .bytes()
+            .all(|b| matches!(b, b'+' | b'-' | b'=' | b'|' | b':' | b' '))
+}
+
+fn cells(line: &str) -> Vec<String> {
+    let line = line.trim();
+    let line = line.strip_prefix('|').unwrap_or(line);
+    let line = line.strip_suffix('|').unwrap_or(line);
+    line.split('|')
+        .map(|cell| String::from(cell.trim()))
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use alloc::format;
+
+    fn row(cells: &[&str]) -> Vec<String> {
+        cells.iter().map(|&c| String::from(c)).collect()
+    }
+
+    #[test]
+    fn test_detect_tables() {
+        let body = "intro\n\
+            | a | b |\n\
+            |---|:-:|\n\
+            | 1 | 2 |\n\
+            ```table\n\
+            +----+-----+\n\
+            | x  | y   |\n\
+            +====+=====+\n\
+            | 3  | 4   |\n\
+            +----+-----+\n\
+            ```\n\
+            | lonely |\n\
+            ```\n\
+            not | a | table\n\
+            ```\n";
+        let mut doc = Document::parse(body);
+        let plain = doc.clone();
+        doc.detect_tables();
+
+        assert_eq!(doc.blocks.len(), 5);
+        assert_eq!(doc.blocks[0], Block::Text(String::from("intro")));
+        match &doc.blocks[1] {
+            Block::Table {
+                rows,
+                header,
+                source,
+            } => {
+                assert_eq!(rows, &vec![row(&["a", "b"]), row(&["1", "2"])]);
+                assert!(header);
+                assert_eq!(source.len(), 3);
+            }
+            block => panic!("expected table, got {:?}", block),
+        }
+        match &doc.blocks[2] {
+            Block::Table { rows, header, .. } => {
+                assert_eq!(rows, &vec![row(&["x", "y"]), row(&["3", "4"])]);
+                assert!(header);
+            }
+            block => panic!("expected table, got {:?}", block),
+        }
+        assert_eq!(doc.blocks[3], Block::Text(String::from("| lonely |")));
+        assert!(matches!(doc.blocks[4], Block::Preformatted { .. }));
+
+        assert_eq!(format!("{}", doc), format!("{}", plain));
+        assert_eq!(format!("{}", plain), body);
+    }
+
+    #[test]
+    fn test_detect_tables_inconsistent() {
+        let mut doc = Document::parse("| a | b |\n| 1 |\n");
+        doc.detect_tables();
+        assert_eq!(doc, Document::parse("| a | b |\n| 1 |\n"));
+    }
+}
@@ -0,0 +1,144 @@
+use super::{Line, Lines};
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum TokenKind {
+    Text,
+    Heading(u8),
+    LinkLabel,
+    ListItem,
+    Quote,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Token<'a> {
+    pub text: &'a str,
+    pub offset: usize,
+    pub position: usize,
+    pub line: usize,
+    pub kind: TokenKind,
+}
+
+impl<'a> Token<'a> {
+    pub fn weight(&self) -> u32 {
+        match self.kind {
+            TokenKind::Heading(level) => 5 - level.clamp(1, 3) as u32,
+            TokenKind::LinkLabel => 2,
+            _ => 1,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Tokens<'a> {
+    body: &'a str,
+    lines: Lines<'a>,
+    rest: &'a str,
+    kind: TokenKind,
+    line: usize,
+    position: usize,
+}
+
+impl<'a> Tokens<'a> {
+    pub fn new(body: &'a str) -> Self {
+        Self {
+            body,
+            lines: Lines::new(body),
+            rest: "",
+            kind: TokenKind::Text,
+            line: 0,
+            position: 0,
+        }
+    }
+
+    fn next_segment(&mut self) -> bool {
+        for line in self.lines.by_ref() {
+            self.line += 1;
+            let (text, kind) = match line {
+                Line::Text(text) => (text, TokenKind::Text),
+                Line::Link {
+                    label: Some(label), ..
+                } => (label, TokenKind::LinkLabel),
+                Line::Heading { level, text } => (text, TokenKind::Heading(level)),
+                Line::ListItem(text) => (text, TokenKind::ListItem),
+                Line::Quote(text) => (text, TokenKind::Quote),
+                _ => continue,
+            };
+            self.rest = text;
+            self.kind = kind;
+            return true;
+        }
+        false
+    }
+}
+
+impl<'a> Iterator for Tokens<'a> {
+    type Item = Token<'a>;
+
+    fn next(&mut self) -> Option<Token<'a>> {
+        loop {
+            let rest = self.rest.trim_start_matches(|c: char| !c.is_alphanumeric());
+            if rest.is_empty() {
+                if !self.next_segment() {
+                    return None;
+                }
+                continue;
+            }
+
+            let end = rest
+                .find(|c: char| !c.is_alphanumeric())
+                .unwrap_or(rest.len());
+            let (text, tail) = rest.split_at(end);
+            self.rest = tail;
+            self.position += 1;
+            return Some(Token {
+                text,
+                offset: text.as_ptr() as usize - self.body.as_ptr() as usize,
+                position: self.position - 1,
+                line: self.line,
+                kind: self.kind,
+            });
+        }
+    }
+}
+
+#[inline]
+pub fn tokenize(body: &str) -> Tokens<'_> {
+    Tokens::new(body)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use alloc::vec::Vec;
+
+    #[test]
+    fn test_tokenize() {
+        let body = "# Hello, world\r\n\
+            => gemini://skip.me/path Read más\n\
+            => /no-label\n\
+            ```\n\
+            preformatted skipped\n\
+            ```\n\
+            * item\n\
+            > it's\n";
+        let tokens: Vec<_> = tokenize(body)
+            .map(|t| (t.text, t.position, t.line, t.kind, t.weight()))
+            .collect();
+        assert_eq!(
+            tokens,
+            [
+                ("Hello", 0, 1, TokenKind::Heading(1), 4),
+                ("world", 1, 1, TokenKind::Heading(1), 4),
+                ("Read", 2, 2, TokenKind::LinkLabel, 2),
+                ("más", 3, 2, TokenKind::LinkLabel, 2),
+                ("item", 4, 7, TokenKind::ListItem, 1),
+                ("it", 5, 8, TokenKind::Quote, 1),
+                ("s", 6, 8, TokenKind::Quote, 1),
+            ]
+        );
+        for token in tokenize(body) {
+            assert_eq!(&body[token.offset..][..token.text.len()], token.text);
+        }
+        assert_eq!(tokenize("").count(), 0);
+    }
+}
@@ -0,0 +1,97 @@
+use super::{Line, Lines};
+
+#[allow(unused_variables)]
+pub trait Visitor<'a> {
+    fn on_text(&mut self, text: &'a str) {}
+
+    fn on_link(&mut self, url: &'a str, label: Option<&'a str>) {}
+
+    fn on_heading(&mut self, level: u8, text: &'a str) {}
+
+    fn on_list_item(&mut self, text: &'a str) {}
+
+    fn on_quote(&mut self, text: &'a str) {}
+
+    fn on_pre_start(&mut self, alt: Option<&'a str>) {}
+
+    fn on_pre_line(&mut self, text: &'a str) {}
+
+    fn on_pre_end(&mut self) {}
+}
+
+pub fn parse_with<'a, V: Visitor<'a> + ?Sized>(doc: &'a str, visitor: &mut V) {
+    let mut lines = Lines::new(doc);
+    while let Some(line) = lines.next() {
+        match line {
+            Line::Text(text) => visitor.on_text(text),
+            Line::Link { url, label } => visitor.on_link(url, label),
+            Line::Heading { level, text } => visitor.on_heading(level, text),
+            Line::ListItem(text) => visitor.on_list_item(text),
+            Line::Quote(text) => visitor.on_quote(text),
+            Line::PreformatToggle { alt } => {
+                if lines.is_preformatted() {
+                    visitor.on_pre_start(alt);
+                } else {
+                    visitor.on_pre_end();
+                }
+            }
+            Line::Preformatted(text) => visitor.on_pre_line(text),
+        }
+    }
+    if lines.is_preformatted() {
+        visitor.on_pre_end();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use alloc::{format, string::String, vec::Vec};
+
+    #[derive(Default)]
+    struct Recorder(Vec<String>);
+
+    impl<'a> Visitor<'a> for Recorder {
+        fn on_heading(&mut self, level: u8, text: &'a str) {
+            self.0.push(format!("h{} {}", level, text));
+        }
+
+        fn on_link(&mut self, url: &'a str, label: Option<&'a str>) {
+            self.0.push(format!("link {} {:?}", url, label));
+        }
+
+        fn on_pre_start(&mut self, alt: Option<&'a str>) {
+            self.0.push(format!("pre {:?}", alt));
+        }
+
+        fn on_pre_line(&mut self, text: &'a str) {
+            self.0.push(format!("| {}", text));
+        }
+
+        fn on_pre_end(&mut self) {
+            self.0.push(String::from("end"));
+        }
+    }
+
+    #[test]
+    fn test_parse_with() {
+        let mut rec = Recorder::default();
+        parse_with(
+            "# Title\nignored text\n=> /a A\n```sh\n=> raw\n```\n* item\n```\nopen",
+            &mut rec,
+        );
+        assert_eq!(
+            rec.0,
+            [
+                "h1 Title",
+                "link /a Some(\"A\")",
+                "pre Some(\"sh\")",
+                "| => raw",
+                "end",
+                "pre None",
+                "| open",
+                "end",
+            ]
+        );
+    }
+}
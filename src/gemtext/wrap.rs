@@ -0,0 +1,210 @@
+use super::{Line, Lines};
+use alloc::string::String;
+
+const ZERO_WIDTH: &[(u32, u32)] = &[
+    (0x0300, 0x036F),
+    (0x0483, 0x0489),
+    (0x0591, 0x05BD),
+    (0x0610, 0x061A),
+    (0x064B, 0x065F),
+    (0x0E31, 0x0E31),
+    (0x0E34, 0x0E3A),
+    (0x1AB0, 0x1AFF),
+    (0x1DC0, 0x1DFF),
+    (0x200B, 0x200F),
+    (0x2028, 0x202E),
+    (0x2060, 0x2064),
+    (0x20D0, 0x20FF),
+    (0xFE00, 0xFE0F),
+    (0xFE20, 0xFE2F),
+    (0xFEFF, 0xFEFF),
+    (0xE0100, 0xE01EF),
+];
+
+const WIDE: &[(u32, u32)] = &[
+    (0x1100, 0x115F),
+    (0x231A, 0x231B),
+    (0x2329, 0x232A),
+    (0x23E9, 0x23EC),
+    (0x25FD, 0x25FE),
+    (0x2614, 0x2615),
+    (0x2648, 0x2653),
+    (0x26AA, 0x26AB),
+    (0x26BD, 0x26BE),
+    (0x26C4, 0x26C5),
+    (0x26F2, 0x26F5),
+    (0x2705, 0x2705),
+    (0x270A, 0x270B),
+    (0x2753, 0x2755),
+    (0x2795, 0x2797),
+    (0x2B1B, 0x2B1C),
+    (0x2E80, 0x303E),
+    (0x3041, 0x33FF),
+    (0x3400, 0x4DBF),
+    (0x4E00, 0x9FFF),
+    (0xA000, 0xA4CF),
+    (0xA960, 0xA97F),
+    (0xAC00, 0xD7A3),
+    (0xF900, 0xFAFF),
+    (0xFE10, 0xFE19),
+    (0xFE30, 0xFE6F),
+    (0xFF00, 0xFF60),
+    (0xFFE0, 0xFFE6),
+    (0x16FE0, 0x16FE4),
+    (0x17000, 0x18CFF),
+    (0x1B000, 0x1B2FF),
+    (0x1F004, 0x1F004),
+    (0x1F0CF, 0x1F0CF),
+    (0x1F18E, 0x1F18E),
+    (0x1F191, 0x1F19A),
+    (0x1F200, 0x1F251),
+    (0x1F300, 0x1F64F),
+    (0x1F680, 0x1F6FF),
+    (0x1F7E0, 0x1F7EB),
+    (0x1F90C, 0x1F9FF),
+    (0x1FA70, 0x1FAFF),
+    (0x20000, 0x2FFFD),
+    (0x30000, 0x3FFFD),
+];
+
+fn in_table(table: &[(u32, u32)], c: u32) -> bool {
+    table
+        .binary_search_by(|&(lo, hi)| {
+            if hi < c {
+                core::cmp::Ordering::Less
+            } else if lo > c {
+                core::cmp::Ordering::Greater
+            } else {
+                core::cmp::Ordering::Equal
+            }
+        })
+        .is_ok()
+}
+
+pub fn char_width(c: char) -> usize {
+    let c = c as u32;
+    if c < 0x20 || (0x7F..0xA0).contains(&c) || in_table(ZERO_WIDTH, c) {
+        0
+    } else if in_table(WIDE, c) {
+        2
+    } else {
+        1
+    }
+}
+
+pub fn str_width(s: &str) -> usize {
+    s.chars().map(char_width).sum()
+}
+
+pub fn reflow(body: &str, width: usize) -> String {
+    let mut out = String::with_capacity(body.len());
+    for (span, line) in Lines::new(body).spanned() {
+        match line {
+            Line::Text(text) => wrap(text, "", "", width, &mut out),
+            Line::ListItem(text) => wrap(text, "* ", "  ", width, &mut out),
+            Line::Quote(text) => wrap(text, "> ", "> ", width, &mut out),
+            Line::Heading { level, text } => {
+                let marker = &"### "[3 - level as usize..];
+                let indent = &"    "[..marker.len()];
+                wrap(text, marker, indent, width, &mut out);
+            }
+            _ => {
+                out.push_str(&body[span.range()]);
+                out.push('\n');
+            }
+        }
+    }
+
+    out
+}
+
+fn wrap(text: &str, first: &str, rest: &str, width: usize, out: &mut String) {
+    let avail = width.saturating_sub(str_width(rest)).max(1);
+    let mut len = 0;
+
+    out.push_str(first);
+    for word in text.split_whitespace() {
+        let mut word = word;
+        let mut word_len = str_width(word);
+        loop {
+            if len > 0 && width > 0 && len + 1 + word_len > avail {
+                out.push('\n');
+                out.push_str(rest);
+                len = 0;
+            }
+            if len > 0 {
+                out.push(' ');
+                len += 1;
+            }
+            if width == 0 || word_len <= avail {
+                out.push_str(word);
+                len += word_len;
+                break;
+            }
+
+            let (head, tail, head_len) = split_at_width(word, avail);
+            out.push_str(head);
+            len += head_len;
+            word = tail;
+            word_len -= head_len;
+        }
+    }
+    out.push('\n');
+}
+
+fn split_at_width(word: &str, avail: usize) -> (&str, &str, usize) {
+    let mut len = 0;
+    for (i, c) in word.char_indices() {
+        let w = char_width(c);
+        if len + w > avail && i > 0 {
+            return (&word[..i], &word[i..], len);
+        }
+        len += w;
+    }
+    (word, "", len)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_width() {
+        assert_eq!(str_width("abc"), 3);
+        assert_eq!(str_width("日本語"), 6);
+        assert_eq!(str_width("e\u{301}"), 1);
+        assert_eq!(str_width("한국"), 4);
+        assert_eq!(str_width("🦀"), 2);
+        assert_eq!(str_width("\u{200b}\t"), 0);
+    }
+
+    #[test]
+    fn test_reflow() {
+        let body = "one two three four\r\n\
+            * alpha beta gamma\n\
+            > quoted words here\n\
+            ## long heading text\n\
+            => gemini://example.com/a/very/long/path a long link label\n\
+            ```\n\
+            keep this   preformatted line as is\n\
+            ```\n\
+            日本語のテキスト\n\
+            \n\
+            abcdefghijklmnop\n";
+        assert_eq!(
+            reflow(body, 10),
+            "one two\nthree four\n\
+             * alpha\n  beta\n  gamma\n\
+             > quoted\n> words\n> here\n\
+             ## long\n   heading\n   text\n\
+             => gemini://example.com/a/very/long/path a long link label\n\
+             ```\n\
+             keep this   preformatted line as is\n\
+             ```\n\
+             日本語のテ\nキスト\n\
+             \n\
+             abcdefghij\nklmnop\n"
+        );
+        assert_eq!(reflow("a b c", 0), "a b c\n");
+    }
+}
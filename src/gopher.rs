@@ -0,0 +1,259 @@
+use crate::gemtext::Builder;
+use alloc::{format, string::String};
+use core::str;
+use percent_encoding::{utf8_percent_encode, AsciiSet, CONTROLS};
+
+pub const DEFAULT_PORT: u16 = 70;
+
+const SELECTOR: &AsciiSet = &CONTROLS
+    .add(b' ')
+    .add(b'"')
+    .add(b'#')
+    .add(b'%')
+    .add(b'<')
+    .add(b'>')
+    .add(b'?')
+    .add(b'`');
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum ItemType {
+    File,
+    Directory,
+    CsoServer,
+    Error,
+    BinHex,
+    Dos,
+    Uuencoded,
+    Search,
+    Telnet,
+    Binary,
+    Mirror,
+    Gif,
+    Image,
+    Tn3270,
+    Html,
+    Info,
+    Sound,
+    Document,
+    Other(u8),
+}
+
+impl ItemType {
+    pub fn from_byte(b: u8) -> Self {
+        match b {
+            b'0' => ItemType::File,
+            b'1' => ItemType::Directory,
+            b'2' => ItemType::CsoServer,
+            b'3' => ItemType::Error,
+            b'4' => ItemType::BinHex,
+            b'5' => ItemType::Dos,
+            b'6' => ItemType::Uuencoded,
+            b'7' => ItemType::Search,
+            b'8' => ItemType::Telnet,
+            b'9' => ItemType::Binary,
+            b'+' => ItemType::Mirror,
+            b'g' => ItemType::Gif,
+            b'I' => ItemType::Image,
+            b'T' => ItemType::Tn3270,
+            b'h' => ItemType::Html,
+            b'i' => ItemType::Info,
+            b's' => ItemType::Sound,
+            b'd' => ItemType::Document,
+            b => ItemType::Other(b),
+        }
+    }
+
+    pub fn as_byte(self) -> u8 {
+        match self {
+            ItemType::File => b'0',
+            ItemType::Directory => b'1',
+            ItemType::CsoServer => b'2',
+            ItemType::Error => b'3',
+            ItemType::BinHex => b'4',
+            ItemType::Dos => b'5',
+            ItemType::Uuencoded => b'6',
+            ItemType::Search => b'7',
+            ItemType::Telnet => b'8',
+            ItemType::Binary => b'9',
+            ItemType::Mirror => b'+',
+            ItemType::Gif => b'g',
+            ItemType::Image => b'I',
+            ItemType::Tn3270 => b'T',
+            ItemType::Html => b'h',
+            ItemType::Info => b'i',
+            ItemType::Sound => b's',
+            ItemType::Document => b'd',
+            ItemType::Other(b) => b,
+        }
+    }
+
+    #[inline]
+    pub fn is_text(self) -> bool {
+        matches!(self, ItemType::Info | ItemType::Error)
+    }
+
+    pub fn scheme(self) -> Option<&'static str> {
+        match self {
+            ItemType::Info | ItemType::Error => None,
+            ItemType::Telnet => Some("telnet"),
+            ItemType::Tn3270 => Some("tn3270"),
+            _ => Some("gopher"),
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Item<'a> {
+    pub kind: ItemType,
+    pub display: &'a str,
+    pub selector: &'a str,
+    pub host: &'a str,
+    pub port: Option<u16>,
+}
+
+impl<'a> Item<'a> {
+    pub fn parse(line: &'a str) -> Self {
+        let line = line.strip_suffix('\r').unwrap_or(line);
+        let kind = match line.bytes().next() {
+            Some(b) if b.is_ascii() => ItemType::from_byte(b),
+            _ => ItemType::Info,
+        };
+        let rest = match kind {
+            ItemType::Info if !line.starts_with('i') => line,
+            _ => &line[1..],
+        };
+
+        let mut fields = rest.split('\t');
+        let display = fields.next().unwrap_or("");
+        let selector = fields.next().unwrap_or("");
+        let host = fields.next().unwrap_or("");
+        let port = fields.next().and_then(|p| p.trim().parse().ok());
+        Self {
+            kind,
+            display,
+            selector,
+            host,
+            port,
+        }
+    }
+
+    pub fn url(&self) -> Option<String> {
+        let scheme = self.kind.scheme()?;
+        if let Some(url) = self.selector.strip_prefix("URL:") {
+            return Some(String::from(url));
+        }
+        if self.host.is_empty() {
+            return None;
+        }
+
+        let authority = match self.port {
+            Some(port) if scheme != "gopher" || port != DEFAULT_PORT => {
+                format!("{}:{}", self.host, port)
+            }
+            _ => String::from(self.host),
+        };
+        Some(match scheme {
+            "gopher" => format!(
+                "gopher://{}/{}{}",
+                authority,
+                self.kind.as_byte() as char,
+                utf8_percent_encode(self.selector, SELECTOR)
+            ),
+            scheme => format!("{}://{}", scheme, authority),
+        })
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Items<'a> {
+    lines: str::Lines<'a>,
+}
+
+impl<'a> Iterator for Items<'a> {
+    type Item = Item<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let line = self.lines.next()?;
+        if line.strip_suffix('\r').unwrap_or(line) == "." {
+            self.lines = "".lines();
+            return None;
+        }
+        Some(Item::parse(line))
+    }
+}
+
+pub fn items(menu: &str) -> Items<'_> {
+    Items {
+        lines: menu.lines(),
+    }
+}
+
+pub fn to_gemtext(menu: &str) -> String {
+    let mut builder = Builder::new();
+    for item in items(menu) {
+        match item.url() {
+            Some(url) => builder.link(&url, Some(item.display)),
+            None => builder.text(item.display),
+        };
+    }
+    builder.build()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_item() {
+        let item = Item::parse("1Phlog\t/phlog\texample.org\t70\r");
+        assert_eq!(item.kind, ItemType::Directory);
+        assert_eq!(item.display, "Phlog");
+        assert_eq!(item.selector, "/phlog");
+        assert_eq!(item.host, "example.org");
+        assert_eq!(item.port, Some(70));
+        assert_eq!(item.url().as_deref(), Some("gopher://example.org/1/phlog"));
+
+        let item = Item::parse("0About me\t/about me.txt\texample.org\t7070");
+        assert_eq!(
+            item.url().as_deref(),
+            Some("gopher://example.org:7070/0/about%20me.txt")
+        );
+
+        let item = Item::parse("8Login\t\tbbs.example.org\t23");
+        assert_eq!(item.url().as_deref(), Some("telnet://bbs.example.org:23"));
+
+        let item = Item::parse("hWeb\tURL:https://example.org/\t\t0");
+        assert_eq!(item.kind, ItemType::Html);
+        assert_eq!(item.url().as_deref(), Some("https://example.org/"));
+
+        let item = Item::parse("iWelcome\t\terror.host\t1");
+        assert!(item.kind.is_text());
+        assert_eq!(item.url(), None);
+
+        let item = Item::parse("");
+        assert_eq!(item.kind, ItemType::Info);
+        assert_eq!(item.display, "");
+
+        assert_eq!(ItemType::from_byte(b'Z'), ItemType::Other(b'Z'));
+        assert_eq!(ItemType::Other(b'Z').as_byte(), b'Z');
+        assert_eq!(ItemType::from_byte(b'g').as_byte(), b'g');
+    }
+
+    #[test]
+    fn test_to_gemtext() {
+        let menu = "i# Welcome\t\tnull.host\t1\r\n\
+                    1Phlog\t/phlog\texample.org\t70\r\n\
+                    7Search\t/search\texample.org\t70\r\n\
+                    3Oops\t\terror.host\t1\r\n\
+                    .\r\n\
+                    1Ignored\t/\texample.org\t70\r\n";
+        assert_eq!(
+            to_gemtext(menu),
+            " # Welcome\n\
+             => gopher://example.org/1/phlog Phlog\n\
+             => gopher://example.org/7/search Search\n\
+             Oops\n"
+        );
+        assert_eq!(items(menu).count(), 4);
+    }
+}
@@ -0,0 +1,279 @@
+use crate::iter::Bytes;
+use crate::util::push_decimal;
+use crate::{next_line_limit, Error, Result, Status, Url, META_MAX_LENGTH};
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::{result, str};
+
+pub const DEFAULT_PORT: u16 = 6775;
+
+const REQUEST_MAX_LENGTH: usize = 2048;
+const INPUT: u32 = 1;
+const REDIRECT: u32 = 3;
+const ERROR: u32 = 4;
+const FIRST_DATA: u32 = 6;
+
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Request {
+    pub url: Option<Url>,
+}
+
+impl Request {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn parse(&mut self, buf: &[u8]) -> Result<usize> {
+        let mut bytes = Bytes::new(buf);
+        let end = complete!(next_line_limit(
+            &mut bytes,
+            REQUEST_MAX_LENGTH - 2,
+            Error::UriTooLong
+        ));
+        let url = Url::parse(str::from_utf8(&buf[..end])?)?;
+        if url.scheme() != "guppy" {
+            return Err(Error::Scheme);
+        }
+
+        self.url = Some(url);
+        Ok(Status::Complete(bytes.pos))
+    }
+
+    pub fn encode(&self, buf: &mut Vec<u8>) -> result::Result<(), Error> {
+        let url = self.url.as_ref().ok_or(Error::EmptyRequest)?;
+        if url.scheme() != "guppy" {
+            return Err(Error::Scheme);
+        }
+        if url.as_str().len() + 2 > REQUEST_MAX_LENGTH {
+            return Err(Error::UriTooLong);
+        }
+
+        buf.extend_from_slice(url.as_str().as_bytes());
+        buf.extend_from_slice(b"\r\n");
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Response {
+    pub seq: Option<u32>,
+    pub meta: Option<String>,
+}
+
+impl Response {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn parse(&mut self, buf: &[u8]) -> Result<usize> {
+        let mut bytes = Bytes::new(buf);
+        let seq = complete!(parse_seq(&mut bytes));
+        if seq < FIRST_DATA && !matches!(seq, INPUT | REDIRECT | ERROR) {
+            return Err(Error::Status);
+        }
+
+        let start = bytes.pos;
+        let end = complete!(next_line_limit(
+            &mut bytes,
+            META_MAX_LENGTH + 1,
+            Error::MetaTooLong
+        ));
+        let meta = match &buf[start..end] {
+            [] if seq >= FIRST_DATA => None,
+            [b' ', meta @ ..] => Some(String::from(str::from_utf8(meta)?)),
+            _ => return Err(Error::MissingSpaceAfterStatus),
+        };
+
+        self.seq = Some(seq);
+        self.meta = meta;
+        Ok(Status::Complete(bytes.pos))
+    }
+
+    pub fn encode(&self, buf: &mut Vec<u8>) -> result::Result<(), Error> {
+        let seq = self.seq.ok_or(Error::Status)?;
+        if seq < FIRST_DATA && !matches!(seq, INPUT | REDIRECT | ERROR) {
+            return Err(Error::Status);
+        }
+
+        match self.meta.as_deref() {
+            Some(meta) if meta.len() > META_MAX_LENGTH => return Err(Error::ResponseHeader),
+            Some(meta) if meta.bytes().any(|b| b == b'\r' || b == b'\n') => {
+                return Err(Error::ResponseHeader)
+            }
+            None if seq < FIRST_DATA => return Err(Error::ResponseHeader),
+            _ => {}
+        }
+
+        push_decimal(buf, seq as usize);
+        if let Some(meta) = self.meta.as_deref() {
+            buf.push(b' ');
+            buf.extend_from_slice(meta.as_bytes());
+        }
+        buf.extend_from_slice(b"\r\n");
+        Ok(())
+    }
+
+    #[inline]
+    pub fn is_input(&self) -> bool {
+        self.seq == Some(INPUT)
+    }
+
+    #[inline]
+    pub fn is_redirect(&self) -> bool {
+        self.seq == Some(REDIRECT)
+    }
+
+    #[inline]
+    pub fn is_error(&self) -> bool {
+        self.seq == Some(ERROR)
+    }
+
+    #[inline]
+    pub fn is_data(&self) -> bool {
+        self.seq.is_some_and(|seq| seq >= FIRST_DATA)
+    }
+
+    pub fn mime(&self) -> Option<&str> {
+        self.meta.as_deref().filter(|_| self.is_data())
+    }
+}
+
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub struct Ack {
+    pub seq: Option<u32>,
+}
+
+impl Ack {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn parse(&mut self, buf: &[u8]) -> Result<usize> {
+        let mut bytes = Bytes::new(buf);
+        let seq = complete!(parse_seq(&mut bytes));
+        if seq < FIRST_DATA {
+            return Err(Error::Status);
+        }
+
+        complete!(next_line_limit(&mut bytes, 0, Error::RequestLine));
+        self.seq = Some(seq);
+        Ok(Status::Complete(bytes.pos))
+    }
+
+    pub fn encode(&self, buf: &mut Vec<u8>) -> result::Result<(), Error> {
+        let seq = self
+            .seq
+            .filter(|&seq| seq >= FIRST_DATA)
+            .ok_or(Error::Status)?;
+        push_decimal(buf, seq as usize);
+        buf.extend_from_slice(b"\r\n");
+        Ok(())
+    }
+}
+
+fn parse_seq(bytes: &mut Bytes) -> Result<u32> {
+    let mut seq: u32 = 0;
+    let mut digits = 0;
+    while let Some(b @ b'0'..=b'9') = bytes.peek() {
+        seq = seq
+            .checked_mul(10)
+            .and_then(|seq| seq.checked_add((b - b'0') as u32))
+            .ok_or(Error::Status)?;
+        digits += 1;
+        bytes.bump();
+    }
+    match (digits, bytes.peek()) {
+        (_, None) => Ok(Status::Partial(Some(1))),
+        (0, Some(..)) => Err(Error::Status),
+        _ => Ok(Status::Complete(seq)),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_request() {
+        let buf = b"guppy://example.com/index.gmi\r\n";
+        let mut req = Request::new();
+        assert_eq!(req.parse(buf), Ok(Status::Complete(31)));
+        assert_eq!(
+            req.url.as_ref().map(Url::as_str),
+            Some("guppy://example.com/index.gmi")
+        );
+
+        let mut out = Vec::new();
+        req.encode(&mut out).unwrap();
+        assert_eq!(out, buf);
+
+        let mut req = Request::new();
+        assert_eq!(
+            req.parse(b"guppy://example.com/"),
+            Ok(Status::Partial(Some(1)))
+        );
+        assert_eq!(req.parse(b"gemini://example.com/\r\n"), Err(Error::Scheme));
+    }
+
+    #[test]
+    fn test_response() {
+        let mut res = Response::new();
+        let buf = b"7 text/gemini\r\n# Hello";
+        assert_eq!(res.parse(buf), Ok(Status::Complete(15)));
+        assert_eq!(res.seq, Some(7));
+        assert_eq!(res.mime(), Some("text/gemini"));
+        assert!(res.is_data());
+
+        let mut out = Vec::new();
+        res.encode(&mut out).unwrap();
+        assert_eq!(out, &buf[..15]);
+
+        assert_eq!(res.parse(b"8\r\nmore"), Ok(Status::Complete(3)));
+        assert_eq!(res.seq, Some(8));
+        assert_eq!(res.meta, None);
+
+        res.parse(b"1 Your name?\r\n").unwrap();
+        assert!(res.is_input());
+        assert_eq!(res.meta.as_deref(), Some("Your name?"));
+        assert_eq!(res.mime(), None);
+        res.parse(b"3 guppy://example.com/new\r\n").unwrap();
+        assert!(res.is_redirect());
+        res.parse(b"4 not found\r\n").unwrap();
+        assert!(res.is_error());
+
+        assert_eq!(res.parse(b"7 text/plain"), Ok(Status::Partial(Some(1))));
+        assert_eq!(res.parse(b"42"), Ok(Status::Partial(Some(1))));
+        assert_eq!(res.parse(b"2 x\r\n"), Err(Error::Status));
+        assert_eq!(res.parse(b"x\r\n"), Err(Error::Status));
+        assert_eq!(res.parse(b"99999999999\r\n"), Err(Error::Status));
+        assert_eq!(res.parse(b"4\r\n"), Err(Error::MissingSpaceAfterStatus));
+        assert_eq!(res.parse(b"7x\r\n"), Err(Error::MissingSpaceAfterStatus));
+
+        let mut out = Vec::new();
+        res.seq = Some(3);
+        res.meta = None;
+        assert_eq!(res.encode(&mut out), Err(Error::ResponseHeader));
+        res.seq = Some(7);
+        res.meta = Some(String::from("text/plain\r\n"));
+        assert_eq!(res.encode(&mut out), Err(Error::ResponseHeader));
+        assert!(out.is_empty());
+    }
+
+    #[test]
+    fn test_ack() {
+        let mut ack = Ack::new();
+        assert_eq!(ack.parse(b"7\r\n"), Ok(Status::Complete(3)));
+        assert_eq!(ack.seq, Some(7));
+
+        let mut out = Vec::new();
+        ack.encode(&mut out).unwrap();
+        assert_eq!(out, b"7\r\n");
+
+        assert_eq!(ack.parse(b"7"), Ok(Status::Partial(Some(1))));
+        assert_eq!(ack.parse(b"7 x\r\n"), Err(Error::RequestLine));
+        assert_eq!(ack.parse(b"4\r\n"), Err(Error::Status));
+    }
+}
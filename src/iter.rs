@@ -1,4 +1,8 @@
-use std::ops;
+use core::mem;
+
+const WORD: usize = mem::size_of::<usize>();
+const LO: usize = usize::MAX / 255;
+const HI: usize = LO * 0x80;
 
 pub struct Bytes<'a> {
     pub slice: &'a [u8],
@@ -8,10 +12,7 @@ pub struct Bytes<'a> {
 impl<'a> Bytes<'a> {
     #[inline]
     pub fn new(slice: &'a [u8]) -> Self {
-        Self {
-            slice: slice,
-            pos: 0,
-        }
+        Self { slice, pos: 0 }
     }
 
     #[inline]
@@ -20,9 +21,47 @@ impl<'a> Bytes<'a> {
     }
 
     #[inline]
-    pub unsafe fn bump(&mut self) {
-        self.pos += 1;
+    pub fn bump(&mut self) {
+        self.pos = self.pos.saturating_add(1);
+    }
+
+    #[inline]
+    pub fn advance(&mut self, n: usize) {
+        self.pos = self.pos.saturating_add(n);
+    }
+
+    #[inline]
+    pub fn remaining(&self) -> &'a [u8] {
+        self.slice.get(self.pos..).unwrap_or(&[])
+    }
+}
+
+#[inline]
+fn has_zero(x: usize) -> bool {
+    x.wrapping_sub(LO) & !x & HI != 0
+}
+
+#[inline]
+pub fn find_eol(haystack: &[u8]) -> Option<usize> {
+    let cr = LO * b'\r' as usize;
+    let lf = LO * b'\n' as usize;
+
+    let mut chunks = haystack.chunks_exact(WORD);
+    let mut offset = 0;
+    for chunk in &mut chunks {
+        let mut word = [0; WORD];
+        word.copy_from_slice(chunk);
+        let x = usize::from_ne_bytes(word);
+        if has_zero(x ^ cr) || has_zero(x ^ lf) {
+            break;
+        }
+        offset += WORD;
     }
+
+    haystack[offset..]
+        .iter()
+        .position(|&b| b == b'\r' || b == b'\n')
+        .map(|i| offset + i)
 }
 
 impl<'a> AsRef<[u8]> for Bytes<'a> {
@@ -36,24 +75,9 @@ impl<'a> Iterator for Bytes<'a> {
 
     #[inline]
     fn next(&mut self) -> Option<u8> {
-        if self.pos < self.slice.len() {
-            let b = unsafe { *self.slice.get_unchecked(self.pos) };
-            self.pos += 1;
-            Some(b)
-        } else {
-            None
-        }
-    }
-}
-
-impl<'a, Idx> ops::Index<Idx> for Bytes<'a>
-where
-    Idx: std::slice::SliceIndex<[u8]>,
-{
-    type Output = Idx::Output;
-
-    fn index(&self, index: Idx) -> &Self::Output {
-        &self.as_ref()[index]
+        let b = self.peek()?;
+        self.pos += 1;
+        Some(b)
     }
 }
 
@@ -61,16 +85,19 @@ macro_rules! complete {
     ($e:expr) => {
         match $e? {
             Status::Complete(v) => v,
-            Status::Partial => return Ok(Status::Partial),
+            Status::Partial(needed) => return Ok(Status::Partial(needed)),
         }
     };
 }
 
 macro_rules! next {
     ($bytes:expr) => {
+        next!($bytes, 1)
+    };
+    ($bytes:expr, $needed:expr) => {
         match $bytes.next() {
             Some(v) => v,
-            None => return Ok(Status::Partial),
+            None => return Ok(Status::Partial(Some($needed))),
         }
     };
 }
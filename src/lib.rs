@@ -1,93 +1,1234 @@
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
+#![cfg_attr(feature = "safe", forbid(unsafe_code))]
+
+extern crate alloc;
+
 #[macro_use]
 mod iter;
+mod builder;
+mod parser;
+mod simd;
+mod status;
+mod uri;
+mod util;
+
+#[cfg(feature = "serde")]
+mod serde_impls;
+
+#[cfg(feature = "std")]
+pub mod body;
+pub mod client;
+#[cfg(all(feature = "ffi", not(feature = "safe")))]
+pub mod ffi;
+pub mod gemtext;
+pub mod gopher;
+pub mod guppy;
+pub mod meta;
+pub mod misfin;
+pub mod nex;
+pub mod robots;
+pub mod router;
+pub mod server;
+pub mod spartan;
+pub mod titan;
 
+pub use builder::{MetaBuilder, RequestBuilder, ResponseBuilder};
+pub use parser::{RequestParser, ResponseParser};
+pub use status::{CertificateRequirement, StatusClass, StatusCode};
+pub use uri::{Span, Uri};
+
+#[cfg(not(feature = "url"))]
+pub use uri::{ParseError as UrlParseError, Uri as Url};
+#[cfg(feature = "url")]
+pub use url::{ParseError as UrlParseError, Url};
+
+use alloc::{borrow::Cow, string::String, vec::Vec};
+use core::{error, fmt, result, str, time::Duration};
 use iter::Bytes;
-use std::{result, str};
-use url::{self, Url};
+use meta::{MediaType, MetaString};
+#[cfg(feature = "std")]
+use std::io;
+use uri::Parts;
 
 const META_MAX_LENGTH: usize = 1024;
+const URL_MAX_LENGTH: usize = 1024;
+const BOM: &[u8] = b"\xef\xbb\xbf";
+pub(crate) const DEFAULT_META: &str = "text/gemini; charset=utf-8";
+
+pub const DEFAULT_PORT: u16 = 1965;
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
 pub enum Error {
     NewLine,
     InvalidUtf8(str::Utf8Error),
-    ParseUrl(url::ParseError),
+    ParseUrl(UrlParseError),
     ResponseHeader,
     Status,
+    EmptyRequest,
+    UriTooLong,
+    TitanParam,
+    MediaType,
+    NotRedirect,
+    CrossSchemeRedirect,
+    RequestLine,
+    Scheme,
+    UserInfo,
+    MissingHost,
+    Fragment,
+    ByteOrderMark,
+    BareLineFeed,
+    ConnectionState,
+    UnexpectedBody,
+    Meta,
+    TooManyRedirects,
+    RedirectLoop,
+    LanguageTag,
+    LimitExceeded,
+    MetaTooLong,
+    MissingSpaceAfterStatus,
+    LoneCarriageReturn,
+    BodyTooShort,
+    BodyTooLong,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::NewLine => f.write_str("invalid line ending"),
+            Error::InvalidUtf8(err) => write!(f, "invalid utf-8: {}", err),
+            Error::ParseUrl(err) => write!(f, "invalid url: {}", err),
+            Error::ResponseHeader => f.write_str("invalid response header"),
+            Error::Status => f.write_str("invalid status code"),
+            Error::EmptyRequest => f.write_str("empty request"),
+            Error::UriTooLong => f.write_str("request url too long"),
+            Error::TitanParam => f.write_str("invalid titan parameter"),
+            Error::MediaType => f.write_str("invalid media type"),
+            Error::NotRedirect => f.write_str("response is not a redirect"),
+            Error::CrossSchemeRedirect => f.write_str("redirect changes url scheme"),
+            Error::RequestLine => f.write_str("invalid request line"),
+            Error::Scheme => f.write_str("unsupported url scheme"),
+            Error::UserInfo => f.write_str("url contains userinfo"),
+            Error::MissingHost => f.write_str("url has no host"),
+            Error::Fragment => f.write_str("url contains a fragment"),
+            Error::ByteOrderMark => f.write_str("unexpected byte order mark"),
+            Error::BareLineFeed => f.write_str("line feed without carriage return"),
+            Error::ConnectionState => f.write_str("operation not valid in connection state"),
+            Error::UnexpectedBody => f.write_str("response body without success status"),
+            Error::Meta => f.write_str("meta does not match response status"),
+            Error::TooManyRedirects => f.write_str("too many redirects"),
+            Error::RedirectLoop => f.write_str("redirect loop detected"),
+            Error::LanguageTag => f.write_str("invalid language tag"),
+            Error::LimitExceeded => f.write_str("document exceeds parser limits"),
+            Error::MetaTooLong => f.write_str("response meta too long"),
+            Error::MissingSpaceAfterStatus => f.write_str("missing space after status code"),
+            Error::LoneCarriageReturn => f.write_str("carriage return without line feed"),
+            Error::BodyTooShort => f.write_str("body shorter than declared size"),
+            Error::BodyTooLong => f.write_str("body longer than declared size"),
+        }
+    }
+}
+
+impl Error {
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            Error::InvalidUtf8(err) => err.error_len().is_none(),
+            Error::ConnectionState => true,
+            _ => false,
+        }
+    }
+}
+
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Error::InvalidUtf8(err) => Some(err),
+            Error::ParseUrl(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl From<Error> for io::Error {
+    fn from(err: Error) -> Self {
+        let kind = match err {
+            Error::InvalidUtf8(err) if err.error_len().is_none() => io::ErrorKind::UnexpectedEof,
+            Error::BodyTooShort => io::ErrorKind::UnexpectedEof,
+            Error::ConnectionState
+            | Error::NotRedirect
+            | Error::CrossSchemeRedirect
+            | Error::TooManyRedirects
+            | Error::RedirectLoop => io::ErrorKind::Other,
+            _ => io::ErrorKind::InvalidData,
+        };
+        io::Error::new(kind, err)
+    }
+}
+
+impl From<UrlParseError> for Error {
+    fn from(err: UrlParseError) -> Self {
+        Error::ParseUrl(err)
+    }
+}
+
+impl From<str::Utf8Error> for Error {
+    fn from(err: str::Utf8Error) -> Self {
+        Error::InvalidUtf8(err)
+    }
+}
+
+pub type Result<T> = result::Result<Status<T>, Error>;
+
+pub type LocatedResult<T> = result::Result<Status<T>, ParseError>;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct ParseError {
+    pub kind: Error,
+    pub offset: usize,
+}
+
+impl ParseError {
+    #[inline]
+    pub fn new(kind: Error, offset: usize) -> Self {
+        Self { kind, offset }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at byte {}", self.kind, self.offset)
+    }
+}
+
+impl error::Error for ParseError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        Some(&self.kind)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Status<T> {
+    Complete(T),
+    Partial(Option<usize>),
+}
+
+impl<T> Status<T> {
+    #[inline]
+    pub fn is_complete(&self) -> bool {
+        matches!(self, Status::Complete(_))
+    }
+
+    #[inline]
+    pub fn needed(&self) -> Option<usize> {
+        match self {
+            Status::Complete(_) => None,
+            Status::Partial(needed) => *needed,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParserConfig {
+    allow_bare_lf: bool,
+    allow_empty_meta: bool,
+    allow_trailing_whitespace: bool,
+    allow_proxy_requests: bool,
+    allow_invalid_utf8: bool,
+    strip_bom: bool,
+    validate_requests: bool,
+    validate_meta: bool,
+    max_url_length: usize,
+    max_meta_length: usize,
+    schemes: &'static [&'static str],
+}
+
+impl Default for ParserConfig {
+    #[inline]
+    fn default() -> Self {
+        Self::lenient()
+    }
+}
+
+impl ParserConfig {
+    pub fn lenient() -> Self {
+        Self {
+            allow_bare_lf: true,
+            allow_empty_meta: true,
+            allow_trailing_whitespace: true,
+            allow_proxy_requests: false,
+            allow_invalid_utf8: false,
+            strip_bom: true,
+            validate_requests: false,
+            validate_meta: false,
+            max_url_length: URL_MAX_LENGTH,
+            max_meta_length: META_MAX_LENGTH,
+            schemes: &["gemini"],
+        }
+    }
+
+    pub fn strict() -> Self {
+        Self {
+            allow_bare_lf: false,
+            allow_trailing_whitespace: false,
+            strip_bom: false,
+            validate_requests: true,
+            ..Self::lenient()
+        }
+    }
+
+    pub fn allow_bare_lf(&mut self, value: bool) -> &mut Self {
+        self.allow_bare_lf = value;
+        self
+    }
+
+    pub fn allow_empty_meta(&mut self, value: bool) -> &mut Self {
+        self.allow_empty_meta = value;
+        self
+    }
+
+    pub fn allow_trailing_whitespace(&mut self, value: bool) -> &mut Self {
+        self.allow_trailing_whitespace = value;
+        self
+    }
+
+    pub fn allow_proxy_requests(&mut self, value: bool) -> &mut Self {
+        self.allow_proxy_requests = value;
+        self
+    }
+
+    pub fn allow_invalid_utf8(&mut self, value: bool) -> &mut Self {
+        self.allow_invalid_utf8 = value;
+        self
+    }
+
+    pub fn strip_bom(&mut self, value: bool) -> &mut Self {
+        self.strip_bom = value;
+        self
+    }
+
+    pub fn validate_requests(&mut self, value: bool) -> &mut Self {
+        self.validate_requests = value;
+        self
+    }
+
+    pub fn validate_meta(&mut self, value: bool) -> &mut Self {
+        self.validate_meta = value;
+        self
+    }
+
+    pub fn max_url_length(&mut self, value: usize) -> &mut Self {
+        self.max_url_length = value;
+        self
+    }
+
+    pub fn max_meta_length(&mut self, value: usize) -> &mut Self {
+        self.max_meta_length = value;
+        self
+    }
+
+    pub fn schemes(&mut self, value: &'static [&'static str]) -> &mut Self {
+        self.schemes = value;
+        self
+    }
+
+    #[inline]
+    fn allows_scheme(&self, scheme: &str) -> bool {
+        self.allow_proxy_requests || self.schemes.iter().any(|s| s.eq_ignore_ascii_case(scheme))
+    }
+
+    pub fn parse_request(&self, req: &mut Request, buf: &[u8]) -> Result<usize> {
+        req.parse_with_config(buf, self)
+    }
+
+    pub fn parse_request_ref<'a>(&self, req: &mut RequestRef<'a>, buf: &'a [u8]) -> Result<usize> {
+        req.parse_with_config(buf, self)
+    }
+
+    pub fn parse_response<'a>(&self, res: &mut ResponseRef<'a>, buf: &'a [u8]) -> Result<usize> {
+        res.parse_with_config(buf, self)
+    }
+
+    #[inline]
+    fn skip_bom(&self, bytes: &mut Bytes) -> result::Result<(), ParseError> {
+        if !bytes.remaining().starts_with(BOM) {
+            return Ok(());
+        }
+        if !self.strip_bom {
+            return Err(ParseError::new(Error::ByteOrderMark, bytes.pos));
+        }
+        bytes.advance(BOM.len());
+        Ok(())
+    }
+
+    #[inline]
+    fn check_line(&self, line: &[u8], terminator: u8) -> result::Result<(), Error> {
+        if !self.allow_bare_lf && terminator == b'\n' {
+            return Err(Error::BareLineFeed);
+        }
+
+        if !self.allow_trailing_whitespace && matches!(line.last(), Some(b' ' | b'\t')) {
+            return Err(Error::NewLine);
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Request {
+    pub url: Option<Url>,
+}
+
+impl Default for Request {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Request {
+    #[inline]
+    pub fn new() -> Self {
+        Self { url: None }
+    }
+
+    #[inline]
+    pub fn parse(&mut self, buf: &[u8]) -> Result<usize> {
+        self.parse_with_config(buf, &ParserConfig::default())
+    }
+
+    #[inline]
+    pub fn parse_with_limit(&mut self, buf: &[u8], limit: usize) -> Result<usize> {
+        self.parse_with_config(buf, ParserConfig::default().max_url_length(limit))
+    }
+
+    #[inline]
+    pub fn parse_with_config(&mut self, buf: &[u8], config: &ParserConfig) -> Result<usize> {
+        self.parse_located(buf, config).map_err(|err| err.kind)
+    }
+
+    pub fn parse_chained(&mut self, first: &[u8], second: &[u8]) -> Result<usize> {
+        match chain_line(first, second) {
+            Cow::Borrowed(buf) => self.parse(buf),
+            Cow::Owned(buf) => self.parse(&buf),
+        }
+    }
+
+    #[cfg(feature = "std")]
+    pub fn parse_from_reader<R: io::BufRead>(&mut self, reader: &mut R) -> io::Result<usize> {
+        let mut buf = Vec::new();
+        loop {
+            read_line(
+                reader,
+                &mut buf,
+                BOM.len() + URL_MAX_LENGTH + 2,
+                Error::UriTooLong,
+            )?;
+            if let Status::Complete(len) = self.parse(&buf).map_err(io::Error::from)? {
+                return Ok(len);
+            }
+        }
+    }
+
+    pub fn parse_located(&mut self, buf: &[u8], config: &ParserConfig) -> LocatedResult<usize> {
+        let mut bytes = Bytes::new(buf);
+        config.skip_bom(&mut bytes)?;
+        complete!(skip_empty_lines(&mut bytes).map_err(|kind| ParseError::new(kind, bytes.pos - 1)));
+
+        let start = bytes.pos;
+        let limit = config.max_url_length;
+        let end = complete!(next_line_limit(&mut bytes, limit, Error::UriTooLong)
+            .map_err(|kind| line_error(kind, &bytes, start, limit)));
+        config
+            .check_line(&buf[start..end], buf[end])
+            .map_err(|kind| ParseError::new(kind, end))?;
+
+        let s = decode_line(&buf[start..end], config.allow_invalid_utf8)
+            .map_err(|err| ParseError::new(err.into(), start + err.valid_up_to()))?;
+        let url = Url::parse(&s).map_err(|err| ParseError::new(err.into(), start))?;
+        if !config.allows_scheme(url.scheme()) {
+            return Err(ParseError::new(Error::Scheme, start));
+        }
+        self.url = Some(url);
+        if config.validate_requests {
+            self.validate_with_config(config)
+                .map_err(|kind| ParseError::new(kind, start))?;
+        }
+
+        Ok(Status::Complete(bytes.pos))
+    }
+
+    #[inline]
+    pub fn validate(&self) -> result::Result<(), Error> {
+        self.validate_with_config(&ParserConfig::strict())
+    }
+
+    pub fn validate_with_config(&self, config: &ParserConfig) -> result::Result<(), Error> {
+        let url = self.url.as_ref().ok_or(Error::EmptyRequest)?;
+        if !config.allows_scheme(url.scheme()) {
+            return Err(Error::Scheme);
+        }
+        if !url.username().is_empty() || url.password().is_some() {
+            return Err(Error::UserInfo);
+        }
+        if url.host_str().is_none_or(str::is_empty) {
+            return Err(Error::MissingHost);
+        }
+        if url.fragment().is_some() {
+            return Err(Error::Fragment);
+        }
+        Ok(())
+    }
+
+    #[inline]
+    pub fn with_input(base: &Url, input: &str) -> result::Result<Self, Error> {
+        RequestBuilder::new(base.clone()).input(input).build()
+    }
+
+    #[inline]
+    pub fn authority(&self) -> Option<Authority<'_>> {
+        let url = self.url.as_ref()?;
+        Some(Authority {
+            host: url.host_str()?,
+            port: url.port(),
+        })
+    }
+
+    pub fn encode(&self, buf: &mut Vec<u8>) -> result::Result<(), Error> {
+        let url = self.url.as_ref().ok_or(Error::EmptyRequest)?.as_str();
+        if url.len() > URL_MAX_LENGTH {
+            return Err(Error::UriTooLong);
+        }
+
+        buf.reserve(url.len() + 2);
+        buf.extend_from_slice(url.as_bytes());
+        buf.extend_from_slice(b"\r\n");
+        Ok(())
+    }
+
+    #[cfg(feature = "std")]
+    pub fn write_to<W: io::Write>(&self, w: &mut W) -> io::Result<()> {
+        let mut buf = Vec::new();
+        self.encode(&mut buf).map_err(invalid_input)?;
+        w.write_all(&buf)
+    }
+}
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct RequestRef<'a> {
+    pub line: Option<&'a str>,
+    parts: Parts,
+}
+
+impl<'a> RequestRef<'a> {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[inline]
+    pub fn parse(&mut self, buf: &'a [u8]) -> Result<usize> {
+        self.parse_with_config(buf, &ParserConfig::default())
+    }
+
+    #[inline]
+    pub fn parse_with_config(&mut self, buf: &'a [u8], config: &ParserConfig) -> Result<usize> {
+        self.parse_located(buf, config).map_err(|err| err.kind)
+    }
+
+    pub fn parse_located(&mut self, buf: &'a [u8], config: &ParserConfig) -> LocatedResult<usize> {
+        let mut bytes = Bytes::new(buf);
+        config.skip_bom(&mut bytes)?;
+        complete!(skip_empty_lines(&mut bytes).map_err(|kind| ParseError::new(kind, bytes.pos - 1)));
+
+        let start = bytes.pos;
+        let limit = config.max_url_length;
+        let end = complete!(next_line_limit(&mut bytes, limit, Error::UriTooLong)
+            .map_err(|kind| line_error(kind, &bytes, start, limit)));
+        config
+            .check_line(&buf[start..end], buf[end])
+            .map_err(|kind| ParseError::new(kind, end))?;
+
+        let line = str::from_utf8(&buf[start..end])
+            .map_err(|err| ParseError::new(err.into(), start + err.valid_up_to()))?;
+        let parts = uri::split(line).map_err(|err| ParseError::new(err.into(), start))?;
+        if !config.allows_scheme(parts.scheme.get(line)) {
+            return Err(ParseError::new(Error::Scheme, start));
+        }
+        *self = Self {
+            line: Some(line),
+            parts,
+        };
+        if config.validate_requests {
+            self.validate_with_config(config)
+                .map_err(|kind| ParseError::new(kind, start))?;
+        }
+
+        Ok(Status::Complete(bytes.pos))
+    }
+
+    #[inline]
+    pub fn scheme(&self) -> Option<&'a str> {
+        self.get(Some(self.parts.scheme))
+    }
+
+    #[inline]
+    pub fn host(&self) -> Option<&'a str> {
+        self.get(self.parts.host)
+    }
+
+    #[inline]
+    pub fn port(&self) -> Option<u16> {
+        self.get(self.parts.port)?.parse().ok()
+    }
+
+    #[inline]
+    pub fn path(&self) -> Option<&'a str> {
+        self.get(Some(self.parts.path))
+    }
+
+    #[inline]
+    pub fn query(&self) -> Option<&'a str> {
+        self.get(self.parts.query)
+    }
+
+    #[inline]
+    pub fn scheme_span(&self) -> Option<Span> {
+        self.line.map(|_| self.parts.scheme)
+    }
+
+    #[inline]
+    pub fn host_span(&self) -> Option<Span> {
+        self.line.and(self.parts.host)
+    }
+
+    #[inline]
+    pub fn port_span(&self) -> Option<Span> {
+        self.line.and(self.parts.port)
+    }
+
+    #[inline]
+    pub fn path_span(&self) -> Option<Span> {
+        self.line.map(|_| self.parts.path)
+    }
+
+    #[inline]
+    pub fn query_span(&self) -> Option<Span> {
+        self.line.and(self.parts.query)
+    }
+
+    #[inline]
+    pub fn validate(&self) -> result::Result<(), Error> {
+        self.validate_with_config(&ParserConfig::strict())
+    }
+
+    pub fn validate_with_config(&self, config: &ParserConfig) -> result::Result<(), Error> {
+        if self.line.is_none() {
+            return Err(Error::EmptyRequest);
+        }
+        if !self.scheme().map_or(config.allow_proxy_requests, |scheme| {
+            config.allows_scheme(scheme)
+        }) {
+            return Err(Error::Scheme);
+        }
+        if self.parts.userinfo.is_some() {
+            return Err(Error::UserInfo);
+        }
+        if self.host().is_none_or(str::is_empty) {
+            return Err(Error::MissingHost);
+        }
+        if self.parts.fragment.is_some() {
+            return Err(Error::Fragment);
+        }
+        Ok(())
+    }
+
+    #[inline]
+    pub fn authority(&self) -> Option<Authority<'a>> {
+        Some(Authority {
+            host: self.host()?,
+            port: self.port(),
+        })
+    }
+
+    pub fn to_url(&self) -> result::Result<Url, Error> {
+        Ok(Url::parse(self.line.ok_or(Error::EmptyRequest)?)?)
+    }
+
+    #[inline]
+    fn get(&self, span: Option<Span>) -> Option<&'a str> {
+        Some(span?.get(self.line?))
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Authority<'a> {
+    pub host: &'a str,
+    pub port: Option<u16>,
+}
+
+impl<'a> Authority<'a> {
+    pub fn parse(buf: &'a [u8]) -> Result<Authority<'a>> {
+        let mut req = RequestRef::new();
+        match req.parse(buf)? {
+            Status::Complete(_) => Ok(Status::Complete(req.authority().ok_or(Error::MissingHost)?)),
+            Status::Partial(needed) => Ok(Status::Partial(needed)),
+        }
+    }
+
+    #[inline]
+    pub fn port_or_default(&self) -> u16 {
+        self.port.unwrap_or(DEFAULT_PORT)
+    }
+
+    #[inline]
+    pub fn matches(&self, host: &str) -> bool {
+        let name = self.host.strip_suffix('.').unwrap_or(self.host);
+        name.eq_ignore_ascii_case(host.strip_suffix('.').unwrap_or(host))
+    }
+}
+
+impl<'a> TryFrom<RequestRef<'a>> for Request {
+    type Error = Error;
+
+    fn try_from(req: RequestRef<'a>) -> result::Result<Self, Error> {
+        Ok(Self {
+            url: Some(req.to_url()?),
+        })
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Response {
+    pub status: Option<StatusCode>,
+    pub meta: Option<MetaString>,
+}
+
+impl Default for Response {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Response {
+    #[inline]
+    pub fn new() -> Self {
+        Self {
+            status: None,
+            meta: None,
+        }
+    }
+
+    #[inline]
+    pub fn parse(&mut self, buf: &[u8]) -> Result<usize> {
+        self.parse_with_config(buf, &ParserConfig::default())
+    }
+
+    #[inline]
+    pub fn parse_with_config(&mut self, buf: &[u8], config: &ParserConfig) -> Result<usize> {
+        self.parse_located(buf, config).map_err(|err| err.kind)
+    }
+
+    pub fn parse_chained(&mut self, first: &[u8], second: &[u8]) -> Result<usize> {
+        match chain_line(first, second) {
+            Cow::Borrowed(buf) => self.parse(buf),
+            Cow::Owned(buf) => self.parse(&buf),
+        }
+    }
+
+    #[cfg(feature = "std")]
+    pub fn parse_from_reader<R: io::BufRead>(&mut self, reader: &mut R) -> io::Result<usize> {
+        let mut buf = Vec::new();
+        read_line(reader, &mut buf, META_MAX_LENGTH + 5, Error::MetaTooLong)?;
+        match self.parse(&buf).map_err(io::Error::from)? {
+            Status::Complete(len) => Ok(len),
+            Status::Partial(_) => Err(Error::ResponseHeader.into()),
+        }
+    }
+
+    pub fn parse_located(&mut self, buf: &[u8], config: &ParserConfig) -> LocatedResult<usize> {
+        let mut res = ResponseRef::new();
+        let status = res.parse_located(buf, config)?;
+        if status.is_complete() {
+            *self = res.into();
+        }
+        Ok(status)
+    }
+
+    #[inline]
+    pub fn class(&self) -> Option<StatusClass> {
+        self.status.and_then(StatusCode::class)
+    }
+
+    #[inline]
+    pub fn redirect_target(&self, base: &Url) -> result::Result<Url, Error> {
+        redirect_target(self.status, self.meta.as_deref(), base, true)
+    }
+
+    #[inline]
+    pub fn redirect_target_with(
+        &self,
+        base: &Url,
+        allow_cross_scheme: bool,
+    ) -> result::Result<Url, Error> {
+        redirect_target(self.status, self.meta.as_deref(), base, allow_cross_scheme)
+    }
+
+    #[inline]
+    pub fn retry_after(&self) -> Option<Duration> {
+        retry_after(self.status, self.meta.as_deref())
+    }
+
+    #[inline]
+    pub fn prompt(&self) -> Option<&str> {
+        prompt(self.status, self.meta.as_deref())
+    }
+
+    #[inline]
+    pub fn validate_meta(&self) -> result::Result<(), Error> {
+        validate_meta(self.status, self.meta.as_deref())
+    }
+
+    #[inline]
+    pub fn charset(&self) -> Option<Cow<'_, str>> {
+        charset(self.status, self.meta.as_deref())
+    }
+
+    #[inline]
+    pub fn media_type(&self) -> Option<MediaType<'_>> {
+        media_type(self.status, self.meta.as_deref(), true)
+    }
+
+    #[inline]
+    pub fn media_type_with_config(&self, config: &ParserConfig) -> Option<MediaType<'_>> {
+        media_type(self.status, self.meta.as_deref(), config.allow_empty_meta)
+    }
+
+    pub fn essence(&self) -> Option<&str> {
+        self.media_type()?;
+        let meta = self
+            .meta
+            .as_deref()
+            .map(str::trim)
+            .filter(|meta| !meta.is_empty());
+        meta.unwrap_or(DEFAULT_META)
+            .split(';')
+            .next()
+            .map(str::trim)
+    }
+
+    pub fn sniff_media_type(&self, body: &[u8]) -> MediaType<'_> {
+        match self.media_type() {
+            Some(media_type) => media_type,
+            None => MediaType::parse(meta::sniff(body)).unwrap(),
+        }
+    }
+
+    #[inline]
+    pub fn is_text(&self) -> bool {
+        self.media_type()
+            .is_some_and(|mt| mt.ty.eq_ignore_ascii_case("text"))
+    }
+
+    #[inline]
+    pub fn is_gemtext(&self) -> bool {
+        self.media_type().is_some_and(|mt| mt.is("text", "gemini"))
+    }
+
+    #[inline]
+    pub fn certificate_requirement(&self) -> Option<(CertificateRequirement, &str)> {
+        let requirement = self.status?.certificate_requirement()?;
+        Some((requirement, self.meta.as_deref().unwrap_or("")))
+    }
+
+    #[inline]
+    pub fn should_present_certificate(&self) -> bool {
+        self.certificate_requirement()
+            .is_some_and(|(requirement, _)| requirement.should_present())
+    }
+
+    pub fn encode(&self, buf: &mut Vec<u8>) -> result::Result<(), Error> {
+        encode_header(self.status, self.meta.as_deref(), buf)
+    }
+
+    #[cfg(feature = "std")]
+    pub fn write_to<W: io::Write>(&self, w: &mut W) -> io::Result<()> {
+        let mut buf = Vec::new();
+        self.encode(&mut buf).map_err(invalid_input)?;
+        w.write_all(&buf)
+    }
+}
+
+impl<'a> From<ResponseRef<'a>> for Response {
+    fn from(res: ResponseRef<'a>) -> Self {
+        Self {
+            status: res.status,
+            meta: res.meta.map(MetaString::from),
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct ResponseRef<'a> {
+    pub status: Option<StatusCode>,
+    pub meta: Option<&'a str>,
+}
+
+impl<'a> Default for ResponseRef<'a> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a> ResponseRef<'a> {
+    #[inline]
+    pub fn new() -> Self {
+        Self {
+            status: None,
+            meta: None,
+        }
+    }
+
+    #[inline]
+    pub fn parse(&mut self, buf: &'a [u8]) -> Result<usize> {
+        self.parse_with_config(buf, &ParserConfig::default())
+    }
+
+    #[inline]
+    pub fn parse_with_config(&mut self, buf: &'a [u8], config: &ParserConfig) -> Result<usize> {
+        self.parse_located(buf, config).map_err(|err| err.kind)
+    }
+
+    pub fn parse_located(&mut self, buf: &'a [u8], config: &ParserConfig) -> LocatedResult<usize> {
+        let mut bytes = Bytes::new(buf);
+        let status = parse_status(&mut bytes).map_err(|kind| ParseError::new(kind, bytes.pos - 1));
+        let status = StatusCode::from_u16_lenient(complete!(status));
+
+        match bytes.peek() {
+            Some(b' ') => bytes.bump(),
+            Some(b'\r' | b'\n') if config.allow_empty_meta => {}
+            Some(_) => return Err(ParseError::new(Error::MissingSpaceAfterStatus, bytes.pos)),
+            None => return Ok(Status::Partial(Some(1))),
+        }
+
+        let start = bytes.pos;
+        let limit = config.max_meta_length;
+        let end = complete!(next_line_limit(&mut bytes, limit, Error::MetaTooLong)
+            .map_err(|kind| line_error(kind, &bytes, start, limit)));
+        config
+            .check_line(&buf[start..end], buf[end])
+            .map_err(|kind| ParseError::new(kind, end))?;
+        if !config.allow_empty_meta && start == end {
+            return Err(ParseError::new(Error::ResponseHeader, start));
+        }
+
+        let meta = str::from_utf8(&buf[start..end])
+            .map_err(|err| ParseError::new(err.into(), start + err.valid_up_to()))?;
+        if config.validate_meta {
+            validate_meta(Some(status), Some(meta)).map_err(|kind| ParseError::new(kind, start))?;
+        }
+
+        self.status = Some(status);
+        self.meta = Some(meta);
+
+        Ok(Status::Complete(bytes.pos))
+    }
+
+    #[inline]
+    pub fn class(&self) -> Option<StatusClass> {
+        self.status.and_then(StatusCode::class)
+    }
+
+    #[inline]
+    pub fn redirect_target(&self, base: &Url) -> result::Result<Url, Error> {
+        redirect_target(self.status, self.meta, base, true)
+    }
+
+    #[inline]
+    pub fn redirect_target_with(
+        &self,
+        base: &Url,
+        allow_cross_scheme: bool,
+    ) -> result::Result<Url, Error> {
+        redirect_target(self.status, self.meta, base, allow_cross_scheme)
+    }
+
+    #[inline]
+    pub fn retry_after(&self) -> Option<Duration> {
+        retry_after(self.status, self.meta)
+    }
+
+    #[inline]
+    pub fn prompt(&self) -> Option<&str> {
+        prompt(self.status, self.meta)
+    }
+
+    #[inline]
+    pub fn validate_meta(&self) -> result::Result<(), Error> {
+        validate_meta(self.status, self.meta)
+    }
+
+    #[inline]
+    pub fn charset(&self) -> Option<Cow<'_, str>> {
+        charset(self.status, self.meta)
+    }
+
+    #[inline]
+    pub fn media_type(&self) -> Option<MediaType<'_>> {
+        media_type(self.status, self.meta, true)
+    }
+
+    #[inline]
+    pub fn media_type_with_config(&self, config: &ParserConfig) -> Option<MediaType<'_>> {
+        media_type(self.status, self.meta, config.allow_empty_meta)
+    }
+
+    pub fn essence(&self) -> Option<&str> {
+        self.media_type()?;
+        let meta = self.meta.map(str::trim).filter(|meta| !meta.is_empty());
+        meta.unwrap_or(DEFAULT_META)
+            .split(';')
+            .next()
+            .map(str::trim)
+    }
+
+    pub fn sniff_media_type(&self, body: &[u8]) -> MediaType<'_> {
+        match self.media_type() {
+            Some(media_type) => media_type,
+            None => MediaType::parse(meta::sniff(body)).unwrap(),
+        }
+    }
+
+    #[inline]
+    pub fn is_text(&self) -> bool {
+        self.media_type()
+            .is_some_and(|mt| mt.ty.eq_ignore_ascii_case("text"))
+    }
+
+    #[inline]
+    pub fn is_gemtext(&self) -> bool {
+        self.media_type().is_some_and(|mt| mt.is("text", "gemini"))
+    }
+
+    #[inline]
+    pub fn certificate_requirement(&self) -> Option<(CertificateRequirement, &str)> {
+        let requirement = self.status?.certificate_requirement()?;
+        Some((requirement, self.meta.unwrap_or("")))
+    }
+
+    #[inline]
+    pub fn should_present_certificate(&self) -> bool {
+        self.certificate_requirement()
+            .is_some_and(|(requirement, _)| requirement.should_present())
+    }
+
+    pub fn encode(&self, buf: &mut Vec<u8>) -> result::Result<(), Error> {
+        encode_header(self.status, self.meta, buf)
+    }
+
+    #[cfg(feature = "std")]
+    pub fn write_to<W: io::Write>(&self, w: &mut W) -> io::Result<()> {
+        let mut buf = Vec::new();
+        self.encode(&mut buf).map_err(invalid_input)?;
+        w.write_all(&buf)
+    }
+}
+
+fn redirect_target(
+    status: Option<StatusCode>,
+    meta: Option<&str>,
+    base: &Url,
+    allow_cross_scheme: bool,
+) -> result::Result<Url, Error> {
+    if !status.is_some_and(StatusCode::is_redirect) {
+        return Err(Error::NotRedirect);
+    }
+
+    let meta = meta.map(str::trim).unwrap_or("");
+    if meta.is_empty() {
+        return Err(Error::ResponseHeader);
+    }
+
+    let target = base.join(meta)?;
+    if !allow_cross_scheme && target.scheme() != base.scheme() {
+        return Err(Error::CrossSchemeRedirect);
+    }
+
+    Ok(target)
+}
+
+fn media_type(
+    status: Option<StatusCode>,
+    meta: Option<&str>,
+    allow_empty: bool,
+) -> Option<MediaType<'_>> {
+    if !status?.is_success() {
+        return None;
+    }
+    match meta.unwrap_or("") {
+        meta if !meta.trim().is_empty() => MediaType::parse(meta).ok(),
+        _ if allow_empty => MediaType::parse(DEFAULT_META).ok(),
+        _ => None,
+    }
+}
+
+fn charset(status: Option<StatusCode>, meta: Option<&str>) -> Option<Cow<'_, str>> {
+    if !status?.is_success() {
+        return None;
+    }
+    let meta = meta.unwrap_or("");
+    let media_type = media_type(status, Some(meta), true)?;
+    let is_text = media_type.ty.eq_ignore_ascii_case("text");
+    match media_type.into_param("charset") {
+        Some(charset) => Some(charset),
+        None if is_text => Some(Cow::Borrowed("utf-8")),
+        None => None,
+    }
+}
+
+fn validate_meta(status: Option<StatusCode>, meta: Option<&str>) -> result::Result<(), Error> {
+    let status = status.ok_or(Error::Status)?;
+    let meta = meta.unwrap_or("");
+    match status.class() {
+        Some(StatusClass::Input) if meta.trim().is_empty() => Err(Error::Meta),
+        Some(StatusClass::Success) if !meta.is_empty() => MediaType::parse(meta).map(|_| ()),
+        Some(StatusClass::Redirect) => {
+            if meta.trim().is_empty() {
+                return Err(Error::Meta);
+            }
+            uri::split_reference(meta.trim())?;
+            Ok(())
+        }
+        _ if status == StatusCode::SlowDown && retry_after(Some(status), Some(meta)).is_none() => {
+            Err(Error::Meta)
+        }
+        _ => Ok(()),
+    }
+}
+
+#[inline]
+fn prompt(status: Option<StatusCode>, meta: Option<&str>) -> Option<&str> {
+    match status {
+        Some(status) if status.is_input() => Some(meta.unwrap_or("")),
+        _ => None,
+    }
 }
 
-impl From<url::ParseError> for Error {
-    fn from(err: url::ParseError) -> Self {
-        Error::ParseUrl(err)
+fn retry_after(status: Option<StatusCode>, meta: Option<&str>) -> Option<Duration> {
+    if status != Some(StatusCode::SlowDown) {
+        return None;
+    }
+    let meta = meta?.trim();
+    if meta.is_empty() || !meta.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
     }
+    meta.parse().ok().map(Duration::from_secs)
 }
 
-impl From<str::Utf8Error> for Error {
-    fn from(err: str::Utf8Error) -> Self {
-        Error::InvalidUtf8(err)
+fn encode_header(
+    status: Option<StatusCode>,
+    meta: Option<&str>,
+    buf: &mut Vec<u8>,
+) -> result::Result<(), Error> {
+    let status = u16::from(status.ok_or(Error::Status)?);
+    if !(10..=99).contains(&status) {
+        return Err(Error::Status);
     }
-}
 
-pub type Result<T> = result::Result<Status<T>, Error>;
+    let meta = meta.unwrap_or("");
+    check_meta(meta)?;
 
-#[derive(Clone, Debug, PartialEq, Eq)]
-pub enum Status<T> {
-    Complete(T),
-    Partial,
+    buf.reserve(meta.len() + 5);
+    buf.push(b'0' + (status / 10) as u8);
+    buf.push(b'0' + (status % 10) as u8);
+    buf.push(b' ');
+    buf.extend_from_slice(meta.as_bytes());
+    buf.extend_from_slice(b"\r\n");
+    Ok(())
 }
 
-#[derive(Clone, Debug, Eq, PartialEq)]
-pub struct Request {
-    pub url: Option<Url>,
+#[inline]
+fn line_error(kind: Error, bytes: &Bytes, start: usize, limit: usize) -> ParseError {
+    if bytes.pos == start {
+        ParseError::new(kind, start + limit)
+    } else {
+        ParseError::new(kind, bytes.pos - 1)
+    }
 }
 
-impl Request {
-    #[inline]
-    pub fn new() -> Self {
-        Self { url: None }
+#[inline]
+fn check_meta(meta: &str) -> result::Result<(), Error> {
+    if meta.len() > META_MAX_LENGTH {
+        return Err(Error::MetaTooLong);
     }
-
-    pub fn parse(&mut self, buf: &[u8]) -> Result<usize> {
-        let mut bytes = Bytes::new(buf);
-        complete!(skip_empty_lines(&mut bytes));
-
-        let start = bytes.pos;
-        let end = complete!(next_line(&mut bytes));
-
-        let s = unsafe { str::from_utf8_unchecked(&bytes[start..end]) };
-        self.url = Some(Url::parse(s)?);
-
-        Ok(Status::Complete(bytes.pos))
+    if meta.bytes().any(|b| b == b'\r' || b == b'\n') {
+        return Err(Error::ResponseHeader);
     }
+    Ok(())
 }
 
-#[derive(Debug, PartialEq, Eq)]
-pub struct Response {
-    pub status: Option<u16>,
-    pub meta: Option<String>,
+#[cfg(feature = "std")]
+fn invalid_input(err: Error) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, err)
 }
 
-impl Response {
-    #[inline]
-    pub fn new() -> Self {
-        Self {
-            status: None,
-            meta: None,
+#[cfg(feature = "std")]
+fn read_line<R: io::BufRead>(
+    reader: &mut R,
+    buf: &mut Vec<u8>,
+    limit: usize,
+    overflow: Error,
+) -> io::Result<()> {
+    loop {
+        let available = reader.fill_buf()?;
+        if available.is_empty() {
+            return Err(io::ErrorKind::UnexpectedEof.into());
         }
-    }
 
-    pub fn parse(&mut self, buf: &[u8]) -> Result<()> {
-        let mut bytes = Bytes::new(buf);
-        self.status = Some(complete!(parse_status(&mut bytes)));
+        let (done, used) = match available.iter().position(|&b| b == b'\n') {
+            Some(i) => (true, i + 1),
+            None => (false, available.len()),
+        };
+        buf.extend_from_slice(&available[..used]);
+        reader.consume(used);
+
+        if buf.len() > limit {
+            return Err(overflow.into());
+        }
+        if done {
+            return Ok(());
+        }
+    }
+}
 
-        expect!(bytes.next() == b' ' => Err(Error::ResponseHeader));
+fn chain_line<'a>(first: &'a [u8], second: &[u8]) -> Cow<'a, [u8]> {
+    if first.contains(&b'\n') {
+        return Cow::Borrowed(first);
+    }
 
-        let start = bytes.pos;
-        let end = complete!(next_line_limit(&mut bytes, META_MAX_LENGTH));
-        self.meta = Some(String::from(str::from_utf8(&bytes[start..end])?));
+    let end = second
+        .iter()
+        .position(|&b| b == b'\n')
+        .map_or(second.len(), |i| i + 1);
+    let mut line = Vec::with_capacity(first.len() + end);
+    line.extend_from_slice(first);
+    line.extend_from_slice(&second[..end]);
+    Cow::Owned(line)
+}
 
-        Ok(Status::Complete(()))
+#[inline]
+fn decode_line(line: &[u8], lossy: bool) -> result::Result<Cow<'_, str>, str::Utf8Error> {
+    #[cfg(not(feature = "safe"))]
+    if line.is_ascii() {
+        return Ok(Cow::Borrowed(unsafe { str::from_utf8_unchecked(line) }));
+    }
+    if lossy {
+        return Ok(String::from_utf8_lossy(line));
     }
+    str::from_utf8(line).map(Cow::Borrowed)
 }
 
 #[inline]
@@ -95,79 +1236,100 @@ fn skip_empty_lines(bytes: &mut Bytes) -> Result<()> {
     loop {
         match bytes.peek() {
             Some(b'\r') => {
-                unsafe {
-                    bytes.bump();
-                }
-
-                expect!(bytes.next() == b'\n' => Err(Error::NewLine));
-            }
-            Some(b'\n') => unsafe {
                 bytes.bump();
-            },
+                expect!(bytes.next() == b'\n' => Err(Error::LoneCarriageReturn));
+            }
+            Some(b'\n') => bytes.bump(),
             Some(..) => return Ok(Status::Complete(())),
-            None => return Ok(Status::Partial),
+            None => return Ok(Status::Partial(Some(1))),
         }
     }
 }
 
 #[inline]
-fn next_line(bytes: &mut Bytes) -> Result<usize> {
-    next_line_inner(bytes, None)
+fn next_line_limit(bytes: &mut Bytes, limit: usize, overflow: Error) -> Result<usize> {
+    next_line_inner(bytes, limit, overflow)
 }
 
 #[inline]
-fn next_line_limit(bytes: &mut Bytes, limit: usize) -> Result<usize> {
-    next_line_inner(bytes, Some(limit))
-}
-
-#[inline]
-fn next_line_inner(bytes: &mut Bytes, limit: Option<usize>) -> Result<usize> {
-    let start = bytes.pos;
-    loop {
-        match bytes.peek() {
-            Some(b'\r') => {
-                unsafe {
-                    bytes.bump();
-                }
-
-                match next!(bytes) {
-                    b'\n' => return Ok(Status::Complete(bytes.pos - 2)),
-                    _ => return Err(Error::NewLine),
-                }
-            }
-            Some(b'\n') => {
-                unsafe {
-                    bytes.bump();
-                }
-
-                return Ok(Status::Complete(bytes.pos - 1));
-            }
-            Some(..) => unsafe {
-                if let Some(limit) = limit {
-                    if bytes.pos - start + 1 > limit {
-                        return Err(Error::NewLine);
-                    }
-                }
+fn next_line_inner(bytes: &mut Bytes, limit: usize, overflow: Error) -> Result<usize> {
+    let rest = bytes.remaining();
+    let window = &rest[..rest.len().min(limit.saturating_add(1))];
 
-                bytes.bump();
-            },
-            None => return Ok(Status::Partial),
+    let len = match simd::find_eol(window) {
+        Some(len) => len,
+        None if rest.len() > limit => return Err(overflow),
+        None => {
+            bytes.advance(rest.len());
+            return Ok(Status::Partial(Some(1)));
         }
+    };
+
+    bytes.advance(len);
+    let end = bytes.pos;
+    match next!(bytes) {
+        b'\n' => Ok(Status::Complete(end)),
+        _ => match next!(bytes) {
+            b'\n' => Ok(Status::Complete(end)),
+            _ => Err(Error::LoneCarriageReturn),
+        },
     }
 }
 
 #[inline]
 fn parse_status(bytes: &mut Bytes) -> Result<u16> {
-    let tens = expect!(bytes.next() == b'0'..=b'9' => Err(Error::Status));
+    let tens = expect!(next!(bytes, 2) => b'0'..=b'9' => Err(Error::Status));
     let ones = expect!(bytes.next() == b'0'..=b'9' => Err(Error::Status));
     let result = ((tens - b'0') as u16 * 10) + (ones - b'0') as u16;
-    Ok(Status::Complete(result as u16))
+    Ok(Status::Complete(result))
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
-    use url::Host;
+
+    #[test]
+    fn test_error_display() {
+        use std::error::Error as _;
+
+        assert_eq!(Error::Status.to_string(), "invalid status code");
+        assert!(Error::Status.source().is_none());
+
+        let err = Error::from(UrlParseError::EmptyHost);
+        assert_eq!(err.to_string(), "invalid url: empty host");
+        assert!(err.source().is_some());
+
+        let boxed: Box<dyn std::error::Error> = Box::new(Error::NewLine);
+        assert_eq!(boxed.to_string(), "invalid line ending");
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_io_error() {
+        let err = io::Error::from(Error::Status);
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert_eq!(err.to_string(), "invalid status code");
+        assert_eq!(
+            err.into_inner().and_then(|e| e.downcast::<Error>().ok()),
+            Some(Box::new(Error::Status))
+        );
+
+        let (truncated, invalid): (&[u8], &[u8]) = (b"caf\xc3", b"caf\xff");
+        let truncated = str::from_utf8(truncated).unwrap_err();
+        assert!(Error::InvalidUtf8(truncated).is_retryable());
+        assert_eq!(
+            io::Error::from(Error::InvalidUtf8(truncated)).kind(),
+            io::ErrorKind::UnexpectedEof
+        );
+        let invalid = str::from_utf8(invalid).unwrap_err();
+        assert!(!Error::InvalidUtf8(invalid).is_retryable());
+        assert!(Error::ConnectionState.is_retryable());
+        assert!(!Error::NewLine.is_retryable());
+        assert_eq!(
+            io::Error::from(Error::TooManyRedirects).kind(),
+            io::ErrorKind::Other
+        );
+    }
 
     #[test]
     fn test_skip_empty_lines() {
@@ -178,44 +1340,91 @@ mod test {
 
         let mut bytes = Bytes::new(b"\r\n\r\n");
 
-        assert_eq!(skip_empty_lines(&mut bytes), Ok(Status::Partial));
+        assert_eq!(skip_empty_lines(&mut bytes), Ok(Status::Partial(Some(1))));
         assert_eq!(bytes.pos, 4);
 
         let mut bytes = Bytes::new(b"\r\n\r");
 
-        assert_eq!(skip_empty_lines(&mut bytes), Ok(Status::Partial));
+        assert_eq!(skip_empty_lines(&mut bytes), Ok(Status::Partial(Some(1))));
         assert_eq!(bytes.pos, 3);
 
         let mut bytes = Bytes::new(b"\r\n\ra");
 
-        assert_eq!(skip_empty_lines(&mut bytes), Err(Error::NewLine));
+        assert_eq!(skip_empty_lines(&mut bytes), Err(Error::LoneCarriageReturn));
     }
 
     #[test]
     fn test_next_line() {
         let mut bytes = Bytes::new(b"gemini://a.com\r\n");
 
-        assert_eq!(next_line(&mut bytes), Ok(Status::Complete(14)));
+        assert_eq!(
+            next_line_limit(&mut bytes, usize::MAX, Error::NewLine),
+            Ok(Status::Complete(14))
+        );
         assert_eq!(bytes.pos, 16);
 
         let mut bytes = Bytes::new(b"gemini://a.com\n");
 
-        assert_eq!(next_line(&mut bytes), Ok(Status::Complete(14)));
+        assert_eq!(
+            next_line_limit(&mut bytes, usize::MAX, Error::NewLine),
+            Ok(Status::Complete(14))
+        );
         assert_eq!(bytes.pos, 15);
 
         let mut bytes = Bytes::new(b"gemini://a.com");
 
-        assert_eq!(next_line(&mut bytes), Ok(Status::Partial));
+        assert_eq!(
+            next_line_limit(&mut bytes, usize::MAX, Error::NewLine),
+            Ok(Status::Partial(Some(1)))
+        );
 
         let mut bytes = Bytes::new(b"gemini://a.com\r\x00");
 
-        assert_eq!(next_line(&mut bytes), Err(Error::NewLine));
+        assert_eq!(
+            next_line_limit(&mut bytes, usize::MAX, Error::NewLine),
+            Err(Error::LoneCarriageReturn)
+        );
     }
 
     #[test]
     fn test_next_line_limit() {
         let mut bytes = Bytes::new(b"text\r");
-        assert_eq!(next_line_limit(&mut bytes, 3), Err(Error::NewLine));
+        assert_eq!(
+            next_line_limit(&mut bytes, 3, Error::MetaTooLong),
+            Err(Error::MetaTooLong)
+        );
+
+        let mut bytes = Bytes::new(b"text\r\n");
+        assert_eq!(
+            next_line_limit(&mut bytes, 4, Error::MetaTooLong),
+            Ok(Status::Complete(4))
+        );
+
+        let mut bytes = Bytes::new(b"text");
+        assert_eq!(
+            next_line_limit(&mut bytes, 3, Error::MetaTooLong),
+            Err(Error::MetaTooLong)
+        );
+
+        let mut bytes = Bytes::new(b"tex");
+        assert_eq!(
+            next_line_limit(&mut bytes, 3, Error::MetaTooLong),
+            Ok(Status::Partial(Some(1)))
+        );
+    }
+
+    #[test]
+    fn test_find_eol() {
+        assert_eq!(iter::find_eol(b""), None);
+        assert_eq!(iter::find_eol(b"abc"), None);
+        for i in 0..40 {
+            let mut buf = vec![b'a'; 40];
+            buf[i] = b'\n';
+            assert_eq!(iter::find_eol(&buf), Some(i));
+            buf[i] = b'\r';
+            assert_eq!(iter::find_eol(&buf), Some(i));
+        }
+        assert_eq!(iter::find_eol(&[0x8d; 16]), None);
     }
 
     #[test]
@@ -225,32 +1434,754 @@ mod test {
         req.parse(buf).unwrap();
         let url = req.url.unwrap();
         assert_eq!(url.scheme(), "gemini");
-        assert_eq!(url.host(), Some(Host::Domain("example.com")));
+        assert_eq!(url.host_str(), Some("example.com"));
 
         let buf = b"gemini://example.com";
         let mut req = Request::new();
-        assert_eq!(req.parse(buf), Ok(Status::Partial));
+        assert_eq!(req.parse(buf), Ok(Status::Partial(Some(1))));
 
         let buf = b"gemini://example.com\r\x00";
         let mut req = Request::new();
-        assert_eq!(req.parse(buf), Err(Error::NewLine));
+        assert_eq!(req.parse(buf), Err(Error::LoneCarriageReturn));
+
+        let url = format!("gemini://example.com/{}", "a".repeat(1003));
+        assert_eq!(url.len(), 1024);
+        let buf = format!("{}\r\n", url);
+        let mut req = Request::new();
+        assert_eq!(req.parse(buf.as_bytes()), Ok(Status::Complete(1026)));
+
+        let buf = format!("{}a\r\n", url);
+        assert_eq!(req.parse(buf.as_bytes()), Err(Error::UriTooLong));
+        assert_eq!(req.parse(&buf.as_bytes()[..1025]), Err(Error::UriTooLong));
+        assert_eq!(
+            req.parse_with_limit(buf.as_bytes(), 2048),
+            Ok(Status::Complete(1027))
+        );
+    }
+
+    #[test]
+    fn test_request_parse_utf8() {
+        let buf = b"gemini://example.com/\xc3\xa9\r\n";
+        let mut req = Request::new();
+        assert_eq!(req.parse(buf), Ok(Status::Complete(buf.len())));
+        assert_eq!(req.url.as_ref().unwrap().path(), "/%C3%A9");
+
+        let buf = b"gemini://example.com/\xff\r\n";
+        assert_eq!(
+            req.parse_located(buf, &ParserConfig::default())
+                .unwrap_err()
+                .offset,
+            21
+        );
+        assert!(matches!(req.parse(buf), Err(Error::InvalidUtf8(_))));
+
+        let config = ParserConfig::default().allow_invalid_utf8(true).clone();
+        assert_eq!(
+            req.parse_with_config(buf, &config),
+            Ok(Status::Complete(buf.len()))
+        );
+        assert_eq!(req.url.unwrap().path(), "/%EF%BF%BD");
+    }
+
+    #[test]
+    fn test_request_parse_bom() {
+        let buf = b"\xef\xbb\xbfgemini://example.com/\r\n";
+        let mut req = Request::new();
+        assert_eq!(req.parse(&buf[..2]), Ok(Status::Partial(Some(1))));
+        assert_eq!(req.parse(buf), Ok(Status::Complete(buf.len())));
+        assert_eq!(req.url.unwrap().as_str(), "gemini://example.com/");
+
+        let mut req = RequestRef::new();
+        assert_eq!(req.parse(buf), Ok(Status::Complete(buf.len())));
+        assert_eq!(req.host(), Some("example.com"));
+
+        let strict = ParserConfig::strict();
+        assert_eq!(
+            req.parse_located(buf, &strict),
+            Err(ParseError::new(Error::ByteOrderMark, 0))
+        );
+    }
+
+    #[test]
+    fn test_request_encode() {
+        let req = Request {
+            url: Some(Url::parse("gemini://example.com/a b").unwrap()),
+        };
+        let mut buf = Vec::new();
+        req.encode(&mut buf).unwrap();
+        assert_eq!(buf, b"gemini://example.com/a%20b\r\n");
+
+        let mut parsed = Request::new();
+        assert_eq!(parsed.parse(&buf), Ok(Status::Complete(buf.len())));
+        assert_eq!(parsed, req);
+
+        let long = format!("gemini://example.com/{}", "a".repeat(1024));
+        let req = Request {
+            url: Some(Url::parse(&long).unwrap()),
+        };
+        assert_eq!(req.encode(&mut Vec::new()), Err(Error::UriTooLong));
+
+        assert_eq!(
+            Request::new().encode(&mut Vec::new()),
+            Err(Error::EmptyRequest)
+        );
     }
 
     #[test]
     fn test_response_parse() {
         let buf = b"20 metadata\r\n";
         let mut res = Response::new();
-        res.parse(buf).unwrap();
-        assert_eq!(res.status, Some(20));
-        assert_eq!(res.meta, Some("metadata".to_string()));
+        assert_eq!(res.parse(buf), Ok(Status::Complete(buf.len())));
+        assert_eq!(res.status, Some(StatusCode::Success));
+        assert_eq!(res.meta.as_deref(), Some("metadata"));
+        assert_eq!(res.parse(b"51 Not"), Ok(Status::Partial(Some(1))));
+        assert_eq!(res.parse(b"51x\r\n"), Err(Error::MissingSpaceAfterStatus));
+        assert_eq!(res.status, Some(StatusCode::Success));
+        assert_eq!(res.meta.as_deref(), Some("metadata"));
 
         let buf = b"20 metadata";
         let mut res = Response::new();
-        assert_eq!(res.parse(buf), Ok(Status::Partial));
+        assert_eq!(res.parse(buf), Ok(Status::Partial(Some(1))));
 
         let buf = b"20 metadata\ra";
         let mut res = Response::new();
-        assert_eq!(res.parse(buf), Err(Error::NewLine));
+        assert_eq!(res.parse(buf), Err(Error::LoneCarriageReturn));
+    }
+
+    #[test]
+    fn test_parser_config() {
+        let strict = ParserConfig::strict();
+        let mut req = Request::new();
+        assert_eq!(
+            req.parse_with_config(b"gemini://a.com/\r\n", &strict),
+            Ok(Status::Complete(17))
+        );
+        assert_eq!(
+            req.parse_with_config(b"gemini://a.com/\n", &strict),
+            Err(Error::BareLineFeed)
+        );
+        assert_eq!(
+            req.parse_with_config(b"gemini://a.com/ \r\n", &strict),
+            Err(Error::NewLine)
+        );
+        assert_eq!(
+            strict.parse_request(&mut req, b"gemini://a.com/\n"),
+            Err(Error::BareLineFeed)
+        );
+        assert_eq!(
+            ParserConfig::default()
+                .max_url_length(8)
+                .parse_request(&mut req, b"gemini://a.com/\r\n"),
+            Err(Error::UriTooLong)
+        );
+
+        let mut res = ResponseRef::new();
+        assert_eq!(
+            res.parse_with_config(b"20 text/gemini\n", &strict),
+            Err(Error::BareLineFeed)
+        );
+        assert_eq!(
+            ParserConfig::default()
+                .allow_empty_meta(false)
+                .parse_response(&mut res, b"20 \r\n"),
+            Err(Error::ResponseHeader)
+        );
+        assert_eq!(
+            ParserConfig::default()
+                .max_meta_length(3)
+                .parse_response(&mut res, b"20 text/gemini\r\n"),
+            Err(Error::MetaTooLong)
+        );
+        assert_eq!(
+            ParserConfig::default().parse_response(&mut res, b"20 \r\n"),
+            Ok(Status::Complete(5))
+        );
+        assert_eq!(
+            ParserConfig::default().parse_response(&mut res, b"51\r\n"),
+            Ok(Status::Complete(4))
+        );
+        assert_eq!(res.status, Some(StatusCode::NotFound));
+        assert_eq!(res.meta, Some(""));
+        assert_eq!(
+            ParserConfig::default()
+                .allow_empty_meta(false)
+                .parse_response(&mut res, b"51\r\n"),
+            Err(Error::MissingSpaceAfterStatus)
+        );
+        assert_eq!(res.status, Some(StatusCode::NotFound));
+
+        res.parse(b"20 text/gemini\r\n").unwrap();
+        assert_eq!(
+            ParserConfig::default().parse_response(&mut res, b"51"),
+            Ok(Status::Partial(Some(1)))
+        );
+        assert_eq!(res.parse(b"51x\r\n"), Err(Error::MissingSpaceAfterStatus));
+        assert_eq!(res.status, Some(StatusCode::Success));
+        assert_eq!(res.meta, Some("text/gemini"));
+
+        let mut res = ResponseRef::new();
+        assert_eq!(res.parse(b"51 Not"), Ok(Status::Partial(Some(1))));
+        assert_eq!(res.status, None);
+        assert_eq!(res.meta, None);
+    }
+
+    #[test]
+    fn test_request_validate() {
+        let req = |url: &str| Request {
+            url: Some(Url::parse(url).unwrap()),
+        };
+
+        assert_eq!(req("gemini://example.com/a?b").validate(), Ok(()));
+        assert_eq!(req("https://example.com/").validate(), Err(Error::Scheme));
+        assert_eq!(
+            req("gemini://u:p@example.com/").validate(),
+            Err(Error::UserInfo)
+        );
+        assert_eq!(req("gemini:///path").validate(), Err(Error::MissingHost));
+        assert_eq!(
+            req("gemini://example.com/#top").validate(),
+            Err(Error::Fragment)
+        );
+        assert_eq!(Request::new().validate(), Err(Error::EmptyRequest));
+
+        let proxy = ParserConfig::strict().allow_proxy_requests(true).clone();
+        assert_eq!(
+            req("https://example.com/").validate_with_config(&proxy),
+            Ok(())
+        );
+        assert_eq!(
+            req("https://u@example.com/").validate_with_config(&proxy),
+            Err(Error::UserInfo)
+        );
+
+        let mercury = ParserConfig::strict()
+            .schemes(&["gemini", "mercury"])
+            .clone();
+        assert_eq!(
+            req("mercury://example.com/").validate_with_config(&mercury),
+            Ok(())
+        );
+        assert_eq!(
+            req("gemini://example.com/").validate_with_config(&mercury),
+            Ok(())
+        );
+        assert_eq!(
+            req("https://example.com/").validate_with_config(&mercury),
+            Err(Error::Scheme)
+        );
+        assert_eq!(req("mercury://example.com/").validate(), Err(Error::Scheme));
+
+        let mut r = Request::new();
+        assert_eq!(
+            ParserConfig::strict().parse_request(&mut r, b"gemini://example.com/#x\r\n"),
+            Err(Error::Fragment)
+        );
+        assert_eq!(
+            r.parse(b"gemini://example.com/#x\r\n"),
+            Ok(Status::Complete(25))
+        );
+    }
+
+    #[test]
+    fn test_request_ref_parse() {
+        let buf = b"\r\ngemini://example.com:1966/a/b?q#f\r\nrest";
+        let mut req = RequestRef::new();
+        assert_eq!(req.parse(buf), Ok(Status::Complete(37)));
+        assert_eq!(req.line, Some("gemini://example.com:1966/a/b?q#f"));
+        assert_eq!(req.scheme(), Some("gemini"));
+        assert_eq!(req.host(), Some("example.com"));
+        assert_eq!(req.port(), Some(1966));
+        assert_eq!(req.path(), Some("/a/b"));
+        assert_eq!(req.query(), Some("q"));
+        assert_eq!(req.host_span(), Some(Span::new(9, 20)));
+        assert_eq!(req.validate(), Err(Error::Fragment));
+
+        let url = Request::try_from(req).unwrap().url.unwrap();
+        assert_eq!(url.as_str(), "gemini://example.com:1966/a/b?q#f");
+
+        let mut req = RequestRef::new();
+        assert_eq!(req.parse(b"gemini://a"), Ok(Status::Partial(Some(1))));
+        assert_eq!(req.line, None);
+        assert_eq!(req.path(), None);
+        assert_eq!(req.validate(), Err(Error::EmptyRequest));
+        assert_eq!(
+            req.parse(b"/relative\r\n"),
+            Err(Error::ParseUrl(UrlParseError::RelativeUrlWithoutBase))
+        );
+        assert!(matches!(
+            req.parse(b"gemini://a/\xff\r\n"),
+            Err(Error::InvalidUtf8(_))
+        ));
+        assert_eq!(
+            ParserConfig::strict().parse_request_ref(&mut req, b"gemini:///x\r\n"),
+            Err(Error::MissingHost)
+        );
+
+        assert_eq!(req.parse(b"https://a/\r\n"), Err(Error::Scheme));
+        assert_eq!(Request::new().parse(b"https://a/\r\n"), Err(Error::Scheme));
+        assert_eq!(
+            ParserConfig::lenient()
+                .allow_proxy_requests(true)
+                .parse_request_ref(&mut req, b"https://a/\r\n"),
+            Ok(Status::Complete(12))
+        );
+
+        let mut config = ParserConfig::strict();
+        assert_eq!(
+            config.parse_request_ref(&mut req, b"mercury://a/\r\n"),
+            Err(Error::Scheme)
+        );
+        config.schemes(&["mercury"]);
+        assert_eq!(
+            config.parse_request_ref(&mut req, b"mercury://a/\r\n"),
+            Ok(Status::Complete(14))
+        );
+    }
+
+    #[test]
+    fn test_authority() {
+        let auth = Authority {
+            host: "Example.COM.",
+            port: Some(1966),
+        };
+        assert_eq!(
+            Authority::parse(b"gemini://Example.COM.:1966/x\r\n"),
+            Ok(Status::Complete(auth))
+        );
+        assert!(auth.matches("example.com"));
+        assert!(!auth.matches("example.org"));
+
+        let auth = Authority {
+            host: "[::1]",
+            port: None,
+        };
+        assert_eq!(
+            Authority::parse(b"gemini://[::1]/\r\n"),
+            Ok(Status::Complete(auth))
+        );
+        assert_eq!(auth.port_or_default(), DEFAULT_PORT);
+
+        assert_eq!(
+            Authority::parse(b"gemini://a.com"),
+            Ok(Status::Partial(Some(1)))
+        );
+        assert_eq!(
+            Authority::parse(b"gemini:a@b.com\r\n"),
+            Err(Error::MissingHost)
+        );
+        assert_eq!(Authority::parse(b"mailto:a@b.com\r\n"), Err(Error::Scheme));
+
+        let mut req = Request::new();
+        assert_eq!(req.authority(), None);
+        req.parse(b"gemini://example.com:1966/\r\n").unwrap();
+        assert_eq!(
+            req.authority(),
+            Some(Authority {
+                host: "example.com",
+                port: Some(1966)
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_located() {
+        let config = ParserConfig::default();
+        let mut req = Request::new();
+
+        assert_eq!(
+            req.parse_located(b"\r\n\rx", &config).unwrap_err(),
+            ParseError::new(Error::LoneCarriageReturn, 3)
+        );
+        assert_eq!(
+            req.parse_located(b"\ngemini://a.com\rx", &config)
+                .unwrap_err(),
+            ParseError::new(Error::LoneCarriageReturn, 16)
+        );
+        assert_eq!(
+            req.parse_located(b"gemini://a.com", ParserConfig::default().max_url_length(4))
+                .unwrap_err(),
+            ParseError::new(Error::UriTooLong, 4)
+        );
+        assert_eq!(
+            req.parse_located(b"\nnot a url\r\n", &config).unwrap_err(),
+            ParseError::new(Error::ParseUrl(UrlParseError::RelativeUrlWithoutBase), 1)
+        );
+        assert_eq!(
+            req.parse_located(b"gemini://a.com\n", &ParserConfig::strict())
+                .unwrap_err(),
+            ParseError::new(Error::BareLineFeed, 14)
+        );
+
+        let mut res = ResponseRef::new();
+        assert_eq!(
+            res.parse_located(b"2x text\r\n", &config).unwrap_err(),
+            ParseError::new(Error::Status, 1)
+        );
+        assert_eq!(
+            res.parse_located(b"20text\r\n", &config).unwrap_err(),
+            ParseError::new(Error::MissingSpaceAfterStatus, 2)
+        );
+        let err = res.parse_located(b"20 te\xffxt\r\n", &config).unwrap_err();
+        assert!(matches!(err.kind, Error::InvalidUtf8(_)));
+        assert_eq!(err.offset, 5);
+        assert_eq!(
+            res.parse_located(b"20 text\rx", &config).unwrap_err(),
+            ParseError::new(Error::LoneCarriageReturn, 8)
+        );
+
+        let e = ParseError::new(Error::Status, 1);
+        assert_eq!(e.to_string(), "invalid status code at byte 1");
+    }
+
+    #[test]
+    fn test_response_ref_parse() {
+        let buf = b"20 text/gemini\r\n# body";
+        let mut res = ResponseRef::new();
+        assert_eq!(res.parse(buf), Ok(Status::Complete(16)));
+        assert_eq!(res.status, Some(StatusCode::Success));
+        assert_eq!(res.meta, Some("text/gemini"));
+
+        let buf = b"20 text/gemini";
+        let mut res = ResponseRef::new();
+        assert_eq!(res.parse(buf), Ok(Status::Partial(Some(1))));
+        assert_eq!(res.meta, None);
+    }
+
+    #[test]
+    fn test_redirect_target() {
+        let base = Url::parse("gemini://example.com/a/b").unwrap();
+        let mut res = ResponseRef::new();
+
+        res.parse(b"31 ../c?d\r\n").unwrap();
+        assert_eq!(
+            res.redirect_target(&base).unwrap().as_str(),
+            "gemini://example.com/c?d"
+        );
+
+        res.parse(b"30 //other.org/\r\n").unwrap();
+        assert_eq!(
+            res.redirect_target(&base).unwrap().as_str(),
+            "gemini://other.org/"
+        );
+
+        res.parse(b"30 https://example.com/\r\n").unwrap();
+        assert!(res.redirect_target(&base).is_ok());
+        assert_eq!(
+            res.redirect_target_with(&base, false),
+            Err(Error::CrossSchemeRedirect)
+        );
+
+        res.parse(b"30 \r\n").unwrap();
+        assert_eq!(res.redirect_target(&base), Err(Error::ResponseHeader));
+
+        res.parse(b"20 text/gemini\r\n").unwrap();
+        assert_eq!(res.redirect_target(&base), Err(Error::NotRedirect));
+    }
+
+    #[test]
+    fn test_retry_after() {
+        let mut res = Response::new();
+        res.parse(b"44 30\r\n").unwrap();
+        assert_eq!(res.retry_after(), Some(Duration::from_secs(30)));
+
+        let mut res = ResponseRef::new();
+        res.parse(b"44 soon\r\n").unwrap();
+        assert_eq!(res.retry_after(), None);
+        res.parse(b"44 -5\r\n").unwrap();
+        assert_eq!(res.retry_after(), None);
+        res.parse(b"44 99999999999999999999\r\n").unwrap();
+        assert_eq!(res.retry_after(), None);
+        res.parse(b"41 30\r\n").unwrap();
+        assert_eq!(res.retry_after(), None);
+    }
+
+    #[test]
+    fn test_input_round_trip() {
+        let mut res = ResponseRef::new();
+        res.parse(b"11 Password:\r\n").unwrap();
+        assert_eq!(res.prompt(), Some("Password:"));
+        res.parse(b"20 text/gemini\r\n").unwrap();
+        assert_eq!(res.prompt(), None);
+        let res = Response {
+            status: Some(StatusCode::Input),
+            meta: None,
+        };
+        assert_eq!(res.prompt(), Some(""));
+
+        let base = Url::parse("gemini://example.com/search?old#top").unwrap();
+        let req = Request::with_input(&base, "a b&c=d/é").unwrap();
+        assert_eq!(
+            req.url.unwrap().as_str(),
+            "gemini://example.com/search?a%20b%26c%3Dd%2F%C3%A9"
+        );
+        assert_eq!(
+            Request::with_input(&base, &"x".repeat(1024)),
+            Err(Error::UriTooLong)
+        );
+    }
+
+    #[test]
+    fn test_certificate_requirement() {
+        let mut res = ResponseRef::new();
+        res.parse(b"60 Please log in\r\n").unwrap();
+        assert_eq!(
+            res.certificate_requirement(),
+            Some((CertificateRequirement::Required, "Please log in"))
+        );
+        assert!(res.should_present_certificate());
+
+        let mut res = Response::new();
+        res.parse(b"61 Members only\r\n").unwrap();
+        assert_eq!(
+            res.certificate_requirement(),
+            Some((CertificateRequirement::NotAuthorized, "Members only"))
+        );
+        assert!(!res.should_present_certificate());
+        res.parse(b"62 Expired\r\n").unwrap();
+        assert!(res.should_present_certificate());
+        res.parse(b"65 Something new\r\n").unwrap();
+        assert_eq!(
+            res.certificate_requirement().map(|(r, _)| r),
+            Some(CertificateRequirement::Required)
+        );
+        res.parse(b"51 Not found\r\n").unwrap();
+        assert_eq!(res.certificate_requirement(), None);
+        assert!(!res.should_present_certificate());
+    }
+
+    #[test]
+    fn test_validate_meta() {
+        let mut config = ParserConfig::default();
+        config.validate_meta(true);
+        let parse = |buf: &'static [u8]| ResponseRef::new().parse_located(buf, &config);
+
+        assert!(parse(b"10 Name?\r\n").is_ok());
+        assert!(parse(b"20 text/gemini; lang=en\r\n").is_ok());
+        assert!(parse(b"20\r\n").is_ok());
+        assert!(parse(b"31 ../other\r\n").is_ok());
+        assert!(parse(b"44 10\r\n").is_ok());
+        assert!(parse(b"51 anything at all\r\n").is_ok());
+
+        assert_eq!(parse(b"10 \r\n"), Err(ParseError::new(Error::Meta, 3)));
+        assert_eq!(
+            parse(b"20 not a media type\r\n"),
+            Err(ParseError::new(Error::MediaType, 3))
+        );
+        assert_eq!(parse(b"30\r\n"), Err(ParseError::new(Error::Meta, 2)));
+        assert!(matches!(
+            parse(b"30 1http://x\r\n"),
+            Err(ParseError {
+                kind: Error::ParseUrl(_),
+                ..
+            })
+        ));
+        assert_eq!(parse(b"44 later\r\n"), Err(ParseError::new(Error::Meta, 3)));
+
+        let mut res = Response::new();
+        res.parse(b"44 later\r\n").unwrap();
+        assert_eq!(res.validate_meta(), Err(Error::Meta));
+    }
+
+    #[test]
+    fn test_charset() {
+        let mut res = ResponseRef::new();
+        res.parse(b"20 text/gemini; charset=ISO-8859-1\r\n")
+            .unwrap();
+        assert_eq!(res.charset().as_deref(), Some("ISO-8859-1"));
+        res.parse(b"20 text/plain\r\n").unwrap();
+        assert_eq!(res.charset().as_deref(), Some("utf-8"));
+        res.parse(b"20 image/png\r\n").unwrap();
+        assert_eq!(res.charset().as_deref(), None);
+        res.parse(b"51 text/plain\r\n").unwrap();
+        assert_eq!(res.charset().as_deref(), None);
+
+        let mut res = Response::new();
+        res.parse(b"20\r\n").unwrap();
+        assert_eq!(res.charset().as_deref(), Some("utf-8"));
+    }
+
+    #[test]
+    fn test_content_type() {
+        let mut res = ResponseRef::new();
+        res.parse(b"20 Text/Gemini; lang=en\r\n").unwrap();
+        assert!(res.is_gemtext());
+        assert!(res.is_text());
+        assert_eq!(res.essence(), Some("Text/Gemini"));
+        assert_eq!(res.media_type().unwrap().get("lang"), Some("en"));
+
+        res.parse(b"20 text/plain\r\n").unwrap();
+        assert!(!res.is_gemtext());
+        assert!(res.is_text());
+        res.parse(b"20 image/png\r\n").unwrap();
+        assert!(!res.is_text());
+        assert_eq!(res.essence(), Some("image/png"));
+        res.parse(b"20 nonsense\r\n").unwrap();
+        assert_eq!(res.media_type(), None);
+        assert_eq!(res.essence(), None);
+        res.parse(b"51 text/gemini\r\n").unwrap();
+        assert!(!res.is_gemtext());
+        assert_eq!(res.essence(), None);
+
+        let mut res = Response::new();
+        res.parse(b"20\r\n").unwrap();
+        assert!(res.is_gemtext());
+        assert_eq!(res.essence(), Some("text/gemini"));
+        assert_eq!(res.media_type().unwrap().get("charset"), Some("utf-8"));
+        assert!(res
+            .media_type_with_config(&ParserConfig::default())
+            .is_some());
+        let mut config = ParserConfig::default();
+        config.allow_empty_meta(false);
+        assert_eq!(res.media_type_with_config(&config), None);
+    }
+
+    #[test]
+    fn test_sniff_media_type() {
+        let mut res = Response::new();
+        res.parse(b"20 broken\r\n").unwrap();
+        assert!(res.sniff_media_type(b"=> /x").is("text", "gemini"));
+        res.parse(b"20 image/png\r\n").unwrap();
+        assert!(res.sniff_media_type(b"=> /x").is("image", "png"));
+        res.parse(b"51 gone\r\n").unwrap();
+        assert!(res
+            .sniff_media_type(b"\0\x01")
+            .is("application", "octet-stream"));
+    }
+
+    #[test]
+    fn test_response_encode() {
+        let res = Response {
+            status: Some(StatusCode::Success),
+            meta: Some("text/gemini".into()),
+        };
+        let mut buf = Vec::new();
+        res.encode(&mut buf).unwrap();
+        assert_eq!(buf, b"20 text/gemini\r\n");
+
+        let mut parsed = Response::new();
+        assert_eq!(parsed.parse(&buf), Ok(Status::Complete(buf.len())));
+        assert_eq!(parsed, res);
+
+        let res = ResponseRef {
+            status: Some(StatusCode::NotFound),
+            meta: None,
+        };
+        let mut buf = Vec::new();
+        res.encode(&mut buf).unwrap();
+        assert_eq!(buf, b"51 \r\n");
+
+        let res = ResponseRef {
+            status: Some(StatusCode::Other(100)),
+            meta: Some("x"),
+        };
+        assert_eq!(res.encode(&mut Vec::new()), Err(Error::Status));
+
+        let res = ResponseRef {
+            status: Some(StatusCode::Input),
+            meta: Some("a\r\n20 b"),
+        };
+        assert_eq!(res.encode(&mut Vec::new()), Err(Error::ResponseHeader));
+
+        let meta = "a".repeat(META_MAX_LENGTH + 1);
+        let res = ResponseRef {
+            status: Some(StatusCode::Input),
+            meta: Some(&meta),
+        };
+        assert_eq!(res.encode(&mut Vec::new()), Err(Error::MetaTooLong));
+        assert_eq!(
+            ResponseRef::new().encode(&mut Vec::new()),
+            Err(Error::Status)
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_write_to() {
+        let req = Request {
+            url: Some(Url::parse("gemini://example.com/").unwrap()),
+        };
+        let mut out = Vec::new();
+        req.write_to(&mut out).unwrap();
+        assert_eq!(out, b"gemini://example.com/\r\n");
+        assert!(Request::new().write_to(&mut Vec::new()).is_err());
+
+        let res = Response {
+            status: Some(StatusCode::Success),
+            meta: Some("text/gemini".into()),
+        };
+        let mut out = Vec::new();
+        res.write_to(&mut out).unwrap();
+        assert_eq!(out, b"20 text/gemini\r\n");
+        assert!(ResponseRef::new().write_to(&mut Vec::new()).is_err());
+    }
+
+    #[test]
+    fn test_parse_chained() {
+        let mut req = Request::new();
+        assert_eq!(
+            req.parse_chained(b"gemini://exa", b"mple.com/\r\nrest"),
+            Ok(Status::Complete(23))
+        );
+        assert_eq!(req.url.unwrap().as_str(), "gemini://example.com/");
+        let mut req = Request::new();
+        assert_eq!(
+            req.parse_chained(b"gemini://a.com/\r\n", b"ignored"),
+            Ok(Status::Complete(17))
+        );
+        assert_eq!(
+            req.parse_chained(b"gemini://", b"a.com"),
+            Ok(Status::Partial(Some(1)))
+        );
+
+        let mut res = Response::new();
+        assert_eq!(
+            res.parse_chained(b"20 text/", b"gemini\r\n# body"),
+            Ok(Status::Complete(16))
+        );
+        assert_eq!(res.meta.as_deref(), Some("text/gemini"));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_parse_from_reader() {
+        use std::io::{BufRead, BufReader, Read};
+
+        let data: &[u8] = b"\r\ngemini://example.com/\r\nbody";
+        let mut reader = BufReader::with_capacity(4, data);
+        let mut req = Request::new();
+        assert_eq!(req.parse_from_reader(&mut reader).unwrap(), 25);
+        assert_eq!(req.url.unwrap().as_str(), "gemini://example.com/");
+        let mut rest = String::new();
+        reader.read_to_string(&mut rest).unwrap();
+        assert_eq!(rest, "body");
+
+        let mut res = Response::new();
+        let mut reader: &[u8] = b"20 text/gemini\r\n# Hi";
+        assert_eq!(res.parse_from_reader(&mut reader).unwrap(), 16);
+        assert_eq!(reader.fill_buf().unwrap(), b"# Hi");
+
+        let err = Request::new()
+            .parse_from_reader(&mut &b"gemini://a"[..])
+            .unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::UnexpectedEof);
+        let long = "a".repeat(2000);
+        let err = Request::new()
+            .parse_from_reader(&mut long.as_bytes())
+            .unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+        let blank = "\r\n".repeat(2000);
+        let err = Request::new()
+            .parse_from_reader(&mut blank.as_bytes())
+            .unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+
+        let url = alloc::format!("gemini://a.com/{}", "a".repeat(URL_MAX_LENGTH - 15));
+        let line = alloc::format!("\u{feff}{}\r\n", url);
+        let mut req = Request::new();
+        assert_eq!(
+            req.parse_from_reader(&mut line.as_bytes()).unwrap(),
+            line.len()
+        );
     }
 
     #[test]
@@ -259,10 +2190,211 @@ mod test {
         assert_eq!(parse_status(&mut bytes), Ok(Status::Complete(10)));
         assert_eq!(bytes.pos, 2);
 
+        let mut bytes = Bytes::new(b"");
+        assert_eq!(parse_status(&mut bytes), Ok(Status::Partial(Some(2))));
+
         let mut bytes = Bytes::new(b"1");
-        assert_eq!(parse_status(&mut bytes), Ok(Status::Partial));
+        assert_eq!(parse_status(&mut bytes), Ok(Status::Partial(Some(1))));
+
+        assert_eq!(Status::<()>::Partial(Some(2)).needed(), Some(2));
+        assert_eq!(Status::Complete(()).needed(), None);
+        assert!(Status::Complete(()).is_complete());
 
         let mut bytes = Bytes::new(b"a0");
         assert_eq!(parse_status(&mut bytes), Err(Error::Status));
     }
+
+    #[test]
+    fn test_status_code() {
+        assert_eq!(StatusCode::try_from(51), Ok(StatusCode::NotFound));
+        assert_eq!(StatusCode::try_from(25), Ok(StatusCode::Other(25)));
+        assert_eq!(StatusCode::try_from(7), Err(Error::Status));
+        assert_eq!(StatusCode::from_u16_lenient(7), StatusCode::Other(7));
+        assert_eq!(StatusCode::from_u16_lenient(99), StatusCode::Other(99));
+        assert_eq!(u16::from(StatusCode::CertificateNotValid), 62);
+        assert_eq!(u16::from(StatusCode::Other(25)), 25);
+        assert_eq!(StatusCode::NOT_FOUND.as_u16(), 51);
+        assert_eq!(StatusCode::from_u16(44), Ok(StatusCode::SLOW_DOWN));
+        assert_eq!(StatusCode::from_u16(69), Ok(StatusCode::Other(69)));
+        assert_eq!(StatusCode::from_u16(9), Err(Error::Status));
+        assert_eq!(StatusCode::from_u16(70), Err(Error::Status));
+
+        const TABLE: [StatusCode; 2] = [StatusCode::SUCCESS, StatusCode::GONE];
+        assert_eq!(TABLE.map(StatusCode::as_u16), [20, 52]);
+        assert!(matches!(
+            StatusCode::from_u16_lenient(20),
+            StatusCode::SUCCESS
+        ));
+
+        assert_eq!(StatusCode::NotFound.reason(), Some("NOT FOUND"));
+        assert_eq!(StatusCode::Other(25).reason(), None);
+        assert_eq!(StatusCode::NotFound.to_string(), "51 NOT FOUND");
+        assert_eq!(
+            StatusCode::CertificateNotValid.to_string(),
+            "62 CERTIFICATE NOT VALID"
+        );
+        assert_eq!(StatusCode::Other(25).to_string(), "25");
+    }
+
+    #[test]
+    fn test_status_class() {
+        assert_eq!(StatusCode::Input.class(), Some(StatusClass::Input));
+        assert_eq!(StatusCode::Other(25).class(), Some(StatusClass::Success));
+        assert_eq!(StatusCode::Other(70).class(), None);
+        assert_eq!(
+            StatusCode::Other(47).canonical(),
+            StatusCode::TemporaryFailure
+        );
+        assert_eq!(
+            StatusCode::Other(38).canonical(),
+            StatusCode::RedirectTemporary
+        );
+        assert_eq!(StatusCode::Other(70).canonical(), StatusCode::Other(70));
+        assert_eq!(StatusCode::SlowDown.canonical(), StatusCode::SlowDown);
+        assert_eq!(
+            StatusCode::from(StatusClass::ClientCertificateRequired),
+            StatusCode::CertificateRequired
+        );
+        assert!(StatusCode::SensitiveInput.is_input());
+        assert!(StatusCode::Success.is_success());
+        assert!(StatusCode::RedirectPermanent.is_redirect());
+        assert!(StatusCode::SlowDown.is_temporary_failure());
+        assert!(StatusCode::NotFound.is_permanent_failure());
+        assert!(StatusCode::CertificateNotValid.is_client_cert_required());
+        assert!(!StatusCode::NotFound.is_success());
+
+        let mut res = Response::new();
+        assert_eq!(res.class(), None);
+        res.parse(b"31 /new\r\n").unwrap();
+        assert_eq!(res.class(), Some(StatusClass::Redirect));
+    }
+
+    const CORPUS: &[&[u8]] = &[
+        b"",
+        b"\r\n",
+        b"gemini://example.com/\r\n",
+        b"gemini://example.com:1965/a/b?q#f\r\n",
+        b"gemini://[::1]:99999/\r\n",
+        b"gemini://user:pw@host/\r\n",
+        b"titan://host/path;size=10;mime=text/plain;token=x\r\n",
+        b"host /path 12\r\nhello world!",
+        b"\xef\xbb\xbfgemini://bom.example/\r\n",
+        b"20 text/gemini; charset=\"utf\\\"-8\"; lang=en,fr\r\n# hi\n",
+        b"20\r\n",
+        b"30 \r\n",
+        b"44 abc\r\n",
+        b"60 certificate\r\n",
+        b"99 \xff\xfe\r\n",
+        b"1",
+        b"2 text\n",
+        b"# h\n=>\n=> /a b\n* \n>\n```alt\npre\n```\n```\n",
+        b"| a | b |\n|---|\n| 1 | 2 |\n```\n+--+\n| x |\n+==+\n",
+        b"<html><a href='x'>y</a><pre><code class=language-rs>&#x110000;&#</code></pre><img src=",
+        b"Title\n====\n[a]: <b>\n> > q\n- [x][a]\n~~~\n",
+        b"User-agent: *\nDisallow: /private\nAllow: \n",
+        b"\x89PNG\r\n\x1a\n",
+        b"en-Latn-US-x-private",
+    ];
+
+    fn fuzz_one(input: &[u8]) {
+        let mut req = Request::new();
+        let _ = req.parse(input);
+        let _ = req.parse_chained(input, input);
+        let _ = req.authority();
+        let _ = req.validate();
+        let mut res = Response::new();
+        let _ = res.parse(input);
+        let _ = res.parse_chained(input, b"\r\n");
+        let _ = (
+            res.retry_after(),
+            res.prompt(),
+            res.charset(),
+            res.essence(),
+        );
+        let _ = (res.validate_meta(), res.certificate_requirement());
+        let _ = res.sniff_media_type(input);
+        for config in [ParserConfig::lenient(), ParserConfig::strict()] {
+            let _ = RequestRef::new().parse_located(input, &config);
+            let mut res = ResponseRef::new();
+            let _ = res.parse_located(input, &config);
+            let _ = (res.media_type_with_config(&config), res.prompt());
+        }
+
+        let mut req_parser = parser::RequestParser::new();
+        let mut res_parser = parser::ResponseParser::new();
+        for end in 0..=input.len() {
+            let _ = req_parser.parse(&input[..end], &mut Request::new());
+            let _ = res_parser.parse(&input[..end], &mut ResponseRef::new());
+        }
+
+        let _ = Authority::parse(input);
+        let _ = titan::TitanRequest::new().parse(input);
+        let _ = spartan::Request::new().parse(input);
+        let _ = spartan::Response::new().parse(input);
+        let _ = meta::sniff(input);
+
+        let text = String::from_utf8_lossy(input);
+        if let Ok(mt) = MediaType::parse(&text) {
+            let _ = (mt.languages(), mt.to_string());
+        }
+        let _ = MediaType::parse_strict(&text);
+        let _ = meta::LanguageTag::parse(&text);
+        let _ = Url::parse(&text);
+        let _ = robots::Robots::parse(&text).is_allowed("agent", "/private/x");
+
+        let mut doc = gemtext::Document::parse(&text);
+        let _ = (doc.outline(), doc.stats(), doc.heading_slugs());
+        doc.detect_tables();
+        let _ = gemtext::render::html::render(&doc);
+        let _ = gemtext::render::markdown::render(&doc);
+        let _ = gemtext::render::ansi::Renderer::new().width(7).render(&doc);
+        let _ = gemtext::diff(&doc, &gemtext::Document::parse_spanned(&text).0);
+        let _ = gemtext::feed::Feed::parse(&text);
+        let _ = gemtext::format(&text);
+        let _ = gemtext::reflow(&text, 5);
+        let _ = gemtext::tokenize(&text).count();
+        let _ = gemtext::from_html(&text);
+        let _ = gemtext::from_markdown(&text);
+
+        let mut stream = gemtext::StreamParser::new();
+        for chunk in input.chunks(3) {
+            let _ = stream.feed(chunk, |_| {});
+        }
+        let _ = stream.finish(|_| {});
+
+        let mut edit = gemtext::EditableDocument::new(text.clone().into_owned());
+        let mid = (0..=text.len() / 2)
+            .rev()
+            .find(|&i| text.is_char_boundary(i))
+            .unwrap_or(0);
+        edit.edit(mid..text.len(), "\n```\n* x");
+        edit.edit(0..0, "> ");
+    }
+
+    #[test]
+    fn test_never_panics() {
+        const BYTES: &[u8] = b"\0\t\r\n #%/:;<=>@[]`|\x7f\x80\xc3\xef\xff";
+
+        for &seed in CORPUS {
+            let mut inputs = vec![seed.to_vec()];
+            for i in 0..seed.len() {
+                inputs.push(seed[..i].to_vec());
+                inputs.push([&seed[..i], &seed[i + 1..]].concat());
+                for &b in BYTES {
+                    let mut input = seed.to_vec();
+                    input[i] = b;
+                    inputs.push(input);
+                }
+            }
+
+            for input in inputs {
+                let result = std::panic::catch_unwind(|| fuzz_one(&input));
+                assert!(
+                    result.is_ok(),
+                    "panicked on {:?}",
+                    String::from_utf8_lossy(&input)
+                );
+            }
+        }
+    }
 }
@@ -0,0 +1,630 @@
+use crate::Error;
+use alloc::{borrow::Cow, string::String, vec::Vec};
+use core::fmt::{self, Write};
+use core::{result, str};
+#[cfg(feature = "small-meta")]
+use {
+    core::{borrow::Borrow, hash, ops::Deref},
+    smallvec::SmallVec,
+};
+
+#[cfg(not(feature = "small-meta"))]
+pub type MetaString = String;
+
+#[cfg(feature = "small-meta")]
+#[derive(Clone, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub struct MetaString(SmallVec<[u8; 64]>);
+
+#[cfg(feature = "small-meta")]
+impl MetaString {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[cfg(not(feature = "safe"))]
+    #[inline]
+    pub fn as_str(&self) -> &str {
+        unsafe { str::from_utf8_unchecked(&self.0) }
+    }
+
+    #[cfg(feature = "safe")]
+    #[inline]
+    pub fn as_str(&self) -> &str {
+        str::from_utf8(&self.0).unwrap_or_default()
+    }
+
+    #[inline]
+    pub fn is_inline(&self) -> bool {
+        !self.0.spilled()
+    }
+}
+
+#[cfg(feature = "small-meta")]
+impl Deref for MetaString {
+    type Target = str;
+
+    #[inline]
+    fn deref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+#[cfg(feature = "small-meta")]
+impl AsRef<str> for MetaString {
+    #[inline]
+    fn as_ref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+#[cfg(feature = "small-meta")]
+impl Borrow<str> for MetaString {
+    #[inline]
+    fn borrow(&self) -> &str {
+        self.as_str()
+    }
+}
+
+#[cfg(feature = "small-meta")]
+impl hash::Hash for MetaString {
+    #[inline]
+    fn hash<H: hash::Hasher>(&self, state: &mut H) {
+        self.as_str().hash(state)
+    }
+}
+
+#[cfg(feature = "small-meta")]
+impl From<&str> for MetaString {
+    #[inline]
+    fn from(s: &str) -> Self {
+        Self(SmallVec::from_slice(s.as_bytes()))
+    }
+}
+
+#[cfg(feature = "small-meta")]
+impl From<String> for MetaString {
+    #[inline]
+    fn from(s: String) -> Self {
+        Self(SmallVec::from_vec(s.into_bytes()))
+    }
+}
+
+#[cfg(feature = "small-meta")]
+impl From<MetaString> for String {
+    #[inline]
+    fn from(s: MetaString) -> Self {
+        String::from(s.as_str())
+    }
+}
+
+#[cfg(feature = "small-meta")]
+impl PartialEq<str> for MetaString {
+    #[inline]
+    fn eq(&self, other: &str) -> bool {
+        self.as_str() == other
+    }
+}
+
+#[cfg(feature = "small-meta")]
+impl<'a> PartialEq<&'a str> for MetaString {
+    #[inline]
+    fn eq(&self, other: &&'a str) -> bool {
+        self.as_str() == *other
+    }
+}
+
+#[cfg(feature = "small-meta")]
+impl fmt::Debug for MetaString {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self.as_str(), f)
+    }
+}
+
+#[cfg(feature = "small-meta")]
+impl fmt::Display for MetaString {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MediaType<'a> {
+    pub ty: &'a str,
+    pub subtype: &'a str,
+    params: Vec<(&'a str, Cow<'a, str>)>,
+}
+
+impl<'a> MediaType<'a> {
+    #[inline]
+    pub fn parse(meta: &'a str) -> result::Result<Self, Error> {
+        Self::parse_with(meta, true)
+    }
+
+    #[inline]
+    pub fn parse_strict(meta: &'a str) -> result::Result<Self, Error> {
+        Self::parse_with(meta, false)
+    }
+
+    fn parse_with(meta: &'a str, allow_quoted: bool) -> result::Result<Self, Error> {
+        let (essence, mut rest) = meta.split_once(';').unwrap_or((meta, ""));
+        let (ty, subtype) = essence.trim().split_once('/').ok_or(Error::MediaType)?;
+        if !is_token(ty) || !is_token(subtype) {
+            return Err(Error::MediaType);
+        }
+
+        let mut params = Vec::new();
+        loop {
+            rest = rest.trim_start_matches([' ', '\t', ';']);
+            if rest.is_empty() {
+                break;
+            }
+
+            let (key, value) = rest.split_once('=').ok_or(Error::MediaType)?;
+            let key = key.trim_end();
+            let value = value.trim_start();
+            if !is_token(key) {
+                return Err(Error::MediaType);
+            }
+
+            let (value, tail) = match value.strip_prefix('"') {
+                Some(quoted) if allow_quoted => unquote(quoted)?,
+                _ => {
+                    let end = value.find(';').unwrap_or(value.len());
+                    let (value, tail) = value.split_at(end);
+                    let value = value.trim_end();
+                    if !is_param_value(value) {
+                        return Err(Error::MediaType);
+                    }
+                    (Cow::Borrowed(value), tail)
+                }
+            };
+            if !tail.trim_start().is_empty() && !tail.trim_start().starts_with(';') {
+                return Err(Error::MediaType);
+            }
+            params.push((key, value));
+            rest = tail;
+        }
+
+        Ok(Self {
+            ty,
+            subtype,
+            params,
+        })
+    }
+
+    #[inline]
+    pub fn is(&self, ty: &str, subtype: &str) -> bool {
+        self.ty.eq_ignore_ascii_case(ty) && self.subtype.eq_ignore_ascii_case(subtype)
+    }
+
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.params
+            .iter()
+            .find(|(k, _)| k.eq_ignore_ascii_case(key))
+            .map(|(_, v)| &**v)
+    }
+
+    pub fn get_all<'s>(&'s self, key: &'s str) -> impl Iterator<Item = &'s str> + 's {
+        self.params
+            .iter()
+            .filter(move |(k, _)| k.eq_ignore_ascii_case(key))
+            .map(|(_, v)| &**v)
+    }
+
+    pub fn into_param(self, key: &str) -> Option<Cow<'a, str>> {
+        self.params
+            .into_iter()
+            .find(|(k, _)| k.eq_ignore_ascii_case(key))
+            .map(|(_, v)| v)
+    }
+
+    pub fn languages(&self) -> result::Result<Vec<LanguageTag<'_>>, Error> {
+        match self.get("lang") {
+            Some(lang) => lang
+                .split(',')
+                .map(|tag| LanguageTag::parse(tag.trim()))
+                .collect(),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    pub fn primary_language(&self) -> Option<&str> {
+        let tag = self.get("lang")?.split(',').next()?.trim();
+        LanguageTag::parse(tag).ok()?.language()
+    }
+
+    #[inline]
+    pub fn params(&self) -> impl Iterator<Item = (&'a str, &str)> + '_ {
+        self.params.iter().map(|(k, v)| (*k, &**v))
+    }
+}
+
+impl<'a> fmt::Display for MediaType<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.ty, self.subtype)?;
+        for (key, value) in &self.params {
+            if is_param_value(value) {
+                write!(f, "; {}={}", key, value)?;
+                continue;
+            }
+            write!(f, "; {}=\"", key)?;
+            for c in value.chars() {
+                if c == '"' || c == '\\' {
+                    f.write_char('\\')?;
+                }
+                f.write_char(c)?;
+            }
+            f.write_char('"')?;
+        }
+        Ok(())
+    }
+}
+
+fn unquote(s: &str) -> result::Result<(Cow<'_, str>, &str), Error> {
+    let mut owned: Option<String> = None;
+    let mut chars = s.char_indices();
+    let mut start = 0;
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '"' => {
+                let value = match owned {
+                    Some(mut owned) => {
+                        owned.push_str(&s[start..i]);
+                        Cow::Owned(owned)
+                    }
+                    None => Cow::Borrowed(&s[..i]),
+                };
+                return Ok((value, &s[i + 1..]));
+            }
+            '\\' => {
+                let (_, escaped) = chars.next().ok_or(Error::MediaType)?;
+                let owned = owned.get_or_insert_with(String::new);
+                owned.push_str(&s[start..i]);
+                owned.push(escaped);
+                start = i + 1 + escaped.len_utf8();
+            }
+            '\r' | '\n' => return Err(Error::MediaType),
+            _ => {}
+        }
+    }
+    Err(Error::MediaType)
+}
+
+const SNIFF_LEN: usize = 512;
+
+const SIGNATURES: &[(&[u8], &str)] = &[
+    (b"\x89PNG\r\n\x1a\n", "image/png"),
+    (b"GIF87a", "image/gif"),
+    (b"GIF89a", "image/gif"),
+    (b"\xff\xd8\xff", "image/jpeg"),
+    (b"%PDF-", "application/pdf"),
+    (b"PK\x03\x04", "application/zip"),
+    (b"\x1f\x8b", "application/gzip"),
+    (b"OggS", "audio/ogg"),
+    (b"ID3", "audio/mpeg"),
+    (b"fLaC", "audio/flac"),
+];
+
+pub fn sniff(body: &[u8]) -> &'static str {
+    let body = &body[..body.len().min(SNIFF_LEN)];
+    if let Some(&(_, media_type)) = SIGNATURES.iter().find(|(sig, _)| body.starts_with(sig)) {
+        return media_type;
+    }
+    if body.len() >= 12 && &body[..4] == b"RIFF" && &body[8..12] == b"WEBP" {
+        return "image/webp";
+    }
+
+    let text = match str::from_utf8(body) {
+        Ok(text) => text,
+        Err(err) if err.error_len().is_none() => {
+            str::from_utf8(&body[..err.valid_up_to()]).unwrap_or_default()
+        }
+        Err(_) => return "application/octet-stream",
+    };
+    let binary = text
+        .bytes()
+        .any(|b| b < 0x20 && !matches!(b, b'\t' | b'\n' | b'\r' | 0x0c | 0x1b));
+    if binary {
+        return "application/octet-stream";
+    }
+
+    let gemtext = text.lines().any(|line| {
+        line.starts_with("=>")
+            || line.starts_with("```")
+            || line.starts_with("* ")
+            || line.starts_with('#') && line.trim_start_matches('#').starts_with(' ')
+    });
+    if gemtext {
+        "text/gemini; charset=utf-8"
+    } else {
+        "text/plain; charset=utf-8"
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct LanguageTag<'a> {
+    tag: &'a str,
+    language: Option<&'a str>,
+    script: Option<&'a str>,
+    region: Option<&'a str>,
+}
+
+impl<'a> LanguageTag<'a> {
+    pub fn parse(tag: &'a str) -> result::Result<Self, Error> {
+        let mut lang = Self {
+            tag,
+            language: None,
+            script: None,
+            region: None,
+        };
+        let mut subtags = tag.split('-').peekable();
+
+        let first = subtags.next().unwrap_or("");
+        if first.eq_ignore_ascii_case("x") {
+            return private_use(subtags).map(|_| lang);
+        }
+        if !(2..=8).contains(&first.len()) || !is_alpha(first) {
+            return Err(Error::LanguageTag);
+        }
+        lang.language = Some(first);
+
+        let mut extlangs = 0;
+        while first.len() <= 3 && extlangs < 3 {
+            match subtags.peek() {
+                Some(s) if s.len() == 3 && is_alpha(s) => subtags.next(),
+                _ => break,
+            };
+            extlangs += 1;
+        }
+        if let Some(s) = subtags.next_if(|s| s.len() == 4 && is_alpha(s)) {
+            lang.script = Some(s);
+        }
+        if let Some(s) = subtags.next_if(|s| {
+            (s.len() == 2 && is_alpha(s)) || (s.len() == 3 && s.bytes().all(|b| b.is_ascii_digit()))
+        }) {
+            lang.region = Some(s);
+        }
+        while subtags.next_if(|s| is_variant(s)).is_some() {}
+
+        while let Some(singleton) = subtags.next() {
+            if singleton.eq_ignore_ascii_case("x") {
+                return private_use(subtags).map(|_| lang);
+            }
+            if singleton.len() != 1 || !is_alphanumeric(singleton) {
+                return Err(Error::LanguageTag);
+            }
+            let mut count = 0;
+            while subtags
+                .next_if(|s| (2..=8).contains(&s.len()) && is_alphanumeric(s))
+                .is_some()
+            {
+                count += 1;
+            }
+            if count == 0 {
+                return Err(Error::LanguageTag);
+            }
+        }
+        Ok(lang)
+    }
+
+    #[inline]
+    pub fn as_str(&self) -> &'a str {
+        self.tag
+    }
+
+    #[inline]
+    pub fn language(&self) -> Option<&'a str> {
+        self.language
+    }
+
+    #[inline]
+    pub fn script(&self) -> Option<&'a str> {
+        self.script
+    }
+
+    #[inline]
+    pub fn region(&self) -> Option<&'a str> {
+        self.region
+    }
+}
+
+impl<'a> fmt::Display for LanguageTag<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.tag)
+    }
+}
+
+fn private_use<'a>(subtags: impl Iterator<Item = &'a str>) -> result::Result<(), Error> {
+    let mut count = 0;
+    for s in subtags {
+        if s.is_empty() || s.len() > 8 || !is_alphanumeric(s) {
+            return Err(Error::LanguageTag);
+        }
+        count += 1;
+    }
+    if count == 0 {
+        return Err(Error::LanguageTag);
+    }
+    Ok(())
+}
+
+#[inline]
+fn is_variant(s: &str) -> bool {
+    is_alphanumeric(s)
+        && match s.len() {
+            5..=8 => true,
+            4 => s.as_bytes()[0].is_ascii_digit(),
+            _ => false,
+        }
+}
+
+#[inline]
+fn is_alpha(s: &str) -> bool {
+    s.bytes().all(|b| b.is_ascii_alphabetic())
+}
+
+#[inline]
+fn is_alphanumeric(s: &str) -> bool {
+    s.bytes().all(|b| b.is_ascii_alphanumeric())
+}
+
+#[inline]
+pub(crate) fn is_token(s: &str) -> bool {
+    !s.is_empty() && s.bytes().all(is_token_byte)
+}
+
+#[inline]
+pub(crate) fn is_param_value(s: &str) -> bool {
+    !s.is_empty() && s.bytes().all(|b| b == b',' || is_token_byte(b))
+}
+
+#[inline]
+fn is_token_byte(b: u8) -> bool {
+    b.is_ascii_graphic() && !b"()<>@,;:\\\"/[]?=".contains(&b)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_media_type_parse() {
+        let mt = MediaType::parse("text/gemini; charset=utf-8; LANG=en").unwrap();
+        assert_eq!(mt.ty, "text");
+        assert_eq!(mt.subtype, "gemini");
+        assert!(mt.is("Text", "GEMINI"));
+        assert_eq!(mt.get("Charset"), Some("utf-8"));
+        assert_eq!(mt.get("lang"), Some("en"));
+        assert_eq!(mt.get("format"), None);
+        assert_eq!(
+            mt.params().collect::<Vec<_>>(),
+            vec![("charset", "utf-8"), ("LANG", "en")]
+        );
+
+        assert_eq!(mt.to_string(), "text/gemini; charset=utf-8; LANG=en");
+
+        let mt = MediaType::parse("image/png;").unwrap();
+        assert_eq!(mt.params().count(), 0);
+
+        assert_eq!(MediaType::parse(""), Err(Error::MediaType));
+        assert_eq!(MediaType::parse("text"), Err(Error::MediaType));
+        assert_eq!(MediaType::parse("text/"), Err(Error::MediaType));
+        assert_eq!(MediaType::parse("text/plain; a"), Err(Error::MediaType));
+        assert_eq!(MediaType::parse("te xt/plain"), Err(Error::MediaType));
+    }
+
+    #[test]
+    fn test_sniff() {
+        assert_eq!(sniff(b"\x89PNG\r\n\x1a\n\0\0"), "image/png");
+        assert_eq!(sniff(b"RIFF\0\0\0\0WEBPVP8 "), "image/webp");
+        assert_eq!(sniff(b"%PDF-1.7"), "application/pdf");
+        assert_eq!(
+            sniff(b"# Title\n\n=> /a link"),
+            "text/gemini; charset=utf-8"
+        );
+        assert_eq!(sniff(b"#hashtag only"), "text/plain; charset=utf-8");
+        assert_eq!(sniff(b"plain words\r\n"), "text/plain; charset=utf-8");
+        assert_eq!(sniff(b"caf\xc3"), "text/plain; charset=utf-8");
+        assert_eq!(sniff(b"\xff\xfe\x00a"), "application/octet-stream");
+        assert_eq!(sniff(b"abc\0def"), "application/octet-stream");
+
+        let mut long = "x".repeat(SNIFF_LEN).into_bytes();
+        long.extend_from_slice(b"\n=> /late");
+        assert_eq!(sniff(&long), "text/plain; charset=utf-8");
+    }
+
+    #[test]
+    fn test_quoted_params() {
+        let mt = MediaType::parse(r#"text/plain; charset="utf-8"; title="a; \"b\" \\ c" ; x=y"#)
+            .unwrap();
+        assert_eq!(mt.get("charset"), Some("utf-8"));
+        assert_eq!(mt.get("title"), Some(r#"a; "b" \ c"#));
+        assert_eq!(mt.get("x"), Some("y"));
+        assert!(matches!(
+            mt.clone().into_param("charset"),
+            Some(Cow::Borrowed("utf-8"))
+        ));
+        assert_eq!(
+            mt.to_string(),
+            r#"text/plain; charset=utf-8; title="a; \"b\" \\ c"; x=y"#
+        );
+
+        assert_eq!(
+            MediaType::parse_strict(r#"text/plain; charset="utf-8""#),
+            Err(Error::MediaType)
+        );
+        assert_eq!(
+            MediaType::parse(r#"text/plain; a="unterminated"#),
+            Err(Error::MediaType)
+        );
+        assert_eq!(
+            MediaType::parse(r#"text/plain; a="b" c"#),
+            Err(Error::MediaType)
+        );
+    }
+
+    #[test]
+    fn test_unknown_params() {
+        let mt = MediaType::parse("text/gemini; x-feed=yes; charset=utf-8; X-Feed=no").unwrap();
+        assert_eq!(
+            mt.params().collect::<Vec<_>>(),
+            vec![("x-feed", "yes"), ("charset", "utf-8"), ("X-Feed", "no")]
+        );
+        assert_eq!(mt.get("X-FEED"), Some("yes"));
+        assert_eq!(mt.get_all("x-feed").collect::<Vec<_>>(), vec!["yes", "no"]);
+        assert_eq!(mt.get_all("lang").count(), 0);
+        assert_eq!(
+            mt.to_string(),
+            "text/gemini; x-feed=yes; charset=utf-8; X-Feed=no"
+        );
+    }
+
+    #[test]
+    fn test_languages() {
+        let mt = MediaType::parse("text/gemini; lang=en-Latn-GB,fr,zh-yue-HK").unwrap();
+        let langs = mt.languages().unwrap();
+        assert_eq!(langs.len(), 3);
+        assert_eq!(langs[0].language(), Some("en"));
+        assert_eq!(langs[0].script(), Some("Latn"));
+        assert_eq!(langs[0].region(), Some("GB"));
+        assert_eq!(langs[1].as_str(), "fr");
+        assert_eq!(langs[2].region(), Some("HK"));
+        assert_eq!(mt.primary_language(), Some("en"));
+
+        let mt = MediaType::parse("text/gemini").unwrap();
+        assert_eq!(mt.languages(), Ok(Vec::new()));
+        assert_eq!(mt.primary_language(), None);
+        let mt = MediaType::parse("text/gemini; lang=en,e").unwrap();
+        assert_eq!(mt.languages(), Err(Error::LanguageTag));
+
+        for tag in [
+            "de-CH-1901",
+            "sl-rozaj-biske",
+            "es-419",
+            "en-a-bbb-x-a",
+            "x-whatever",
+        ] {
+            assert!(LanguageTag::parse(tag).is_ok(), "{}", tag);
+        }
+        assert_eq!(LanguageTag::parse("x-whatever").unwrap().language(), None);
+        for tag in ["", "e", "en-", "en-a", "en-x", "toolongtag", "en_GB", "1en"] {
+            assert_eq!(LanguageTag::parse(tag), Err(Error::LanguageTag), "{}", tag);
+        }
+    }
+
+    #[cfg(feature = "small-meta")]
+    #[test]
+    fn test_meta_string() {
+        let meta = MetaString::from("text/gemini");
+        assert!(meta.is_inline());
+        assert_eq!(meta, "text/gemini");
+        assert_eq!(meta.to_string(), "text/gemini");
+        assert_eq!(std::format!("{:?}", meta), "\"text/gemini\"");
+
+        let long = MetaString::from("a".repeat(100));
+        assert!(!long.is_inline());
+        assert_eq!(long.len(), 100);
+        assert_eq!(String::from(long), "a".repeat(100));
+    }
+}
@@ -0,0 +1,265 @@
+use crate::iter::Bytes;
+use crate::{
+    next_line_limit, parse_status, skip_empty_lines, Error, Result, Status, META_MAX_LENGTH,
+};
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::{result, str};
+
+const SCHEME: &str = "misfin://";
+const REQUEST_MAX_LENGTH: usize = 2048;
+
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Request {
+    pub mailbox: Option<String>,
+    pub host: Option<String>,
+    pub message: Option<String>,
+}
+
+impl Request {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn parse(&mut self, buf: &[u8]) -> Result<usize> {
+        let mut bytes = Bytes::new(buf);
+        complete!(skip_empty_lines(&mut bytes));
+
+        let start = bytes.pos;
+        let end = complete!(next_line_limit(
+            &mut bytes,
+            REQUEST_MAX_LENGTH - 2,
+            Error::UriTooLong
+        ));
+        let line = str::from_utf8(&buf[start..end])?;
+
+        let rest = line.strip_prefix(SCHEME).ok_or(Error::Scheme)?;
+        let (address, message) = rest.split_once(' ').ok_or(Error::RequestLine)?;
+        let (mailbox, host) = address.split_once('@').ok_or(Error::RequestLine)?;
+        if mailbox.is_empty() || host.contains('@') {
+            return Err(Error::RequestLine);
+        }
+        if host.is_empty() {
+            return Err(Error::MissingHost);
+        }
+
+        self.mailbox = Some(String::from(mailbox));
+        self.host = Some(String::from(host));
+        self.message = Some(String::from(message));
+
+        Ok(Status::Complete(bytes.pos))
+    }
+
+    pub fn encode(&self, buf: &mut Vec<u8>) -> result::Result<(), Error> {
+        let mailbox = self.mailbox.as_deref().ok_or(Error::EmptyRequest)?;
+        let host = self.host.as_deref().ok_or(Error::MissingHost)?;
+        let message = self.message.as_deref().unwrap_or("");
+        if mailbox.is_empty() || invalid_address(mailbox) || invalid_address(host) {
+            return Err(Error::RequestLine);
+        }
+        if host.is_empty() {
+            return Err(Error::MissingHost);
+        }
+        if message.bytes().any(|b| b == b'\r' || b == b'\n') {
+            return Err(Error::RequestLine);
+        }
+
+        let len = SCHEME.len() + mailbox.len() + 1 + host.len() + 1 + message.len() + 2;
+        if len > REQUEST_MAX_LENGTH {
+            return Err(Error::UriTooLong);
+        }
+
+        buf.extend_from_slice(SCHEME.as_bytes());
+        buf.extend_from_slice(mailbox.as_bytes());
+        buf.push(b'@');
+        buf.extend_from_slice(host.as_bytes());
+        buf.push(b' ');
+        buf.extend_from_slice(message.as_bytes());
+        buf.extend_from_slice(b"\r\n");
+        Ok(())
+    }
+
+    pub fn address(&self) -> Option<String> {
+        let mailbox = self.mailbox.as_deref()?;
+        let host = self.host.as_deref()?;
+        let mut address = String::with_capacity(mailbox.len() + 1 + host.len());
+        address.push_str(mailbox);
+        address.push('@');
+        address.push_str(host);
+        Some(address)
+    }
+}
+
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Response {
+    pub status: Option<u8>,
+    pub meta: Option<String>,
+}
+
+impl Response {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn parse(&mut self, buf: &[u8]) -> Result<usize> {
+        let mut bytes = Bytes::new(buf);
+        let status = complete!(parse_status(&mut bytes));
+        if !(20..70).contains(&status) {
+            return Err(Error::Status);
+        }
+        expect!(bytes.next() == b' ' => Err(Error::MissingSpaceAfterStatus));
+
+        let start = bytes.pos;
+        let end = complete!(next_line_limit(
+            &mut bytes,
+            META_MAX_LENGTH,
+            Error::MetaTooLong
+        ));
+        let meta = str::from_utf8(&buf[start..end])?;
+
+        self.status = Some(status as u8);
+        self.meta = Some(String::from(meta));
+        Ok(Status::Complete(bytes.pos))
+    }
+
+    pub fn encode(&self, buf: &mut Vec<u8>) -> result::Result<(), Error> {
+        let status = self.status.ok_or(Error::Status)?;
+        if !(20..70).contains(&status) {
+            return Err(Error::Status);
+        }
+
+        let meta = self.meta.as_deref().unwrap_or("");
+        if meta.len() > META_MAX_LENGTH || meta.bytes().any(|b| b == b'\r' || b == b'\n') {
+            return Err(Error::ResponseHeader);
+        }
+
+        buf.push(b'0' + status / 10);
+        buf.push(b'0' + status % 10);
+        buf.push(b' ');
+        buf.extend_from_slice(meta.as_bytes());
+        buf.extend_from_slice(b"\r\n");
+        Ok(())
+    }
+
+    #[inline]
+    pub fn is_success(&self) -> bool {
+        self.class() == Some(2)
+    }
+
+    #[inline]
+    pub fn is_redirect(&self) -> bool {
+        self.class() == Some(3)
+    }
+
+    #[inline]
+    pub fn is_temporary_failure(&self) -> bool {
+        self.class() == Some(4)
+    }
+
+    #[inline]
+    pub fn is_permanent_failure(&self) -> bool {
+        self.class() == Some(5)
+    }
+
+    #[inline]
+    pub fn is_certificate_failure(&self) -> bool {
+        self.class() == Some(6)
+    }
+
+    pub fn fingerprint(&self) -> Option<&str> {
+        self.meta.as_deref().filter(|_| self.is_success())
+    }
+
+    pub fn redirect(&self) -> Option<&str> {
+        self.meta.as_deref().filter(|_| self.is_redirect())
+    }
+
+    #[inline]
+    fn class(&self) -> Option<u8> {
+        self.status.map(|s| s / 10)
+    }
+}
+
+#[inline]
+fn invalid_address(s: &str) -> bool {
+    s.bytes()
+        .any(|b| b == b'@' || b == b' ' || b == b'\r' || b == b'\n')
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_request() {
+        let buf = b"misfin://alice@example.com Hello, world!\r\nrest";
+        let mut req = Request::new();
+        assert_eq!(req.parse(buf), Ok(Status::Complete(42)));
+        assert_eq!(req.mailbox.as_deref(), Some("alice"));
+        assert_eq!(req.host.as_deref(), Some("example.com"));
+        assert_eq!(req.message.as_deref(), Some("Hello, world!"));
+        assert_eq!(req.address().as_deref(), Some("alice@example.com"));
+
+        let mut out = Vec::new();
+        req.encode(&mut out).unwrap();
+        assert_eq!(out, &buf[..42]);
+
+        let mut req = Request::new();
+        assert_eq!(
+            req.parse(b"misfin://a@b.com hi"),
+            Ok(Status::Partial(Some(1)))
+        );
+        assert_eq!(req.parse(b"gemini://a@b.com hi\r\n"), Err(Error::Scheme));
+        assert_eq!(req.parse(b"misfin://a@b.com\r\n"), Err(Error::RequestLine));
+        assert_eq!(req.parse(b"misfin://b.com hi\r\n"), Err(Error::RequestLine));
+        assert_eq!(
+            req.parse(b"misfin://@b.com hi\r\n"),
+            Err(Error::RequestLine)
+        );
+        assert_eq!(req.parse(b"misfin://a@ hi\r\n"), Err(Error::MissingHost));
+
+        let mut long = Vec::from(&b"misfin://a@b.com "[..]);
+        long.resize(REQUEST_MAX_LENGTH, b'x');
+        assert_eq!(req.parse(&long), Err(Error::UriTooLong));
+
+        req.mailbox = Some(String::from("a"));
+        req.host = Some(String::from("b.com"));
+        req.message = Some(String::from("two\nlines"));
+        assert_eq!(req.encode(&mut Vec::new()), Err(Error::RequestLine));
+    }
+
+    #[test]
+    fn test_response() {
+        let mut res = Response::new();
+        assert_eq!(res.parse(b"20 abc123\r\n"), Ok(Status::Complete(11)));
+        assert_eq!(res.status, Some(20));
+        assert!(res.is_success());
+        assert_eq!(res.fingerprint(), Some("abc123"));
+        assert_eq!(res.redirect(), None);
+
+        let mut out = Vec::new();
+        res.encode(&mut out).unwrap();
+        assert_eq!(out, b"20 abc123\r\n");
+
+        res.parse(b"31 bob@example.org\r\n").unwrap();
+        assert!(res.is_redirect());
+        assert_eq!(res.redirect(), Some("bob@example.org"));
+
+        res.parse(b"45 mailbox full\r\n").unwrap();
+        assert!(res.is_temporary_failure());
+        res.parse(b"51 \r\n").unwrap();
+        assert!(res.is_permanent_failure());
+        res.parse(b"63 \r\n").unwrap();
+        assert!(res.is_certificate_failure());
+
+        assert_eq!(res.parse(b"20 x"), Ok(Status::Partial(Some(1))));
+        assert_eq!(res.parse(b"10 x\r\n"), Err(Error::Status));
+        assert_eq!(res.parse(b"20x\r\n"), Err(Error::MissingSpaceAfterStatus));
+        assert_eq!(res.status, Some(63));
+
+        res.status = Some(7);
+        assert_eq!(res.encode(&mut Vec::new()), Err(Error::Status));
+    }
+}
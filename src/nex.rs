@@ -0,0 +1,186 @@
+use crate::gemtext::Builder;
+use crate::iter::Bytes;
+use crate::{next_line_limit, Error, Result, Status, Url, URL_MAX_LENGTH};
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::{result, str};
+
+pub const DEFAULT_PORT: u16 = 1900;
+
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Request {
+    pub path: Option<String>,
+}
+
+impl Request {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn parse(&mut self, buf: &[u8]) -> Result<usize> {
+        let mut bytes = Bytes::new(buf);
+        let end = complete!(next_line_limit(
+            &mut bytes,
+            URL_MAX_LENGTH,
+            Error::UriTooLong
+        ));
+        let path = str::from_utf8(&buf[..end])?;
+        if path.bytes().any(|b| b.is_ascii_control()) {
+            return Err(Error::RequestLine);
+        }
+
+        self.path = Some(String::from(path));
+        Ok(Status::Complete(bytes.pos))
+    }
+
+    pub fn encode(&self, buf: &mut Vec<u8>) -> result::Result<(), Error> {
+        let path = self.path.as_deref().unwrap_or("");
+        if path.len() > URL_MAX_LENGTH {
+            return Err(Error::UriTooLong);
+        }
+        if path.bytes().any(|b| b.is_ascii_control()) {
+            return Err(Error::RequestLine);
+        }
+
+        buf.extend_from_slice(path.as_bytes());
+        buf.push(b'\n');
+        Ok(())
+    }
+
+    #[inline]
+    pub fn is_directory(&self) -> bool {
+        self.path
+            .as_deref()
+            .is_none_or(|p| p.is_empty() || p.ends_with('/'))
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Entry<'a> {
+    Link {
+        url: &'a str,
+        label: Option<&'a str>,
+    },
+    Text(&'a str),
+}
+
+impl<'a> Entry<'a> {
+    pub fn parse(line: &'a str) -> Self {
+        let line = line.strip_suffix('\r').unwrap_or(line);
+        let rest = match line.strip_prefix("=>") {
+            Some(rest) => rest.trim_start(),
+            None => return Entry::Text(line),
+        };
+        if rest.is_empty() {
+            return Entry::Text(line);
+        }
+
+        let (url, label) = match rest.find(char::is_whitespace) {
+            Some(i) => (&rest[..i], Some(rest[i..].trim()).filter(|l| !l.is_empty())),
+            None => (rest, None),
+        };
+        Entry::Link { url, label }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Entries<'a> {
+    lines: str::Split<'a, char>,
+}
+
+impl<'a> Iterator for Entries<'a> {
+    type Item = Entry<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.lines.next().map(Entry::parse)
+    }
+}
+
+pub fn entries(listing: &str) -> Entries<'_> {
+    let listing = listing.strip_suffix('\n').unwrap_or(listing);
+    Entries {
+        lines: listing.split('\n'),
+    }
+}
+
+pub fn to_gemtext(listing: &str, base: Option<&Url>) -> String {
+    let mut builder = Builder::new();
+    for entry in entries(listing) {
+        match entry {
+            Entry::Link { url, label } => match base.and_then(|base| base.join(url).ok()) {
+                Some(url) => builder.link(url.as_str(), label),
+                None => builder.link(url, label),
+            },
+            Entry::Text(text) => builder.text(text),
+        };
+    }
+    builder.build()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_request() {
+        let mut req = Request::new();
+        assert_eq!(req.parse(b"docs/\r\nrest"), Ok(Status::Complete(7)));
+        assert_eq!(req.path.as_deref(), Some("docs/"));
+        assert!(req.is_directory());
+
+        assert_eq!(req.parse(b"\n"), Ok(Status::Complete(1)));
+        assert_eq!(req.path.as_deref(), Some(""));
+        assert!(req.is_directory());
+
+        assert_eq!(req.parse(b"notes.txt\n"), Ok(Status::Complete(10)));
+        assert!(!req.is_directory());
+
+        let mut out = Vec::new();
+        req.encode(&mut out).unwrap();
+        assert_eq!(out, b"notes.txt\n");
+
+        assert_eq!(req.parse(b"notes.txt"), Ok(Status::Partial(Some(1))));
+        assert_eq!(req.parse(b"a\tb\n"), Err(Error::RequestLine));
+
+        let mut long = Vec::new();
+        long.resize(URL_MAX_LENGTH + 1, b'a');
+        assert_eq!(req.parse(&long), Err(Error::UriTooLong));
+    }
+
+    #[test]
+    fn test_entries() {
+        let listing = "Welcome\n=> docs/ Documents\n=>notes.txt\n=> \n";
+        let entries: Vec<_> = entries(listing).collect();
+        assert_eq!(
+            entries,
+            [
+                Entry::Text("Welcome"),
+                Entry::Link {
+                    url: "docs/",
+                    label: Some("Documents")
+                },
+                Entry::Link {
+                    url: "notes.txt",
+                    label: None
+                },
+                Entry::Text("=> "),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_to_gemtext() {
+        let listing = "# not a heading\n\n=> docs/ Documents\n=> nex://other.net/\n";
+        assert_eq!(
+            to_gemtext(listing, None),
+            " # not a heading\n\n=> docs/ Documents\n=> nex://other.net/\n"
+        );
+
+        let base = Url::parse("nex://example.com/dir/").unwrap();
+        assert_eq!(
+            to_gemtext("=> docs/ Documents\n", Some(&base)),
+            "=> nex://example.com/dir/docs/ Documents\n"
+        );
+    }
+}
@@ -0,0 +1,254 @@
+use crate::iter::Bytes;
+use crate::{
+    next_line_limit, parse_status, skip_empty_lines, Error, ParserConfig, Request, ResponseRef,
+    Result, Status, StatusCode, BOM,
+};
+
+#[derive(Clone, Debug)]
+pub struct RequestParser {
+    config: ParserConfig,
+    start: Option<usize>,
+    examined: usize,
+}
+
+impl Default for RequestParser {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RequestParser {
+    #[inline]
+    pub fn new() -> Self {
+        Self::with_config(ParserConfig::default())
+    }
+
+    #[inline]
+    pub fn with_limit(limit: usize) -> Self {
+        Self::with_config(ParserConfig::default().max_url_length(limit).clone())
+    }
+
+    #[inline]
+    pub fn with_config(config: ParserConfig) -> Self {
+        Self {
+            config,
+            start: None,
+            examined: 0,
+        }
+    }
+
+    #[inline]
+    pub fn reset(&mut self) {
+        self.start = None;
+        self.examined = 0;
+    }
+
+    pub fn parse(&mut self, buf: &[u8], req: &mut Request) -> Result<usize> {
+        let start = match self.start {
+            Some(start) => start,
+            None => {
+                let mut bytes = Bytes::new(buf);
+                bytes.advance(self.examined);
+
+                if let Status::Partial(needed) = skip_empty_lines(&mut bytes)? {
+                    self.examined = resume_at(buf);
+                    return Ok(Status::Partial(needed));
+                }
+
+                self.start = Some(bytes.pos);
+                self.examined = bytes.pos;
+                bytes.pos
+            }
+        };
+
+        let limit = (self.config.max_url_length + BOM.len()).saturating_sub(self.examined - start);
+        complete!(scan_line(buf, &mut self.examined, limit, Error::UriTooLong));
+
+        req.parse_located(buf, &self.config).map_err(|err| err.kind)
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct ResponseParser {
+    config: ParserConfig,
+    status: Option<StatusCode>,
+    start: usize,
+    examined: usize,
+}
+
+impl ResponseParser {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[inline]
+    pub fn with_config(config: ParserConfig) -> Self {
+        Self {
+            config,
+            ..Self::default()
+        }
+    }
+
+    #[inline]
+    pub fn reset(&mut self) {
+        self.status = None;
+        self.start = 0;
+        self.examined = 0;
+    }
+
+    pub fn parse<'a>(&mut self, buf: &'a [u8], res: &mut ResponseRef<'a>) -> Result<usize> {
+        if self.status.is_none() {
+            let mut bytes = Bytes::new(buf);
+            let status = StatusCode::from_u16_lenient(complete!(parse_status(&mut bytes)));
+            match bytes.peek() {
+                Some(b' ') => bytes.bump(),
+                Some(b'\r' | b'\n') if self.config.allow_empty_meta => {}
+                Some(_) => return Err(Error::MissingSpaceAfterStatus),
+                None => return Ok(Status::Partial(Some(1))),
+            }
+
+            self.status = Some(status);
+            self.start = bytes.pos;
+            self.examined = bytes.pos;
+        }
+
+        let limit = self
+            .config
+            .max_meta_length
+            .saturating_sub(self.examined - self.start);
+        complete!(scan_line(
+            buf,
+            &mut self.examined,
+            limit,
+            Error::MetaTooLong
+        ));
+
+        res.parse_located(buf, &self.config).map_err(|err| err.kind)
+    }
+}
+
+#[inline]
+fn scan_line(buf: &[u8], examined: &mut usize, limit: usize, overflow: Error) -> Result<usize> {
+    let mut bytes = Bytes::new(buf);
+    bytes.advance(*examined);
+
+    match next_line_limit(&mut bytes, limit, overflow)? {
+        Status::Complete(end) => {
+            *examined = bytes.pos;
+            Ok(Status::Complete(end))
+        }
+        Status::Partial(needed) => {
+            *examined = resume_at(buf);
+            Ok(Status::Partial(needed))
+        }
+    }
+}
+
+#[inline]
+fn resume_at(buf: &[u8]) -> usize {
+    match buf.last() {
+        Some(b'\r') => buf.len() - 1,
+        _ => buf.len(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_request_parser() {
+        let buf = b"\r\ngemini://example.com/\r\n";
+        let mut parser = RequestParser::new();
+        let mut req = Request::new();
+        for i in 0..buf.len() {
+            assert_eq!(
+                parser.parse(&buf[..i], &mut req),
+                Ok(Status::Partial(Some(1)))
+            );
+        }
+        assert_eq!(parser.parse(buf, &mut req), Ok(Status::Complete(buf.len())));
+        assert_eq!(req.url.unwrap().as_str(), "gemini://example.com/");
+
+        let mut parser = RequestParser::with_limit(10);
+        let mut req = Request::new();
+        assert_eq!(
+            parser.parse(b"gemini://a", &mut req),
+            Ok(Status::Partial(Some(1)))
+        );
+        assert_eq!(
+            parser.parse(b"gemini://a.com\r\n", &mut req),
+            Err(Error::UriTooLong)
+        );
+
+        let strict = ParserConfig::strict();
+        for buf in [
+            &b"gemini://a.com/\n"[..],
+            b"gemini://a.com/ \r\n",
+            b"https://a.com/\r\n",
+            b"\xef\xbb\xbfgemini://a.com/\r\n",
+            b"gemini://a.com/#x\r\n",
+        ] {
+            let expected = strict.parse_request(&mut Request::new(), buf);
+            assert!(expected.is_err());
+            let mut parser = RequestParser::with_config(strict.clone());
+            assert_eq!(parser.parse(buf, &mut req), expected);
+        }
+    }
+
+    #[test]
+    fn test_response_parser() {
+        let buf = b"20 text/gemini\r\nbody";
+        let mut parser = ResponseParser::new();
+        for i in 0..16 {
+            let mut res = ResponseRef::new();
+            let needed = if i == 0 { 2 } else { 1 };
+            assert_eq!(
+                parser.parse(&buf[..i], &mut res),
+                Ok(Status::Partial(Some(needed)))
+            );
+        }
+        let mut res = ResponseRef::new();
+        assert_eq!(parser.parse(buf, &mut res), Ok(Status::Complete(16)));
+        assert_eq!(res.status, Some(StatusCode::Success));
+        assert_eq!(res.meta, Some("text/gemini"));
+
+        let mut parser = ResponseParser::new();
+        let mut res = ResponseRef::new();
+        assert_eq!(
+            parser.parse(b"20 a\r", &mut res),
+            Ok(Status::Partial(Some(1)))
+        );
+        assert_eq!(
+            parser.parse(b"20 a\rb", &mut res),
+            Err(Error::LoneCarriageReturn)
+        );
+
+        parser.reset();
+        assert_eq!(parser.parse(b"51\r\n", &mut res), Ok(Status::Complete(4)));
+        assert_eq!(res.meta, Some(""));
+
+        parser.reset();
+        assert_eq!(parser.parse(b"2x", &mut res), Err(Error::Status));
+
+        let mut config = ParserConfig::strict();
+        config.allow_empty_meta(false).max_meta_length(4);
+        let mut parser = ResponseParser::with_config(config);
+        assert_eq!(
+            parser.parse(b"51\r\n", &mut res),
+            Err(Error::MissingSpaceAfterStatus)
+        );
+        parser.reset();
+        assert_eq!(
+            parser.parse(b"20 text/gemini\r\n", &mut res),
+            Err(Error::MetaTooLong)
+        );
+        parser.reset();
+        assert_eq!(
+            parser.parse(b"20 a/b\n", &mut res),
+            Err(Error::BareLineFeed)
+        );
+    }
+}
@@ -0,0 +1,162 @@
+use alloc::{string::String, vec::Vec};
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum VirtualAgent {
+    Archiver,
+    Indexer,
+    Researcher,
+    Webproxy,
+}
+
+impl VirtualAgent {
+    #[inline]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            VirtualAgent::Archiver => "archiver",
+            VirtualAgent::Indexer => "indexer",
+            VirtualAgent::Researcher => "researcher",
+            VirtualAgent::Webproxy => "webproxy",
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Rule {
+    Allow(String),
+    Disallow(String),
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+struct Group {
+    agents: Vec<String>,
+    rules: Vec<Rule>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Robots {
+    groups: Vec<Group>,
+}
+
+impl Robots {
+    pub fn parse(body: &str) -> Self {
+        let mut groups: Vec<Group> = Vec::new();
+        let mut in_rules = true;
+
+        for line in body.lines() {
+            let line = line.split('#').next().unwrap_or("").trim();
+            let Some((key, value)) = line.split_once(':') else {
+                continue;
+            };
+            let value = value.trim();
+
+            if key.trim().eq_ignore_ascii_case("user-agent") {
+                if in_rules {
+                    groups.push(Group::default());
+                    in_rules = false;
+                }
+                if let Some(group) = groups.last_mut() {
+                    group.agents.push(value.to_ascii_lowercase());
+                }
+                continue;
+            }
+
+            let rule = match key.trim().to_ascii_lowercase().as_str() {
+                "allow" => Rule::Allow(String::from(value)),
+                "disallow" => Rule::Disallow(String::from(value)),
+                _ => continue,
+            };
+            in_rules = true;
+            if let Some(group) = groups.last_mut() {
+                group.rules.push(rule);
+            }
+        }
+
+        Self { groups }
+    }
+
+    #[inline]
+    pub fn is_allowed(&self, agent: &str, path: &str) -> bool {
+        self.check(path, |a| a == "*" || a.eq_ignore_ascii_case(agent))
+    }
+
+    #[inline]
+    pub fn is_allowed_virtual(&self, agent: VirtualAgent, path: &str) -> bool {
+        self.is_allowed(agent.as_str(), path)
+    }
+
+    pub fn is_allowed_as(&self, agent: &str, virtual_agent: VirtualAgent, path: &str) -> bool {
+        self.check(path, |a| {
+            a == "*" || a.eq_ignore_ascii_case(agent) || a == virtual_agent.as_str()
+        })
+    }
+
+    fn check<F>(&self, path: &str, f: F) -> bool
+    where
+        F: Fn(&str) -> bool,
+    {
+        let rules = self
+            .groups
+            .iter()
+            .filter(|g| g.agents.iter().any(|a| f(a)))
+            .flat_map(|g| &g.rules);
+
+        let mut best: Option<(usize, bool)> = None;
+        for rule in rules {
+            let (prefix, allow) = match rule {
+                Rule::Allow(prefix) => (prefix, true),
+                Rule::Disallow(prefix) if prefix.is_empty() => continue,
+                Rule::Disallow(prefix) => (prefix, false),
+            };
+            if !path.starts_with(prefix.as_str()) {
+                continue;
+            }
+            let better = match best {
+                Some((len, best_allow)) => {
+                    prefix.len() > len || (prefix.len() == len && allow && !best_allow)
+                }
+                None => true,
+            };
+            if better {
+                best = Some((prefix.len(), allow));
+            }
+        }
+        best.is_none_or(|(_, allow)| allow)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const ROBOTS: &str = "# Gemini robots.txt\n\
+        User-agent: *\n\
+        Disallow: /private\n\
+        \n\
+        User-agent: archiver\n\
+        User-agent: Indexer\n\
+        Disallow: /\n\
+        Allow: /public\n\
+        \n\
+        User-agent: webproxy\n\
+        Disallow:\n";
+
+    #[test]
+    fn test_robots() {
+        let robots = Robots::parse(ROBOTS);
+        assert!(robots.is_allowed("researcher", "/index.gmi"));
+        assert!(!robots.is_allowed("researcher", "/private/x"));
+        assert!(!robots.is_allowed_virtual(VirtualAgent::Archiver, "/index.gmi"));
+        assert!(robots.is_allowed_virtual(VirtualAgent::Indexer, "/public/a.gmi"));
+        assert!(!robots.is_allowed("INDEXER", "/"));
+        assert!(!robots.is_allowed_virtual(VirtualAgent::Webproxy, "/private"));
+        assert!(robots.is_allowed_virtual(VirtualAgent::Webproxy, "/index.gmi"));
+        assert!(!robots.is_allowed("mybot", "/private"));
+        assert!(robots.is_allowed("mybot", "/index.gmi"));
+        assert!(!robots.is_allowed_as("mybot", VirtualAgent::Archiver, "/index.gmi"));
+        assert!(!robots.is_allowed_as("mybot", VirtualAgent::Webproxy, "/private/x"));
+        assert!(robots.is_allowed_as("mybot", VirtualAgent::Webproxy, "/index.gmi"));
+
+        let empty = Robots::parse("");
+        assert!(empty.is_allowed("indexer", "/"));
+    }
+}
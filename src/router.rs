@@ -0,0 +1,205 @@
+use crate::{Request, Response, StatusCode};
+use alloc::{boxed::Box, string::String, vec::Vec};
+
+pub trait Handler {
+    fn handle(&self, req: &Request, params: &Params<'_>) -> (Response, Vec<u8>);
+}
+
+impl<F> Handler for F
+where
+    F: Fn(&Request, &Params<'_>) -> (Response, Vec<u8>),
+{
+    #[inline]
+    fn handle(&self, req: &Request, params: &Params<'_>) -> (Response, Vec<u8>) {
+        self(req, params)
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Params<'a> {
+    params: Vec<(&'a str, &'a str)>,
+}
+
+impl<'a> Params<'a> {
+    pub fn get(&self, name: &str) -> Option<&'a str> {
+        self.params
+            .iter()
+            .find(|(n, _)| *n == name)
+            .map(|&(_, value)| value)
+    }
+
+    #[inline]
+    pub fn iter(&self) -> impl Iterator<Item = (&'a str, &'a str)> + '_ {
+        self.params.iter().copied()
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.params.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.params.is_empty()
+    }
+}
+
+struct Route<'h> {
+    pattern: String,
+    handler: Box<dyn Handler + 'h>,
+}
+
+pub struct Router<'h> {
+    routes: Vec<Route<'h>>,
+    fallback: Option<Box<dyn Handler + 'h>>,
+}
+
+impl<'h> Default for Router<'h> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'h> Router<'h> {
+    #[inline]
+    pub fn new() -> Self {
+        Self {
+            routes: Vec::new(),
+            fallback: None,
+        }
+    }
+
+    pub fn route<H: Handler + 'h>(mut self, pattern: &str, handler: H) -> Self {
+        self.routes.push(Route {
+            pattern: String::from(pattern),
+            handler: Box::new(handler),
+        });
+        self
+    }
+
+    pub fn fallback<H: Handler + 'h>(mut self, handler: H) -> Self {
+        self.fallback = Some(Box::new(handler));
+        self
+    }
+
+    pub fn dispatch(&self, req: &Request) -> (Response, Vec<u8>) {
+        let Some(url) = &req.url else {
+            return failure(StatusCode::BadRequest, "Bad request");
+        };
+
+        for route in &self.routes {
+            if let Some(params) = matches(&route.pattern, url.path()) {
+                return route.handler.handle(req, &params);
+            }
+        }
+        match &self.fallback {
+            Some(handler) => handler.handle(req, &Params::default()),
+            None => failure(StatusCode::NotFound, "Not found"),
+        }
+    }
+}
+
+fn matches<'a>(pattern: &'a str, path: &'a str) -> Option<Params<'a>> {
+    let mut params = Vec::new();
+    let mut segments = path.strip_prefix('/').unwrap_or(path).split('/');
+    let mut rest = path.strip_prefix('/').unwrap_or(path);
+
+    for part in pattern.strip_prefix('/').unwrap_or(pattern).split('/') {
+        if let Some(name) = part.strip_prefix('*') {
+            params.push((name, rest));
+            return Some(Params { params });
+        }
+
+        let segment = segments.next()?;
+        rest = rest.get(segment.len() + 1..).unwrap_or("");
+        match part.strip_prefix(':') {
+            Some(name) if !segment.is_empty() => params.push((name, segment)),
+            Some(_) => return None,
+            None if part == segment => {}
+            None => return None,
+        }
+    }
+
+    match segments.next() {
+        Some(_) => None,
+        None => Some(Params { params }),
+    }
+}
+
+#[inline]
+fn failure(status: StatusCode, message: &str) -> (Response, Vec<u8>) {
+    let res = Response {
+        status: Some(status),
+        meta: Some(message.into()),
+    };
+    (res, Vec::new())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::Url;
+
+    fn request(url: &str) -> Request {
+        Request {
+            url: Some(Url::parse(url).unwrap()),
+        }
+    }
+
+    fn ok(body: &str) -> (Response, Vec<u8>) {
+        let res = Response {
+            status: Some(StatusCode::Success),
+            meta: Some("text/gemini".into()),
+        };
+        (res, body.as_bytes().to_vec())
+    }
+
+    #[test]
+    fn test_matches() {
+        let params = matches("/posts/:id", "/posts/42").unwrap();
+        assert_eq!(params.get("id"), Some("42"));
+        assert_eq!(params.len(), 1);
+        assert!(matches("/posts/:id", "/posts/").is_none());
+        assert!(matches("/posts/:id", "/posts/42/edit").is_none());
+        assert!(matches("/posts", "/posts/42").is_none());
+        assert!(matches("/", "/").unwrap().is_empty());
+
+        let params = matches("/files/*path", "/files/a/b.gmi").unwrap();
+        assert_eq!(params.get("path"), Some("a/b.gmi"));
+        let params = matches("/files/*path", "/files").unwrap();
+        assert_eq!(params.get("path"), Some(""));
+        let params = matches("/:user/*rest", "/alice/").unwrap();
+        assert_eq!(
+            params.iter().collect::<Vec<_>>(),
+            [("user", "alice"), ("rest", "")]
+        );
+    }
+
+    #[test]
+    fn test_dispatch() {
+        let router = Router::new()
+            .route("/", |_: &Request, _: &Params<'_>| ok("index"))
+            .route("/posts/:id", |_: &Request, p: &Params<'_>| {
+                ok(p.get("id").unwrap())
+            });
+
+        assert_eq!(router.dispatch(&request("gemini://a.com/")).1, b"index");
+        assert_eq!(
+            router.dispatch(&request("gemini://a.com/posts/7?x")).1,
+            b"7"
+        );
+
+        let (res, body) = router.dispatch(&request("gemini://a.com/nope"));
+        assert_eq!(res.status, Some(StatusCode::NotFound));
+        assert!(body.is_empty());
+        let (res, _) = router.dispatch(&Request::new());
+        assert_eq!(res.status, Some(StatusCode::BadRequest));
+
+        let router = router.fallback(|_: &Request, _: &Params<'_>| ok("fallback"));
+        assert_eq!(
+            router.dispatch(&request("gemini://a.com/nope")).1,
+            b"fallback"
+        );
+    }
+}
@@ -0,0 +1,353 @@
+use crate::gemtext::{Block, Document};
+use crate::meta::MetaString;
+use crate::Url;
+use crate::{Request, Response, ResponseRef, Status, StatusCode};
+use alloc::{string::String, vec::Vec};
+use core::{fmt, marker::PhantomData};
+use serde::de::{self, Deserialize, Deserializer, EnumAccess, MapAccess, SeqAccess, VariantAccess};
+use serde::ser::{Serialize, SerializeStruct, SerializeStructVariant, Serializer};
+
+macro_rules! fields {
+    ($name:ident, $visitor:ident, $expecting:expr, { $($field:ident: $ty:ty),* $(,)? }) => {
+        struct $name {
+            $($field: $ty,)*
+        }
+
+        impl $name {
+            const FIELDS: &'static [&'static str] = &[$(stringify!($field)),*];
+        }
+
+        struct $visitor;
+
+        impl<'de> de::Visitor<'de> for $visitor {
+            type Value = $name;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str($expecting)
+            }
+
+            fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<$name, A::Error> {
+                let mut len = 0;
+                $(
+                    let $field = seq
+                        .next_element()?
+                        .ok_or_else(|| de::Error::invalid_length(len, &self))?;
+                    len += 1;
+                )*
+                let _ = len;
+                Ok($name { $($field,)* })
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<$name, A::Error> {
+                $(let mut $field = None;)*
+                while let Some(key) = map.next_key::<String>()? {
+                    match key.as_str() {
+                        $(stringify!($field) => $field = Some(map.next_value()?),)*
+                        _ => {
+                            map.next_value::<de::IgnoredAny>()?;
+                        }
+                    }
+                }
+                Ok($name {
+                    $($field: $field.ok_or_else(|| de::Error::missing_field(stringify!($field)))?,)*
+                })
+            }
+        }
+    };
+}
+
+fields!(RequestFields, RequestVisitor, "struct Request", { url: Option<UrlDef> });
+fields!(ResponseFields, ResponseVisitor, "struct Response", {
+    status: Option<StatusCode>,
+    meta: Option<MetaString>,
+});
+fields!(DocumentFields, DocumentVisitor, "struct Document", { blocks: Vec<Block> });
+fields!(LinkFields, LinkVisitor, "struct variant Block::Link", {
+    url: String,
+    label: Option<String>,
+});
+fields!(HeadingFields, HeadingVisitor, "struct variant Block::Heading", {
+    level: u8,
+    text: String,
+});
+fields!(PreformattedFields, PreformattedVisitor, "struct variant Block::Preformatted", {
+    alt: Option<String>,
+    lines: Vec<String>,
+});
+fields!(TableFields, TableVisitor, "struct variant Block::Table", {
+    rows: Vec<Vec<String>>,
+    header: bool,
+    source: Vec<Block>,
+});
+
+struct UrlDef(Url);
+
+impl<'de> Deserialize<'de> for UrlDef {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        Url::parse(&s).map(UrlDef).map_err(de::Error::custom)
+    }
+}
+
+#[cfg(feature = "small-meta")]
+impl Serialize for MetaString {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self)
+    }
+}
+
+#[cfg(feature = "small-meta")]
+impl<'de> Deserialize<'de> for MetaString {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer).map(MetaString::from)
+    }
+}
+
+impl Serialize for StatusCode {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u16(u16::from(*self))
+    }
+}
+
+impl<'de> Deserialize<'de> for StatusCode {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        u16::deserialize(deserializer).map(StatusCode::from_u16_lenient)
+    }
+}
+
+impl<T: Serialize> Serialize for Status<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Status::Complete(v) => serializer.serialize_newtype_variant("Status", 0, "Complete", v),
+            Status::Partial(needed) => {
+                serializer.serialize_newtype_variant("Status", 1, "Partial", needed)
+            }
+        }
+    }
+}
+
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for Status<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct Visitor<T>(PhantomData<T>);
+
+        impl<'de, T: Deserialize<'de>> de::Visitor<'de> for Visitor<T> {
+            type Value = Status<T>;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("enum Status")
+            }
+
+            fn visit_enum<A: EnumAccess<'de>>(self, data: A) -> Result<Status<T>, A::Error> {
+                let (variant, access): (String, _) = data.variant()?;
+                match variant.as_str() {
+                    "Complete" => access.newtype_variant().map(Status::Complete),
+                    "Partial" => access.newtype_variant().map(Status::Partial),
+                    other => Err(de::Error::unknown_variant(other, STATUS_VARIANTS)),
+                }
+            }
+        }
+
+        deserializer.deserialize_enum("Status", STATUS_VARIANTS, Visitor(PhantomData))
+    }
+}
+
+const STATUS_VARIANTS: &[&str] = &["Complete", "Partial"];
+
+impl Serialize for Request {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut s = serializer.serialize_struct("Request", 1)?;
+        s.serialize_field("url", &self.url.as_ref().map(Url::as_str))?;
+        s.end()
+    }
+}
+
+impl<'de> Deserialize<'de> for Request {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let fields =
+            deserializer.deserialize_struct("Request", RequestFields::FIELDS, RequestVisitor)?;
+        Ok(Request {
+            url: fields.url.map(|url: UrlDef| url.0),
+        })
+    }
+}
+
+impl Serialize for Response {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serialize_response(self.status, self.meta.as_deref(), serializer)
+    }
+}
+
+impl<'a> Serialize for ResponseRef<'a> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serialize_response(self.status, self.meta, serializer)
+    }
+}
+
+fn serialize_response<S: Serializer>(
+    status: Option<StatusCode>,
+    meta: Option<&str>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    let mut s = serializer.serialize_struct("Response", 2)?;
+    s.serialize_field("status", &status)?;
+    s.serialize_field("meta", &meta)?;
+    s.end()
+}
+
+impl<'de> Deserialize<'de> for Response {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let fields =
+            deserializer.deserialize_struct("Response", ResponseFields::FIELDS, ResponseVisitor)?;
+        Ok(Response {
+            status: fields.status,
+            meta: fields.meta,
+        })
+    }
+}
+
+impl Serialize for Document {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut s = serializer.serialize_struct("Document", 1)?;
+        s.serialize_field("blocks", &self.blocks)?;
+        s.end()
+    }
+}
+
+impl<'de> Deserialize<'de> for Document {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let fields =
+            deserializer.deserialize_struct("Document", DocumentFields::FIELDS, DocumentVisitor)?;
+        Ok(Document {
+            blocks: fields.blocks,
+        })
+    }
+}
+
+const BLOCK_VARIANTS: &[&str] = &[
+    "Text",
+    "Link",
+    "Heading",
+    "List",
+    "Quote",
+    "Preformatted",
+    "Table",
+];
+
+impl Serialize for Block {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Block::Text(text) => serializer.serialize_newtype_variant("Block", 0, "Text", text),
+            Block::Link { url, label } => {
+                let mut s = serializer.serialize_struct_variant("Block", 1, "Link", 2)?;
+                s.serialize_field("url", url)?;
+                s.serialize_field("label", label)?;
+                s.end()
+            }
+            Block::Heading { level, text } => {
+                let mut s = serializer.serialize_struct_variant("Block", 2, "Heading", 2)?;
+                s.serialize_field("level", level)?;
+                s.serialize_field("text", text)?;
+                s.end()
+            }
+            Block::List(items) => serializer.serialize_newtype_variant("Block", 3, "List", items),
+            Block::Quote(lines) => serializer.serialize_newtype_variant("Block", 4, "Quote", lines),
+            Block::Preformatted { alt, lines } => {
+                let mut s = serializer.serialize_struct_variant("Block", 5, "Preformatted", 2)?;
+                s.serialize_field("alt", alt)?;
+                s.serialize_field("lines", lines)?;
+                s.end()
+            }
+            Block::Table {
+                rows,
+                header,
+                source,
+            } => {
+                let mut s = serializer.serialize_struct_variant("Block", 6, "Table", 3)?;
+                s.serialize_field("rows", rows)?;
+                s.serialize_field("header", header)?;
+                s.serialize_field("source", source)?;
+                s.end()
+            }
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Block {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct Visitor;
+
+        impl<'de> de::Visitor<'de> for Visitor {
+            type Value = Block;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("enum Block")
+            }
+
+            fn visit_enum<A: EnumAccess<'de>>(self, data: A) -> Result<Block, A::Error> {
+                let (variant, access): (String, _) = data.variant()?;
+                match variant.as_str() {
+                    "Text" => access.newtype_variant().map(Block::Text),
+                    "Link" => {
+                        let f = access.struct_variant(LinkFields::FIELDS, LinkVisitor)?;
+                        Ok(Block::Link {
+                            url: f.url,
+                            label: f.label,
+                        })
+                    }
+                    "Heading" => {
+                        let f = access.struct_variant(HeadingFields::FIELDS, HeadingVisitor)?;
+                        Ok(Block::Heading {
+                            level: f.level,
+                            text: f.text,
+                        })
+                    }
+                    "List" => access.newtype_variant().map(Block::List),
+                    "Quote" => access.newtype_variant().map(Block::Quote),
+                    "Preformatted" => {
+                        let f = access
+                            .struct_variant(PreformattedFields::FIELDS, PreformattedVisitor)?;
+                        Ok(Block::Preformatted {
+                            alt: f.alt,
+                            lines: f.lines,
+                        })
+                    }
+                    "Table" => {
+                        let f = access.struct_variant(TableFields::FIELDS, TableVisitor)?;
+                        Ok(Block::Table {
+                            rows: f.rows,
+                            header: f.header,
+                            source: f.source,
+                        })
+                    }
+                    other => Err(de::Error::unknown_variant(other, BLOCK_VARIANTS)),
+                }
+            }
+        }
+
+        deserializer.deserialize_enum("Block", BLOCK_VARIANTS, Visitor)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use serde::de::value::{Error, MapDeserializer, U16Deserializer};
+    use serde::de::IntoDeserializer;
+
+    #[test]
+    fn test_status_code() {
+        let de: U16Deserializer<Error> = 51u16.into_deserializer();
+        assert_eq!(StatusCode::deserialize(de), Ok(StatusCode::NotFound));
+    }
+
+    #[test]
+    fn test_status() {
+        let de = MapDeserializer::<_, Error>::new(core::iter::once(("Complete", 5usize)));
+        let de = serde::de::value::MapAccessDeserializer::new(de);
+        assert_eq!(Status::<usize>::deserialize(de), Ok(Status::Complete(5)));
+
+        let de = MapDeserializer::<_, Error>::new(core::iter::once(("Partial", ())));
+        let de = serde::de::value::MapAccessDeserializer::new(de);
+        assert_eq!(Status::<usize>::deserialize(de), Ok(Status::Partial(None)));
+    }
+}
@@ -0,0 +1,167 @@
+use crate::{Error, Request, Response, Status, StatusCode};
+use alloc::vec::Vec;
+use core::mem;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Event {
+    RequestReceived(Request),
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum State {
+    ReadRequest,
+    SendHeader,
+    SendBody,
+    NoBody,
+    Closed,
+}
+
+#[derive(Debug)]
+pub struct Connection {
+    state: State,
+    transmit: Vec<u8>,
+    buf: Vec<u8>,
+}
+
+impl Default for Connection {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Connection {
+    #[inline]
+    pub fn new() -> Self {
+        Self {
+            state: State::ReadRequest,
+            transmit: Vec::new(),
+            buf: Vec::new(),
+        }
+    }
+
+    pub fn feed(&mut self, data: &[u8]) -> Result<Vec<Event>, Error> {
+        let mut events = Vec::new();
+        if self.state != State::ReadRequest {
+            return Ok(events);
+        }
+
+        self.buf.extend_from_slice(data);
+        let mut req = Request::new();
+        match req.parse(&self.buf)? {
+            Status::Complete(_) => {}
+            Status::Partial(_) => return Ok(events),
+        }
+
+        self.state = State::SendHeader;
+        self.buf = Vec::new();
+        events.push(Event::RequestReceived(req));
+        Ok(events)
+    }
+
+    pub fn send_header(&mut self, res: &Response) -> Result<(), Error> {
+        if self.state != State::SendHeader {
+            return Err(Error::ConnectionState);
+        }
+        res.encode(&mut self.transmit)?;
+        self.state = if res.status.is_some_and(StatusCode::is_success) {
+            State::SendBody
+        } else {
+            State::NoBody
+        };
+        Ok(())
+    }
+
+    pub fn send_body(&mut self, data: &[u8]) -> Result<(), Error> {
+        match self.state {
+            State::SendBody => {
+                self.transmit.extend_from_slice(data);
+                Ok(())
+            }
+            State::NoBody => Err(Error::UnexpectedBody),
+            _ => Err(Error::ConnectionState),
+        }
+    }
+
+    pub fn poll_transmit(&mut self) -> Option<Vec<u8>> {
+        if self.transmit.is_empty() {
+            None
+        } else {
+            Some(mem::take(&mut self.transmit))
+        }
+    }
+
+    pub fn finish(&mut self) -> Result<(), Error> {
+        match self.state {
+            State::ReadRequest | State::SendHeader => Err(Error::ConnectionState),
+            State::SendBody | State::NoBody | State::Closed => {
+                self.state = State::Closed;
+                Ok(())
+            }
+        }
+    }
+
+    #[inline]
+    pub fn is_closed(&self) -> bool {
+        self.state == State::Closed
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::Url;
+    use alloc::vec;
+
+    #[test]
+    fn test_transaction() {
+        let mut conn = Connection::new();
+        assert_eq!(
+            conn.send_header(&Response::new()),
+            Err(Error::ConnectionState)
+        );
+        assert_eq!(conn.feed(b"gemini://exa"), Ok(vec![]));
+        let events = conn.feed(b"mple.com/\r\n").unwrap();
+        assert_eq!(
+            events,
+            vec![Event::RequestReceived(Request {
+                url: Some(Url::parse("gemini://example.com/").unwrap()),
+            })]
+        );
+        assert_eq!(conn.feed(b"extra"), Ok(vec![]));
+        assert_eq!(conn.send_body(b"early"), Err(Error::ConnectionState));
+        assert_eq!(conn.poll_transmit(), None);
+
+        let res = Response {
+            status: Some(StatusCode::Success),
+            meta: Some("text/gemini".into()),
+        };
+        conn.send_header(&res).unwrap();
+        assert_eq!(conn.send_header(&res), Err(Error::ConnectionState));
+        conn.send_body(b"# Hi\n").unwrap();
+        assert_eq!(
+            conn.poll_transmit(),
+            Some(b"20 text/gemini\r\n# Hi\n".to_vec())
+        );
+        assert_eq!(conn.finish(), Ok(()));
+        assert!(conn.is_closed());
+        assert_eq!(conn.send_body(b"late"), Err(Error::ConnectionState));
+    }
+
+    #[test]
+    fn test_no_body_on_failure() {
+        let mut conn = Connection::new();
+        assert_eq!(conn.finish(), Err(Error::ConnectionState));
+        conn.feed(b"gemini://example.com/missing\r\n").unwrap();
+        let res = Response {
+            status: Some(StatusCode::NotFound),
+            meta: Some("Not found".into()),
+        };
+        conn.send_header(&res).unwrap();
+        assert_eq!(conn.send_body(b"body"), Err(Error::UnexpectedBody));
+        assert_eq!(conn.poll_transmit(), Some(b"51 Not found\r\n".to_vec()));
+        assert!(!conn.is_closed());
+        assert_eq!(conn.finish(), Ok(()));
+        assert!(conn.is_closed());
+    }
+}
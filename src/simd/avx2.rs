@@ -0,0 +1,21 @@
+use crate::iter;
+use core::arch::x86_64::*;
+
+#[target_feature(enable = "avx2")]
+pub unsafe fn find_eol(haystack: &[u8]) -> Option<usize> {
+    let cr = _mm256_set1_epi8(b'\r' as i8);
+    let lf = _mm256_set1_epi8(b'\n' as i8);
+
+    let mut offset = 0;
+    while offset + 32 <= haystack.len() {
+        let v = _mm256_loadu_si256(haystack.as_ptr().add(offset) as *const __m256i);
+        let eq = _mm256_or_si256(_mm256_cmpeq_epi8(v, cr), _mm256_cmpeq_epi8(v, lf));
+        let mask = _mm256_movemask_epi8(eq);
+        if mask != 0 {
+            return Some(offset + mask.trailing_zeros() as usize);
+        }
+        offset += 32;
+    }
+
+    iter::find_eol(&haystack[offset..]).map(|i| offset + i)
+}
@@ -0,0 +1,128 @@
+use crate::iter;
+
+#[cfg(all(
+    not(feature = "safe"),
+    target_arch = "x86_64",
+    any(feature = "std", test, target_feature = "avx2")
+))]
+mod avx2;
+#[cfg(all(
+    not(feature = "safe"),
+    target_arch = "aarch64",
+    target_feature = "neon"
+))]
+mod neon;
+#[cfg(all(
+    not(feature = "safe"),
+    target_arch = "x86_64",
+    any(feature = "std", test, not(target_feature = "avx2"))
+))]
+mod sse2;
+
+#[cfg(all(not(feature = "safe"), target_arch = "x86_64", feature = "std"))]
+mod runtime {
+    use core::sync::atomic::{AtomicU8, Ordering};
+
+    const INIT: u8 = 0;
+    const SSE2: u8 = 1;
+    const AVX2: u8 = 2;
+
+    static FEATURE: AtomicU8 = AtomicU8::new(INIT);
+
+    #[inline]
+    pub fn find_eol(haystack: &[u8]) -> Option<usize> {
+        let feature = match FEATURE.load(Ordering::Relaxed) {
+            INIT => {
+                let feature = if std::is_x86_feature_detected!("avx2") {
+                    AVX2
+                } else {
+                    SSE2
+                };
+                FEATURE.store(feature, Ordering::Relaxed);
+                feature
+            }
+            feature => feature,
+        };
+
+        unsafe {
+            match feature {
+                AVX2 => super::avx2::find_eol(haystack),
+                _ => super::sse2::find_eol(haystack),
+            }
+        }
+    }
+}
+
+#[inline]
+pub fn find_eol(haystack: &[u8]) -> Option<usize> {
+    if haystack.len() < 16 {
+        return iter::find_eol(haystack);
+    }
+
+    #[cfg(all(not(feature = "safe"), target_arch = "x86_64", feature = "std"))]
+    return runtime::find_eol(haystack);
+
+    #[cfg(all(
+        not(feature = "safe"),
+        target_arch = "x86_64",
+        not(feature = "std"),
+        target_feature = "avx2"
+    ))]
+    return unsafe { avx2::find_eol(haystack) };
+
+    #[cfg(all(
+        not(feature = "safe"),
+        target_arch = "x86_64",
+        not(feature = "std"),
+        not(target_feature = "avx2")
+    ))]
+    return unsafe { sse2::find_eol(haystack) };
+
+    #[cfg(all(
+        not(feature = "safe"),
+        target_arch = "aarch64",
+        target_feature = "neon"
+    ))]
+    return unsafe { neon::find_eol(haystack) };
+
+    #[allow(unreachable_code)]
+    iter::find_eol(haystack)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use alloc::vec;
+
+    fn check(f: fn(&[u8]) -> Option<usize>) {
+        for len in [0, 1, 15, 16, 17, 31, 32, 33, 64, 100] {
+            let buf = vec![b'a'; len];
+            assert_eq!(f(&buf), None);
+            for i in 0..len {
+                for eol in [b'\r', b'\n'] {
+                    let mut buf = vec![0x8d; len];
+                    buf[i] = eol;
+                    if i + 1 < len {
+                        buf[len - 1] = b'\n';
+                    }
+                    assert_eq!(f(&buf), Some(i), "len {} at {}", len, i);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_find_eol() {
+        check(find_eol);
+        check(iter::find_eol);
+    }
+
+    #[cfg(all(not(feature = "safe"), target_arch = "x86_64"))]
+    #[test]
+    fn test_find_eol_x86() {
+        check(|h| unsafe { sse2::find_eol(h) });
+        if std::is_x86_feature_detected!("avx2") {
+            check(|h| unsafe { avx2::find_eol(h) });
+        }
+    }
+}
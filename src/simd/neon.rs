@@ -0,0 +1,22 @@
+use crate::iter;
+use core::arch::aarch64::*;
+
+#[target_feature(enable = "neon")]
+pub unsafe fn find_eol(haystack: &[u8]) -> Option<usize> {
+    let cr = vdupq_n_u8(b'\r');
+    let lf = vdupq_n_u8(b'\n');
+
+    let mut offset = 0;
+    while offset + 16 <= haystack.len() {
+        let v = vld1q_u8(haystack.as_ptr().add(offset));
+        let eq = vorrq_u8(vceqq_u8(v, cr), vceqq_u8(v, lf));
+        if vmaxvq_u8(eq) != 0 {
+            let nibbles = vshrn_n_u16::<4>(vreinterpretq_u16_u8(eq));
+            let mask = vget_lane_u64::<0>(vreinterpret_u64_u8(nibbles));
+            return Some(offset + (mask.trailing_zeros() / 4) as usize);
+        }
+        offset += 16;
+    }
+
+    iter::find_eol(&haystack[offset..]).map(|i| offset + i)
+}
@@ -0,0 +1,21 @@
+use crate::iter;
+use core::arch::x86_64::*;
+
+#[target_feature(enable = "sse2")]
+pub unsafe fn find_eol(haystack: &[u8]) -> Option<usize> {
+    let cr = _mm_set1_epi8(b'\r' as i8);
+    let lf = _mm_set1_epi8(b'\n' as i8);
+
+    let mut offset = 0;
+    while offset + 16 <= haystack.len() {
+        let v = _mm_loadu_si128(haystack.as_ptr().add(offset) as *const __m128i);
+        let eq = _mm_or_si128(_mm_cmpeq_epi8(v, cr), _mm_cmpeq_epi8(v, lf));
+        let mask = _mm_movemask_epi8(eq);
+        if mask != 0 {
+            return Some(offset + mask.trailing_zeros() as usize);
+        }
+        offset += 16;
+    }
+
+    iter::find_eol(&haystack[offset..]).map(|i| offset + i)
+}
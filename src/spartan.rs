@@ -0,0 +1,189 @@
+use crate::iter::Bytes;
+use crate::util::{parse_decimal, push_decimal};
+use crate::{
+    next_line_limit, skip_empty_lines, Error, Result, Status, META_MAX_LENGTH, URL_MAX_LENGTH,
+};
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::{result, str};
+
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Request {
+    pub host: Option<String>,
+    pub path: Option<String>,
+    pub content_length: Option<usize>,
+}
+
+impl Request {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn parse(&mut self, buf: &[u8]) -> Result<usize> {
+        let mut bytes = Bytes::new(buf);
+        complete!(skip_empty_lines(&mut bytes));
+
+        let start = bytes.pos;
+        let end = complete!(next_line_limit(
+            &mut bytes,
+            URL_MAX_LENGTH,
+            Error::RequestLine
+        ));
+        let line = str::from_utf8(&buf[start..end])?;
+
+        let mut parts = line.split(' ');
+        let (host, path, len) = match (parts.next(), parts.next(), parts.next(), parts.next()) {
+            (Some(host), Some(path), Some(len), None) => (host, path, len),
+            _ => return Err(Error::RequestLine),
+        };
+        if host.is_empty() || !path.starts_with('/') {
+            return Err(Error::RequestLine);
+        }
+
+        let len = parse_decimal(len).ok_or(Error::RequestLine)?;
+
+        self.host = Some(String::from(host));
+        self.path = Some(String::from(path));
+        self.content_length = Some(len);
+
+        Ok(Status::Complete(bytes.pos))
+    }
+
+    pub fn encode(&self, buf: &mut Vec<u8>) -> result::Result<(), Error> {
+        let host = self.host.as_deref().ok_or(Error::EmptyRequest)?;
+        let path = self.path.as_deref().unwrap_or("/");
+        if host.is_empty() || !path.starts_with('/') || has_space(host) || has_space(path) {
+            return Err(Error::RequestLine);
+        }
+
+        buf.extend_from_slice(host.as_bytes());
+        buf.push(b' ');
+        buf.extend_from_slice(path.as_bytes());
+        buf.push(b' ');
+        push_decimal(buf, self.content_length.unwrap_or(0));
+        buf.extend_from_slice(b"\r\n");
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Response {
+    pub status: Option<u8>,
+    pub meta: Option<String>,
+}
+
+impl Response {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn parse(&mut self, buf: &[u8]) -> Result<usize> {
+        let mut bytes = Bytes::new(buf);
+        let status = expect!(bytes.next() == b'2'..=b'5' => Err(Error::Status)) - b'0';
+        expect!(bytes.next() == b' ' => Err(Error::MissingSpaceAfterStatus));
+
+        let start = bytes.pos;
+        let end = complete!(next_line_limit(
+            &mut bytes,
+            META_MAX_LENGTH,
+            Error::MetaTooLong
+        ));
+        let meta = str::from_utf8(&buf[start..end])?;
+
+        self.status = Some(status);
+        self.meta = Some(String::from(meta));
+        Ok(Status::Complete(bytes.pos))
+    }
+
+    pub fn encode(&self, buf: &mut Vec<u8>) -> result::Result<(), Error> {
+        let status = self.status.ok_or(Error::Status)?;
+        if !(2..=5).contains(&status) {
+            return Err(Error::Status);
+        }
+
+        let meta = self.meta.as_deref().unwrap_or("");
+        if meta.len() > META_MAX_LENGTH || meta.bytes().any(|b| b == b'\r' || b == b'\n') {
+            return Err(Error::ResponseHeader);
+        }
+
+        buf.push(b'0' + status);
+        buf.push(b' ');
+        buf.extend_from_slice(meta.as_bytes());
+        buf.extend_from_slice(b"\r\n");
+        Ok(())
+    }
+
+    #[inline]
+    pub fn is_success(&self) -> bool {
+        self.status == Some(2)
+    }
+
+    #[inline]
+    pub fn is_redirect(&self) -> bool {
+        self.status == Some(3)
+    }
+}
+
+#[inline]
+fn has_space(s: &str) -> bool {
+    s.bytes().any(|b| b == b' ' || b == b'\r' || b == b'\n')
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_request() {
+        let buf = b"example.com /upload 5\r\nhello";
+        let mut req = Request::new();
+        assert_eq!(req.parse(buf), Ok(Status::Complete(23)));
+        assert_eq!(req.host.as_deref(), Some("example.com"));
+        assert_eq!(req.path.as_deref(), Some("/upload"));
+        assert_eq!(req.content_length, Some(5));
+
+        let mut out = Vec::new();
+        req.encode(&mut out).unwrap();
+        assert_eq!(out, &buf[..23]);
+
+        let mut req = Request::new();
+        assert_eq!(req.parse(b"example.com / 0"), Ok(Status::Partial(Some(1))));
+        assert_eq!(req.parse(b"example.com /\r\n"), Err(Error::RequestLine));
+        assert_eq!(req.parse(b"example.com x 0\r\n"), Err(Error::RequestLine));
+        assert_eq!(req.parse(b"example.com / -1\r\n"), Err(Error::RequestLine));
+        assert_eq!(req.parse(b"a / 0 0\r\n"), Err(Error::RequestLine));
+        assert_eq!(req.parse(b"a / +5\r\n"), Err(Error::RequestLine));
+        assert_eq!(req, Request::new());
+
+        let mut long = Vec::from(&b"a /"[..]);
+        long.resize(URL_MAX_LENGTH + 1, b'x');
+        assert_eq!(req.parse(&long), Err(Error::RequestLine));
+    }
+
+    #[test]
+    fn test_response() {
+        let mut res = Response::new();
+        assert_eq!(res.parse(b"2 text/gemini\r\n"), Ok(Status::Complete(15)));
+        assert_eq!(res.status, Some(2));
+        assert_eq!(res.meta.as_deref(), Some("text/gemini"));
+        assert!(res.is_success());
+
+        let mut out = Vec::new();
+        res.encode(&mut out).unwrap();
+        assert_eq!(out, b"2 text/gemini\r\n");
+
+        assert_eq!(res.parse(b"3 /new"), Ok(Status::Partial(Some(1))));
+        assert_eq!(
+            res.parse(b"20 text/gemini\r\n"),
+            Err(Error::MissingSpaceAfterStatus)
+        );
+        assert_eq!(res.parse(b"1 x\r\n"), Err(Error::Status));
+        assert_eq!(res.status, Some(2));
+        assert_eq!(res.meta.as_deref(), Some("text/gemini"));
+
+        res.status = Some(7);
+        assert_eq!(res.encode(&mut Vec::new()), Err(Error::Status));
+    }
+}
@@ -0,0 +1,245 @@
+use crate::Error;
+use core::fmt;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum StatusCode {
+    Input,
+    SensitiveInput,
+    Success,
+    RedirectTemporary,
+    RedirectPermanent,
+    TemporaryFailure,
+    ServerUnavailable,
+    CgiError,
+    ProxyError,
+    SlowDown,
+    PermanentFailure,
+    NotFound,
+    Gone,
+    ProxyRequestRefused,
+    BadRequest,
+    CertificateRequired,
+    CertificateNotAuthorized,
+    CertificateNotValid,
+    Other(u16),
+}
+
+impl TryFrom<u16> for StatusCode {
+    type Error = Error;
+
+    fn try_from(code: u16) -> Result<Self, Error> {
+        if !(10..=69).contains(&code) {
+            return Err(Error::Status);
+        }
+        Ok(Self::from_u16_lenient(code))
+    }
+}
+
+impl StatusCode {
+    pub const INPUT: StatusCode = StatusCode::Input;
+    pub const SENSITIVE_INPUT: StatusCode = StatusCode::SensitiveInput;
+    pub const SUCCESS: StatusCode = StatusCode::Success;
+    pub const REDIRECT_TEMPORARY: StatusCode = StatusCode::RedirectTemporary;
+    pub const REDIRECT_PERMANENT: StatusCode = StatusCode::RedirectPermanent;
+    pub const TEMPORARY_FAILURE: StatusCode = StatusCode::TemporaryFailure;
+    pub const SERVER_UNAVAILABLE: StatusCode = StatusCode::ServerUnavailable;
+    pub const CGI_ERROR: StatusCode = StatusCode::CgiError;
+    pub const PROXY_ERROR: StatusCode = StatusCode::ProxyError;
+    pub const SLOW_DOWN: StatusCode = StatusCode::SlowDown;
+    pub const PERMANENT_FAILURE: StatusCode = StatusCode::PermanentFailure;
+    pub const NOT_FOUND: StatusCode = StatusCode::NotFound;
+    pub const GONE: StatusCode = StatusCode::Gone;
+    pub const PROXY_REQUEST_REFUSED: StatusCode = StatusCode::ProxyRequestRefused;
+    pub const BAD_REQUEST: StatusCode = StatusCode::BadRequest;
+    pub const CERTIFICATE_REQUIRED: StatusCode = StatusCode::CertificateRequired;
+    pub const CERTIFICATE_NOT_AUTHORIZED: StatusCode = StatusCode::CertificateNotAuthorized;
+    pub const CERTIFICATE_NOT_VALID: StatusCode = StatusCode::CertificateNotValid;
+
+    #[inline]
+    pub fn from_u16(code: u16) -> Result<Self, Error> {
+        Self::try_from(code)
+    }
+
+    pub fn from_u16_lenient(code: u16) -> Self {
+        match code {
+            10 => StatusCode::Input,
+            11 => StatusCode::SensitiveInput,
+            20 => StatusCode::Success,
+            30 => StatusCode::RedirectTemporary,
+            31 => StatusCode::RedirectPermanent,
+            40 => StatusCode::TemporaryFailure,
+            41 => StatusCode::ServerUnavailable,
+            42 => StatusCode::CgiError,
+            43 => StatusCode::ProxyError,
+            44 => StatusCode::SlowDown,
+            50 => StatusCode::PermanentFailure,
+            51 => StatusCode::NotFound,
+            52 => StatusCode::Gone,
+            53 => StatusCode::ProxyRequestRefused,
+            59 => StatusCode::BadRequest,
+            60 => StatusCode::CertificateRequired,
+            61 => StatusCode::CertificateNotAuthorized,
+            62 => StatusCode::CertificateNotValid,
+            code => StatusCode::Other(code),
+        }
+    }
+
+    pub const fn as_u16(self) -> u16 {
+        match self {
+            StatusCode::Input => 10,
+            StatusCode::SensitiveInput => 11,
+            StatusCode::Success => 20,
+            StatusCode::RedirectTemporary => 30,
+            StatusCode::RedirectPermanent => 31,
+            StatusCode::TemporaryFailure => 40,
+            StatusCode::ServerUnavailable => 41,
+            StatusCode::CgiError => 42,
+            StatusCode::ProxyError => 43,
+            StatusCode::SlowDown => 44,
+            StatusCode::PermanentFailure => 50,
+            StatusCode::NotFound => 51,
+            StatusCode::Gone => 52,
+            StatusCode::ProxyRequestRefused => 53,
+            StatusCode::BadRequest => 59,
+            StatusCode::CertificateRequired => 60,
+            StatusCode::CertificateNotAuthorized => 61,
+            StatusCode::CertificateNotValid => 62,
+            StatusCode::Other(code) => code,
+        }
+    }
+
+    pub fn reason(self) -> Option<&'static str> {
+        Some(match self {
+            StatusCode::Input => "INPUT",
+            StatusCode::SensitiveInput => "SENSITIVE INPUT",
+            StatusCode::Success => "SUCCESS",
+            StatusCode::RedirectTemporary => "REDIRECT - TEMPORARY",
+            StatusCode::RedirectPermanent => "REDIRECT - PERMANENT",
+            StatusCode::TemporaryFailure => "TEMPORARY FAILURE",
+            StatusCode::ServerUnavailable => "SERVER UNAVAILABLE",
+            StatusCode::CgiError => "CGI ERROR",
+            StatusCode::ProxyError => "PROXY ERROR",
+            StatusCode::SlowDown => "SLOW DOWN",
+            StatusCode::PermanentFailure => "PERMANENT FAILURE",
+            StatusCode::NotFound => "NOT FOUND",
+            StatusCode::Gone => "GONE",
+            StatusCode::ProxyRequestRefused => "PROXY REQUEST REFUSED",
+            StatusCode::BadRequest => "BAD REQUEST",
+            StatusCode::CertificateRequired => "CLIENT CERTIFICATE REQUIRED",
+            StatusCode::CertificateNotAuthorized => "CERTIFICATE NOT AUTHORISED",
+            StatusCode::CertificateNotValid => "CERTIFICATE NOT VALID",
+            StatusCode::Other(_) => return None,
+        })
+    }
+
+    pub fn class(self) -> Option<StatusClass> {
+        match u16::from(self) / 10 {
+            1 => Some(StatusClass::Input),
+            2 => Some(StatusClass::Success),
+            3 => Some(StatusClass::Redirect),
+            4 => Some(StatusClass::TemporaryFailure),
+            5 => Some(StatusClass::PermanentFailure),
+            6 => Some(StatusClass::ClientCertificateRequired),
+            _ => None,
+        }
+    }
+
+    #[inline]
+    pub fn is_input(self) -> bool {
+        self.class() == Some(StatusClass::Input)
+    }
+
+    #[inline]
+    pub fn is_success(self) -> bool {
+        self.class() == Some(StatusClass::Success)
+    }
+
+    #[inline]
+    pub fn is_redirect(self) -> bool {
+        self.class() == Some(StatusClass::Redirect)
+    }
+
+    #[inline]
+    pub fn is_temporary_failure(self) -> bool {
+        self.class() == Some(StatusClass::TemporaryFailure)
+    }
+
+    #[inline]
+    pub fn is_permanent_failure(self) -> bool {
+        self.class() == Some(StatusClass::PermanentFailure)
+    }
+
+    #[inline]
+    pub fn is_client_cert_required(self) -> bool {
+        self.class() == Some(StatusClass::ClientCertificateRequired)
+    }
+
+    pub fn canonical(self) -> Self {
+        match self {
+            StatusCode::Other(_) => self.class().map_or(self, StatusCode::from),
+            _ => self,
+        }
+    }
+
+    pub fn certificate_requirement(self) -> Option<CertificateRequirement> {
+        match self {
+            StatusCode::CertificateNotAuthorized => Some(CertificateRequirement::NotAuthorized),
+            StatusCode::CertificateNotValid => Some(CertificateRequirement::NotValid),
+            _ if self.is_client_cert_required() => Some(CertificateRequirement::Required),
+            _ => None,
+        }
+    }
+}
+
+impl From<StatusCode> for u16 {
+    #[inline]
+    fn from(code: StatusCode) -> Self {
+        code.as_u16()
+    }
+}
+
+impl fmt::Display for StatusCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.reason() {
+            Some(reason) => write!(f, "{} {}", self.as_u16(), reason),
+            None => write!(f, "{}", self.as_u16()),
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum StatusClass {
+    Input,
+    Success,
+    Redirect,
+    TemporaryFailure,
+    PermanentFailure,
+    ClientCertificateRequired,
+}
+
+impl From<StatusClass> for StatusCode {
+    fn from(class: StatusClass) -> Self {
+        match class {
+            StatusClass::Input => StatusCode::Input,
+            StatusClass::Success => StatusCode::Success,
+            StatusClass::Redirect => StatusCode::RedirectTemporary,
+            StatusClass::TemporaryFailure => StatusCode::TemporaryFailure,
+            StatusClass::PermanentFailure => StatusCode::PermanentFailure,
+            StatusClass::ClientCertificateRequired => StatusCode::CertificateRequired,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum CertificateRequirement {
+    Required,
+    NotAuthorized,
+    NotValid,
+}
+
+impl CertificateRequirement {
+    #[inline]
+    pub fn should_present(self) -> bool {
+        self != CertificateRequirement::NotAuthorized
+    }
+}
@@ -0,0 +1,236 @@
+use crate::iter::Bytes;
+use crate::meta::is_token;
+use crate::util::{parse_decimal, push_decimal};
+use crate::Url;
+use crate::{next_line_limit, skip_empty_lines, Error, Result, Status, URL_MAX_LENGTH};
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::{result, str};
+#[cfg(feature = "std")]
+use std::io;
+
+const REQUEST_MAX_LENGTH: usize = URL_MAX_LENGTH + 1024;
+
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct TitanRequest {
+    pub url: Option<Url>,
+    pub token: Option<String>,
+    pub mime: Option<String>,
+    pub size: Option<usize>,
+}
+
+impl TitanRequest {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn parse(&mut self, buf: &[u8]) -> Result<usize> {
+        let mut bytes = Bytes::new(buf);
+        complete!(skip_empty_lines(&mut bytes));
+
+        let start = bytes.pos;
+        let end = complete!(next_line_limit(
+            &mut bytes,
+            REQUEST_MAX_LENGTH - 2,
+            Error::UriTooLong
+        ));
+        let line = str::from_utf8(&buf[start..end])?;
+
+        let (url, params) = match line.find(';') {
+            Some(i) => (&line[..i], &line[i + 1..]),
+            None => (line, ""),
+        };
+
+        let mut token = None;
+        let mut mime = None;
+        let mut size = None;
+        for param in params.split(';').filter(|p| !p.is_empty()) {
+            let (key, value) = param.split_once('=').ok_or(Error::TitanParam)?;
+            match key {
+                "token" => token = Some(String::from(value)),
+                "mime" => mime = Some(String::from(value)),
+                "size" => size = Some(parse_decimal(value).ok_or(Error::TitanParam)?),
+                _ => {}
+            }
+        }
+
+        let size = size.ok_or(Error::TitanParam)?;
+        self.url = Some(Url::parse(url)?);
+        self.token = token;
+        self.mime = mime;
+        self.size = Some(size);
+
+        Ok(Status::Complete(bytes.pos))
+    }
+
+    pub fn encode(&self, buf: &mut Vec<u8>) -> result::Result<(), Error> {
+        let url = self.url.as_ref().ok_or(Error::EmptyRequest)?.as_str();
+        if !url.starts_with("titan:") {
+            return Err(Error::Scheme);
+        }
+        if url.len() > URL_MAX_LENGTH {
+            return Err(Error::UriTooLong);
+        }
+        if url.contains(';') {
+            return Err(Error::TitanParam);
+        }
+        let size = self.size.ok_or(Error::TitanParam)?;
+        let token = self.token.as_deref();
+        if token
+            .is_some_and(|t| t.is_empty() || t.bytes().any(|b| b == b';' || !b.is_ascii_graphic()))
+        {
+            return Err(Error::TitanParam);
+        }
+        let mime = self.mime.as_deref();
+        if let Some(mime) = mime {
+            match mime.split_once('/') {
+                Some((ty, subtype)) if is_token(ty) && is_token(subtype) => {}
+                _ => return Err(Error::MediaType),
+            }
+        }
+
+        let mut line = Vec::with_capacity(url.len() + 32);
+        line.extend_from_slice(url.as_bytes());
+        if let Some(token) = token {
+            line.extend_from_slice(b";token=");
+            line.extend_from_slice(token.as_bytes());
+        }
+        if let Some(mime) = mime {
+            line.extend_from_slice(b";mime=");
+            line.extend_from_slice(mime.as_bytes());
+        }
+        line.extend_from_slice(b";size=");
+        push_decimal(&mut line, size);
+        if line.len() + 2 > REQUEST_MAX_LENGTH {
+            return Err(Error::UriTooLong);
+        }
+
+        buf.extend_from_slice(&line);
+        buf.extend_from_slice(b"\r\n");
+        Ok(())
+    }
+
+    #[cfg(feature = "std")]
+    pub fn write_to<W: io::Write>(&self, w: &mut W) -> io::Result<()> {
+        let mut buf = Vec::new();
+        self.encode(&mut buf).map_err(crate::invalid_input)?;
+        w.write_all(&buf)
+    }
+
+    #[cfg(feature = "std")]
+    pub fn upload<W: io::Write, R: io::Read>(&self, w: &mut W, body: R) -> io::Result<u64> {
+        self.write_to(w)?;
+
+        let size = self.size.unwrap_or(0) as u64;
+        let written = io::copy(&mut body.take(size), w)?;
+        if written < size {
+            return Err(Error::BodyTooShort.into());
+        }
+        Ok(written)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_titan_parse() {
+        let buf = b"titan://example.com/a.gmi;mime=text/gemini;token=t0k;size=5\r\nhello";
+        let mut req = TitanRequest::new();
+        let pos = match req.parse(buf) {
+            Ok(Status::Complete(pos)) => pos,
+            res => panic!("{:?}", res),
+        };
+        assert_eq!(&buf[pos..], b"hello");
+        assert_eq!(req.url.unwrap().as_str(), "titan://example.com/a.gmi");
+        assert_eq!(req.token.as_deref(), Some("t0k"));
+        assert_eq!(req.mime.as_deref(), Some("text/gemini"));
+        assert_eq!(req.size, Some(5));
+
+        let mut req = TitanRequest::new();
+        assert_eq!(
+            req.parse(b"titan://a.com/;size=5"),
+            Ok(Status::Partial(Some(1)))
+        );
+        assert_eq!(
+            req.parse(b"titan://a.com/;mime=text/plain\r\n"),
+            Err(Error::TitanParam)
+        );
+        assert_eq!(
+            req.parse(b"titan://a.com/;size=x\r\n"),
+            Err(Error::TitanParam)
+        );
+        assert_eq!(
+            req.parse(b"titan://a.com/;size\r\n"),
+            Err(Error::TitanParam)
+        );
+        assert_eq!(
+            req.parse(b"titan://a.com/;size=+5\r\n"),
+            Err(Error::TitanParam)
+        );
+
+        let mut long = Vec::from(&b"titan://a.com/;token="[..]);
+        long.resize(REQUEST_MAX_LENGTH, b'x');
+        assert_eq!(req.parse(&long), Err(Error::UriTooLong));
+    }
+
+    #[test]
+    fn test_titan_encode() {
+        let mut req = TitanRequest {
+            url: Some(Url::parse("titan://example.com/a.gmi").unwrap()),
+            token: Some(String::from("t0k")),
+            mime: Some(String::from("text/gemini")),
+            size: Some(5),
+        };
+        let mut out = Vec::new();
+        req.encode(&mut out).unwrap();
+        assert_eq!(
+            out,
+            b"titan://example.com/a.gmi;token=t0k;mime=text/gemini;size=5\r\n"
+        );
+
+        let mut parsed = TitanRequest::new();
+        assert_eq!(parsed.parse(&out), Ok(Status::Complete(out.len())));
+        assert_eq!(parsed, req);
+
+        let mut out = Vec::from(&b"keep"[..]);
+        req.token = Some(String::from("a;b"));
+        assert_eq!(req.encode(&mut out), Err(Error::TitanParam));
+        assert_eq!(out, b"keep");
+        req.token = Some("a".repeat(REQUEST_MAX_LENGTH));
+        assert_eq!(req.encode(&mut out), Err(Error::UriTooLong));
+        assert_eq!(out, b"keep");
+        req.token = None;
+        req.mime = Some(String::from("text"));
+        assert_eq!(req.encode(&mut Vec::new()), Err(Error::MediaType));
+        req.mime = None;
+        req.size = None;
+        assert_eq!(req.encode(&mut Vec::new()), Err(Error::TitanParam));
+        req.size = Some(0);
+        req.url = Some(Url::parse("gemini://example.com/").unwrap());
+        assert_eq!(req.encode(&mut Vec::new()), Err(Error::Scheme));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_titan_upload() {
+        let req = TitanRequest {
+            url: Some(Url::parse("titan://example.com/a.txt").unwrap()),
+            size: Some(5),
+            ..TitanRequest::new()
+        };
+        let mut out = Vec::new();
+        assert_eq!(req.upload(&mut out, &b"hello"[..]).unwrap(), 5);
+        assert_eq!(out, b"titan://example.com/a.txt;size=5\r\nhello");
+
+        let err = req.upload(&mut Vec::new(), &b"hi"[..]).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+        let mut out = Vec::new();
+        let mut body = &b"hello!"[..];
+        assert_eq!(req.upload(&mut out, &mut body).unwrap(), 5);
+        assert!(out.ends_with(b"\r\nhello"));
+        assert_eq!(body, b"!");
+    }
+}
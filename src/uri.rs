@@ -0,0 +1,518 @@
+use crate::UrlParseError as ParseErrorKind;
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+#[cfg(not(feature = "url"))]
+use core::error;
+use core::{fmt, ops::Range, result, str::FromStr};
+use percent_encoding::{utf8_percent_encode, AsciiSet, CONTROLS};
+
+const ESCAPE: &AsciiSet = &CONTROLS.add(b' ').add(b'"').add(b'<').add(b'>').add(b'`');
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+}
+
+impl Span {
+    #[inline]
+    pub fn new(start: usize, end: usize) -> Self {
+        Self { start, end }
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.end - self.start
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.start == self.end
+    }
+
+    #[inline]
+    pub fn get<'a>(&self, s: &'a str) -> &'a str {
+        &s[self.start..self.end]
+    }
+}
+
+impl From<Span> for Range<usize> {
+    #[inline]
+    fn from(span: Span) -> Self {
+        span.start..span.end
+    }
+}
+
+#[cfg(not(feature = "url"))]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum ParseError {
+    EmptyHost,
+    InvalidPort,
+    InvalidIpv6Address,
+    InvalidDomainCharacter,
+    RelativeUrlWithoutBase,
+}
+
+#[cfg(not(feature = "url"))]
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            ParseError::EmptyHost => "empty host",
+            ParseError::InvalidPort => "invalid port number",
+            ParseError::InvalidIpv6Address => "invalid IPv6 address",
+            ParseError::InvalidDomainCharacter => "invalid domain character",
+            ParseError::RelativeUrlWithoutBase => "relative URL without a base",
+        })
+    }
+}
+
+#[cfg(not(feature = "url"))]
+impl error::Error for ParseError {}
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub(crate) struct Parts {
+    pub scheme: Span,
+    pub userinfo: Option<Span>,
+    pub host: Option<Span>,
+    pub port: Option<Span>,
+    pub path: Span,
+    pub query: Option<Span>,
+    pub fragment: Option<Span>,
+}
+
+pub(crate) fn split(s: &str) -> result::Result<Parts, ParseErrorKind> {
+    let (scheme, parts) = split_reference(s)?;
+    if scheme.is_none() {
+        return Err(ParseErrorKind::RelativeUrlWithoutBase);
+    }
+    Ok(parts)
+}
+
+pub(crate) fn split_reference(s: &str) -> result::Result<(Option<Span>, Parts), ParseErrorKind> {
+    let b = s.as_bytes();
+    let mut parts = Parts::default();
+    let mut pos = 0;
+
+    let colon = find(b, 0, |c| matches!(c, b':' | b'/' | b'?' | b'#'));
+    let scheme = if b.get(colon) == Some(&b':') {
+        let valid = b[..colon].first().is_some_and(u8::is_ascii_alphabetic)
+            && b[..colon]
+                .iter()
+                .all(|&c| c.is_ascii_alphanumeric() || matches!(c, b'+' | b'-' | b'.'));
+        if !valid {
+            return Err(ParseErrorKind::RelativeUrlWithoutBase);
+        }
+        parts.scheme = Span::new(0, colon);
+        pos = colon + 1;
+        Some(parts.scheme)
+    } else {
+        None
+    };
+    if b.iter().any(|&c| c <= b' ' || c == 0x7f) {
+        return Err(ParseErrorKind::InvalidDomainCharacter);
+    }
+
+    if b[pos..].starts_with(b"//") {
+        pos += 2;
+        let end = find(b, pos, |c| matches!(c, b'/' | b'?' | b'#'));
+        let mut host_start = pos;
+        if let Some(at) = b[pos..end].iter().rposition(|&c| c == b'@') {
+            parts.userinfo = Some(Span::new(pos, pos + at));
+            host_start = pos + at + 1;
+        }
+
+        let host_end = if b.get(host_start) == Some(&b'[') {
+            let close = b[host_start..end]
+                .iter()
+                .position(|&c| c == b']')
+                .ok_or(ParseErrorKind::InvalidIpv6Address)?;
+            host_start + close + 1
+        } else {
+            find(b, host_start, |c| c == b':').min(end)
+        };
+        parts.host = Some(Span::new(host_start, host_end));
+
+        if host_end < end {
+            if b[host_end] != b':' {
+                return Err(ParseErrorKind::InvalidPort);
+            }
+            let port = Span::new(host_end + 1, end);
+            if !b[port.start..port.end].iter().all(u8::is_ascii_digit) {
+                return Err(ParseErrorKind::InvalidPort);
+            }
+            if s[port.start..port.end].parse::<u16>().is_err() && !port.is_empty() {
+                return Err(ParseErrorKind::InvalidPort);
+            }
+            parts.port = Some(port);
+        }
+        pos = end;
+    }
+
+    let end = find(b, pos, |c| matches!(c, b'?' | b'#'));
+    parts.path = Span::new(pos, end);
+    pos = end;
+
+    if b.get(pos) == Some(&b'?') {
+        let end = find(b, pos + 1, |c| c == b'#');
+        parts.query = Some(Span::new(pos + 1, end));
+        pos = end;
+    }
+    if b.get(pos) == Some(&b'#') {
+        parts.fragment = Some(Span::new(pos + 1, b.len()));
+    }
+
+    Ok((scheme, parts))
+}
+
+#[inline]
+fn find(b: &[u8], from: usize, f: impl Fn(u8) -> bool) -> usize {
+    b[from..]
+        .iter()
+        .position(|&c| f(c))
+        .map_or(b.len(), |i| from + i)
+}
+
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct Uri {
+    serialization: String,
+    parts: Parts,
+}
+
+impl Uri {
+    pub fn parse(input: &str) -> result::Result<Self, ParseErrorKind> {
+        let input = input.trim_matches(|c: char| c <= ' ');
+        let mut serialization = utf8_percent_encode(input, ESCAPE).to_string();
+        let parts = split(&serialization)?;
+        serialization[..parts.scheme.end].make_ascii_lowercase();
+        if let Some(host) = parts.host {
+            serialization[host.start..host.end].make_ascii_lowercase();
+        }
+        Ok(Self {
+            serialization,
+            parts,
+        })
+    }
+
+    #[inline]
+    pub fn as_str(&self) -> &str {
+        &self.serialization
+    }
+
+    #[inline]
+    pub fn scheme(&self) -> &str {
+        self.parts.scheme.get(&self.serialization)
+    }
+
+    #[inline]
+    pub fn username(&self) -> &str {
+        match self.parts.userinfo {
+            Some(info) => info
+                .get(&self.serialization)
+                .split(':')
+                .next()
+                .unwrap_or(""),
+            None => "",
+        }
+    }
+
+    #[inline]
+    pub fn password(&self) -> Option<&str> {
+        let info = self.parts.userinfo?.get(&self.serialization);
+        info.split_once(':').map(|(_, password)| password)
+    }
+
+    #[inline]
+    pub fn host_str(&self) -> Option<&str> {
+        self.parts.host.map(|host| host.get(&self.serialization))
+    }
+
+    #[inline]
+    pub fn port(&self) -> Option<u16> {
+        self.parts.port?.get(&self.serialization).parse().ok()
+    }
+
+    pub fn port_or_known_default(&self) -> Option<u16> {
+        self.port().or(match self.scheme() {
+            "http" | "ws" => Some(80),
+            "https" | "wss" => Some(443),
+            "ftp" => Some(21),
+            _ => None,
+        })
+    }
+
+    #[inline]
+    pub fn path(&self) -> &str {
+        self.parts.path.get(&self.serialization)
+    }
+
+    #[inline]
+    pub fn query(&self) -> Option<&str> {
+        self.parts.query.map(|q| q.get(&self.serialization))
+    }
+
+    #[inline]
+    pub fn fragment(&self) -> Option<&str> {
+        self.parts.fragment.map(|f| f.get(&self.serialization))
+    }
+
+    pub fn set_query(&mut self, query: Option<&str>) {
+        let fragment = self.fragment().map(String::from);
+        let mut s = String::from(&self.serialization[..self.parts.path.end]);
+        if let Some(query) = query {
+            s.push('?');
+            s.push_str(query);
+        }
+        if let Some(fragment) = fragment {
+            s.push('#');
+            s.push_str(&fragment);
+        }
+        *self = Self::parse(&s).unwrap_or_else(|_| self.clone());
+    }
+
+    pub fn set_fragment(&mut self, fragment: Option<&str>) {
+        let end = match self.parts.fragment {
+            Some(f) => f.start - 1,
+            None => self.serialization.len(),
+        };
+        self.serialization.truncate(end);
+        self.parts.fragment = None;
+        if let Some(fragment) = fragment {
+            self.serialization.push('#');
+            let start = self.serialization.len();
+            self.serialization.push_str(fragment);
+            self.parts.fragment = Some(Span::new(start, self.serialization.len()));
+        }
+    }
+
+    pub fn join(&self, input: &str) -> result::Result<Self, ParseErrorKind> {
+        let (scheme, r) = split_reference(input)?;
+        if scheme.is_some() {
+            let path = remove_dot_segments(r.path.get(input));
+            return Self::parse(&assemble(input, &r, &path, r.query.map(|q| q.get(input))));
+        }
+
+        let base = &self.serialization;
+        let mut target = String::from(self.scheme());
+        target.push(':');
+
+        let (authority, path, query) = if r.host.is_some() {
+            let path = remove_dot_segments(r.path.get(input));
+            let authority = &input[..r.path.start];
+            (authority, path, r.query.map(|q| q.get(input)))
+        } else {
+            let authority = match self.parts.host {
+                Some(_) => &base[self.parts.scheme.end + 1..self.parts.path.start],
+                None => "",
+            };
+            let rpath = r.path.get(input);
+            if rpath.is_empty() {
+                let query = r.query.map(|q| q.get(input)).or(self.query());
+                (authority, String::from(self.path()), query)
+            } else if rpath.starts_with('/') {
+                (
+                    authority,
+                    remove_dot_segments(rpath),
+                    r.query.map(|q| q.get(input)),
+                )
+            } else {
+                let merged = match self.path().rfind('/') {
+                    Some(i) => format!("{}{}", &self.path()[..=i], rpath),
+                    None if self.parts.host.is_some() => format!("/{}", rpath),
+                    None => String::from(rpath),
+                };
+                (
+                    authority,
+                    remove_dot_segments(&merged),
+                    r.query.map(|q| q.get(input)),
+                )
+            }
+        };
+
+        target.push_str(authority);
+        target.push_str(&path);
+        if let Some(query) = query {
+            target.push('?');
+            target.push_str(query);
+        }
+        if let Some(fragment) = r.fragment {
+            target.push('#');
+            target.push_str(fragment.get(input));
+        }
+        Self::parse(&target)
+    }
+}
+
+fn assemble(input: &str, parts: &Parts, path: &str, query: Option<&str>) -> String {
+    let mut s = String::from(&input[..parts.path.start]);
+    s.push_str(path);
+    if let Some(query) = query {
+        s.push('?');
+        s.push_str(query);
+    }
+    if let Some(fragment) = parts.fragment {
+        s.push('#');
+        s.push_str(fragment.get(input));
+    }
+    s
+}
+
+fn remove_dot_segments(path: &str) -> String {
+    let mut out: Vec<&str> = Vec::new();
+    let absolute = path.starts_with('/');
+    let segments: Vec<&str> = path.split('/').collect();
+    let last = segments.len() - 1;
+
+    for (i, segment) in segments.iter().enumerate() {
+        if absolute && i == 0 {
+            continue;
+        }
+        match *segment {
+            "." => {
+                if i == last {
+                    out.push("");
+                }
+            }
+            ".." => {
+                out.pop();
+                if i == last {
+                    out.push("");
+                }
+            }
+            segment => out.push(segment),
+        }
+    }
+
+    let joined = out.join("/");
+    if absolute {
+        format!("/{}", joined)
+    } else {
+        joined
+    }
+}
+
+impl FromStr for Uri {
+    type Err = ParseErrorKind;
+
+    #[inline]
+    fn from_str(s: &str) -> result::Result<Self, ParseErrorKind> {
+        Self::parse(s)
+    }
+}
+
+impl fmt::Display for Uri {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.serialization)
+    }
+}
+
+impl fmt::Debug for Uri {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&self.serialization, f)
+    }
+}
+
+impl AsRef<str> for Uri {
+    #[inline]
+    fn as_ref(&self) -> &str {
+        &self.serialization
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_split() {
+        let s = "gemini://user@example.com:1965/a/b?q=1#frag";
+        let parts = split(s).unwrap();
+        assert_eq!(parts.scheme.get(s), "gemini");
+        assert_eq!(parts.userinfo.unwrap().get(s), "user");
+        assert_eq!(parts.host.unwrap().get(s), "example.com");
+        assert_eq!(parts.port.unwrap().get(s), "1965");
+        assert_eq!(parts.path.get(s), "/a/b");
+        assert_eq!(parts.query.unwrap().get(s), "q=1");
+        assert_eq!(parts.fragment.unwrap().get(s), "frag");
+
+        let s = "gemini://[::1]?x";
+        let parts = split(s).unwrap();
+        assert_eq!(parts.host.unwrap().get(s), "[::1]");
+        assert_eq!(parts.port, None);
+        assert!(parts.path.is_empty());
+        assert_eq!(parts.query.unwrap().get(s), "x");
+
+        let s = "mailto:a@b.c";
+        let parts = split(s).unwrap();
+        assert_eq!(parts.host, None);
+        assert_eq!(parts.path.get(s), "a@b.c");
+
+        assert!(split("/relative").is_err());
+        assert!(split("1http://a").is_err());
+        assert!(split("gemini://a:b/").is_err());
+        assert!(split("gemini://a:99999/").is_err());
+        assert!(split("gemini://a b/").is_err());
+    }
+
+    #[test]
+    fn test_uri() {
+        let uri = Uri::parse("GEMINI://u:p@Example.COM:1966/a?q#f").unwrap();
+        assert_eq!(uri.as_str(), "gemini://u:p@example.com:1966/a?q#f");
+        assert_eq!(uri.scheme(), "gemini");
+        assert_eq!((uri.username(), uri.password()), ("u", Some("p")));
+        assert_eq!(uri.host_str(), Some("example.com"));
+        assert_eq!(uri.port(), Some(1966));
+        assert_eq!(uri.path(), "/a");
+        assert_eq!(uri.query(), Some("q"));
+        assert_eq!(uri.fragment(), Some("f"));
+
+        let uri = Uri::parse(" gemini://a.com/a b/\u{e9}\n").unwrap();
+        assert_eq!(uri.as_str(), "gemini://a.com/a%20b/%C3%A9");
+
+        let mut uri = Uri::parse("gemini://a.com/x#f").unwrap();
+        uri.set_query(Some("in%20put"));
+        assert_eq!(uri.as_str(), "gemini://a.com/x?in%20put#f");
+        uri.set_fragment(None);
+        assert_eq!(uri.as_str(), "gemini://a.com/x?in%20put");
+        assert_eq!(uri.fragment(), None);
+        assert_eq!(
+            Uri::parse("https://a.com").unwrap().port_or_known_default(),
+            Some(443)
+        );
+    }
+
+    #[test]
+    fn test_uri_join() {
+        let base = Uri::parse("gemini://a.com/b/c/d;p?q").unwrap();
+        for (reference, expected) in [
+            ("g:h", "g:h"),
+            ("g", "gemini://a.com/b/c/g"),
+            ("./g", "gemini://a.com/b/c/g"),
+            ("g/", "gemini://a.com/b/c/g/"),
+            ("/g", "gemini://a.com/g"),
+            ("//g", "gemini://g"),
+            ("?y", "gemini://a.com/b/c/d;p?y"),
+            ("g?y#s", "gemini://a.com/b/c/g?y#s"),
+            ("#s", "gemini://a.com/b/c/d;p?q#s"),
+            ("", "gemini://a.com/b/c/d;p?q"),
+            (".", "gemini://a.com/b/c/"),
+            ("..", "gemini://a.com/b/"),
+            ("../g", "gemini://a.com/b/g"),
+            ("../../../g", "gemini://a.com/g"),
+            ("/./g", "gemini://a.com/g"),
+            ("g/../h", "gemini://a.com/b/c/h"),
+        ] {
+            assert_eq!(
+                base.join(reference).unwrap().as_str(),
+                expected,
+                "{}",
+                reference
+            );
+        }
+
+        let base = Uri::parse("gemini://a.com").unwrap();
+        assert_eq!(base.join("x").unwrap().as_str(), "gemini://a.com/x");
+    }
+}
@@ -0,0 +1,42 @@
+use alloc::vec::Vec;
+
+pub(crate) fn parse_decimal(s: &str) -> Option<usize> {
+    if s.is_empty() || !s.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    s.parse().ok()
+}
+
+pub(crate) fn push_decimal(buf: &mut Vec<u8>, mut n: usize) {
+    let mut digits = [0; 20];
+    let mut i = digits.len();
+    loop {
+        i -= 1;
+        digits[i] = b'0' + (n % 10) as u8;
+        n /= 10;
+        if n == 0 {
+            break;
+        }
+    }
+    buf.extend_from_slice(&digits[i..]);
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_decimal() {
+        assert_eq!(parse_decimal("0"), Some(0));
+        assert_eq!(parse_decimal("1024"), Some(1024));
+        assert_eq!(parse_decimal(""), None);
+        assert_eq!(parse_decimal("+1"), None);
+        assert_eq!(parse_decimal("99999999999999999999999"), None);
+
+        let mut buf = Vec::new();
+        push_decimal(&mut buf, 0);
+        buf.push(b' ');
+        push_decimal(&mut buf, usize::MAX);
+        assert_eq!(buf, alloc::format!("0 {}", usize::MAX).as_bytes());
+    }
+}